@@ -1,62 +1,163 @@
-use crate::config::AppConfig;
+use crate::config::{default_output_dir, prepare_output_dir, AppConfig};
 use crate::deno_manager::{DenoManager, DenoStatus};
-use crate::network::{self, NetworkInterface};
-use crate::transcription::{EngineInfo, InstallProgress as TranscriptionInstallProgress, TranscribeProgress, TranscriptionModel as TranscriptionModelInfo};
+use crate::error::AppError;
+use crate::ffmpeg_manager::FfmpegManager;
+use crate::logging;
+use crate::media;
+use crate::network::{self, InterfaceTest, NetworkInterface};
+use crate::pipeline::{resolve_download_options, ClipRequest, Download, DownloadRequest, LocalTranscriptionRequest, Pipeline, RemoteTranscriptionRequest, TaskStatus, PipelineObserver, TaskStore, TaskTotals};
+use crate::sherpa_manager::SherpaManager;
+use crate::transcription::{check_nvidia_gpu, diarization, refresh_nvidia_gpu, parse_srt, EngineInfo, SubtitleCue, InstallProgress as TranscriptionInstallProgress, TranscribeProgress, TranscriptionModel as TranscriptionModelInfo, TranscriptionSettings};
 use crate::transcription_manager::TranscriptionManager;
-use crate::whisper::Whisper;
+use crate::whisper::{FfmpegInfo, Whisper};
 use crate::whisper_manager::{InstallProgress as WhisperInstallProgress, WhisperManager, WhisperModel, WhisperStatus};
-use crate::ytdlp::{DownloadOptions, DownloadProgress, VideoInfo, YtDlp};
+use crate::ytdlp::{FormatDetails, SizeEstimate, VideoInfo, YtDlp};
 use crate::ytdlp_manager::{InstallProgress, YtDlpManager, YtDlpStatus};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter, State};
+use tauri_plugin_clipboard_manager::ClipboardExt;
 use tokio::sync::{mpsc, watch, Mutex};
-use uuid::Uuid;
 
+/// Combined progress for a queue of model downloads
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SubtitleSettings {
-    pub enabled: bool,
-    pub engine: String,  // "whisper_rs", "moonshine"
-    pub model: String,
-    pub style: String,   // "word" or "sentence"
+pub struct ModelQueueProgress {
+    pub engine_id: String,
+    pub model_id: String,
+    pub index: usize,              // 0-based position of the current model
+    pub total: usize,              // Number of models in the queue
+    pub model_percentage: f64,     // Progress of the current model
+    pub overall_percentage: f64,   // Progress across the whole queue
+    pub stage: String,
 }
 
+/// Disk used by installed models and binaries, in bytes
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Download {
-    pub id: String,
-    pub url: String,
-    pub title: String,
-    pub thumbnail: Option<String>,
-    pub status: String,
-    pub progress: f64,
-    pub speed: Option<String>,
-    pub eta: Option<String>,
-    pub output_path: Option<String>,
-    pub format: String,
-    pub error: Option<String>,
-    pub duration: Option<f64>,
-    pub whisper_model: Option<String>,
-    pub transcription_engine: Option<String>,
-    pub transcription_progress: Option<f64>,
-    pub transcription_message: Option<String>,
-    pub task_type: String,           // "download" | "local_transcribe"
-    pub source_path: Option<String>, // Input file path for local transcriptions
+pub struct DiskUsage {
+    pub models: HashMap<String, u64>, // Keyed by models subdirectory (engine), "whisper" for loose files
+    pub binaries: u64,
+    pub total: u64,
+}
+
+/// Summary numbers for a dashboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppStats {
+    pub by_status: HashMap<String, usize>, // Tasks currently in the list
+    pub totals: TaskTotals,                // Session totals, kept after tasks are cleared
+    pub disk_usage: Option<DiskUsage>,     // Only computed when requested
+}
+
+/// GPU as seen by the transcription engines
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GpuInfo {
+    pub vendor: Option<String>, // "nvidia" or "apple"; None when no supported GPU was found
+    pub available: bool,
+}
+
+/// Toolchain snapshot for support requests; rendered in the UI and copied as JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diagnostics {
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub offline: bool,
+    pub ytdlp: YtDlpStatus,
+    pub deno: DenoStatus,
+    pub whisper: WhisperStatus,
+    pub sherpa_installed: bool,
+    pub ffmpeg: FfmpegInfo,
+    pub gpu: GpuInfo,
+    pub engines: Vec<EngineInfo>, // Includes each engine's models and whether they're installed
+}
+
+/// Progress for update_all, one step per managed tool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateAllProgress {
+    pub tool: String,      // "yt-dlp", "deno", "whisper" or "sherpa-onnx"
+    pub step: usize,       // 1-based
+    pub total_steps: usize,
+    pub percentage: f64,   // Progress of the current tool's download
+    pub stage: String,
+}
+
+/// What update_all did with each tool
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateSummary {
+    pub updated: Vec<String>,       // "yt-dlp 2025.01.15", tool plus the version now installed
+    pub up_to_date: Vec<String>,
+    pub not_installed: Vec<String>, // Skipped; update_all never installs new tools
+    pub failed: Vec<String>,        // "tool: error"
+}
+
+/// Total size of all files under a path
+fn dir_size(path: &std::path::Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Walk the models and bin directories
+fn compute_disk_usage() -> Result<DiskUsage, String> {
+    let models_dir = WhisperManager::get_models_dir()?;
+    let mut models = HashMap::new();
+    if let Ok(entries) = std::fs::read_dir(&models_dir) {
+        for entry in entries.flatten() {
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            // Engine models live in subdirectories; whisper.cpp models sit at the top level
+            let (key, size) = if meta.is_dir() {
+                (entry.file_name().to_string_lossy().to_string(), dir_size(&entry.path()))
+            } else {
+                ("whisper".to_string(), meta.len())
+            };
+            *models.entry(key).or_insert(0) += size;
+        }
+    }
+
+    let binaries = dir_size(&YtDlpManager::get_bin_dir()?);
+    let total = models.values().sum::<u64>() + binaries;
+
+    Ok(DiskUsage { models, binaries, total })
 }
 
 pub struct AppState {
     pub config: Mutex<AppConfig>,
-    pub downloads: Mutex<HashMap<String, Download>>,
-    pub cancel_senders: Mutex<HashMap<String, watch::Sender<bool>>>,
+    pub tasks: Arc<TaskStore>,
+    pub model_cancel_senders: Mutex<HashMap<String, watch::Sender<bool>>>, // Keyed by model_download_key
+    pub video_info_cache: Mutex<HashMap<String, (Instant, VideoInfo)>>,   // Keyed by URL
 }
 
+/// How long fetched video info is reused before yt-dlp is asked again
+const VIDEO_INFO_TTL: Duration = Duration::from_secs(5 * 60);
+
 impl Default for AppState {
     fn default() -> Self {
+        let mut config = AppConfig::load();
+        network::apply_config(&config);
+        // Checked once at startup, not in load(), which many callers use to read settings;
+        // a removed drive or deleted folder would otherwise fail every download
+        if let Err(e) = prepare_output_dir(&config.output_dir) {
+            log::warn!("{}; falling back to the default download folder", e);
+            config.output_dir = default_output_dir();
+        }
+
         Self {
-            config: Mutex::new(AppConfig::load()),
-            downloads: Mutex::new(HashMap::new()),
-            cancel_senders: Mutex::new(HashMap::new()),
+            config: Mutex::new(config),
+            tasks: Arc::new(TaskStore::default()),
+            model_cancel_senders: Mutex::new(HashMap::new()),
+            video_info_cache: Mutex::new(HashMap::new()),
         }
     }
 }
@@ -66,258 +167,350 @@ pub async fn check_ytdlp() -> Result<bool, String> {
     Ok(YtDlp::check_installed().await)
 }
 
+/// Video info for a URL, reusing a recent fetch unless `refresh` is set
+/// The format picker asks for the same URL several times in a row
+async fn cached_video_info(state: &AppState, url: &str, refresh: bool) -> Result<VideoInfo, AppError> {
+    if !refresh {
+        if let Some((fetched_at, info)) = state.video_info_cache.lock().await.get(url) {
+            if fetched_at.elapsed() < VIDEO_INFO_TTL {
+                return Ok(info.clone());
+            }
+        }
+    }
+
+    let (cookies_browser, credentials) = {
+        let config = state.config.lock().await;
+        (config.cookies_browser.clone(), config.credentials())
+    };
+    let info = YtDlp::get_video_info(url, cookies_browser.as_deref(), &credentials).await?;
+
+    let mut cache = state.video_info_cache.lock().await;
+    cache.retain(|_, (fetched_at, _)| fetched_at.elapsed() < VIDEO_INFO_TTL);
+    cache.insert(url.to_string(), (Instant::now(), info.clone()));
+    Ok(info)
+}
+
 #[tauri::command]
 pub async fn get_video_info(
     state: State<'_, Arc<AppState>>,
     url: String,
-) -> Result<VideoInfo, String> {
-    let cookies_browser = state.config.lock().await.cookies_browser.clone();
-    YtDlp::get_video_info(&url, cookies_browser.as_deref()).await
+    refresh: Option<bool>,
+) -> Result<VideoInfo, AppError> {
+    cached_video_info(&state, &url, refresh.unwrap_or(false)).await
 }
 
+/// List a video's formats grouped for a custom format picker
 #[tauri::command]
-pub async fn start_download(
-    app: AppHandle,
+pub async fn get_format_details(
+    state: State<'_, Arc<AppState>>,
+    url: String,
+) -> Result<FormatDetails, AppError> {
+    let info = cached_video_info(&state, &url, false).await?;
+    Ok(YtDlp::group_formats(&info))
+}
+
+/// Estimate how large a download will be for the given format
+#[tauri::command]
+pub async fn estimate_download_size(
     state: State<'_, Arc<AppState>>,
     url: String,
     format: String,
-    title: String,
-    thumbnail: Option<String>,
-    subtitle_settings: Option<SubtitleSettings>,
-    duration: Option<f64>,
-) -> Result<String, String> {
-    let download_id = Uuid::new_v4().to_string();
+) -> Result<Option<SizeEstimate>, AppError> {
+    let info = cached_video_info(&state, &url, false).await?;
+
+    let quality = format.split_once(':').map_or(format.as_str(), |(q, _)| q);
+    Ok(YtDlp::estimate_size(&info, quality))
+}
+
+/// Show the yt-dlp command a download would run, without starting it
+#[tauri::command]
+pub async fn preview_download_command(
+    state: State<'_, Arc<AppState>>,
+    url: String,
+    format: String,
+    output_container: Option<String>,
+) -> Result<Vec<String>, String> {
     let config = state.config.lock().await;
+    let (options, _) = resolve_download_options(&config, &format, output_container, false, None)?;
+    drop(config);
 
-    // Use per-video subtitle settings if provided, otherwise fall back to global config
-    let (generate_subtitles, transcription_engine, transcription_model, transcription_style) = match &subtitle_settings {
-        Some(settings) => (
-            settings.enabled,
-            settings.engine.clone(),
-            settings.model.clone(),
-            settings.style.clone(),
-        ),
-        None => (
-            config.generate_subtitles,
-            config.transcription_engine.clone(),
-            config.transcription_model.clone(),
-            "sentence".to_string(), // Default style when using global config
-        ),
-    };
+    let mut command = vec![YtDlp::get_command().to_string_lossy().to_string()];
+    let mut args = YtDlp::build_download_args(&url, &options);
+    if let Some(i) = args.iter().position(|a| a == "--password") {
+        if let Some(password) = args.get_mut(i + 1) {
+            *password = "********".to_string();
+        }
+    }
+    command.extend(args);
+    Ok(command)
+}
 
-    let download = Download {
-        id: download_id.clone(),
-        url: url.clone(),
-        title: title.clone(),
-        thumbnail: thumbnail.clone(),
-        status: "pending".to_string(),
-        progress: 0.0,
-        speed: None,
-        eta: None,
-        output_path: None,
-        format: format.clone(),
-        error: None,
-        duration,
-        whisper_model: if generate_subtitles { Some(transcription_model.clone()) } else { None },
-        transcription_engine: if generate_subtitles { Some(transcription_engine.clone()) } else { None },
-        transcription_progress: None,
-        transcription_message: None,
-        task_type: "download".to_string(),
-        source_path: None,
-    };
+/// Set the site password for this session; it is never written to disk
+#[tauri::command]
+pub async fn set_auth_password(
+    state: State<'_, Arc<AppState>>,
+    password: Option<String>,
+) -> Result<(), String> {
+    let mut config = state.config.lock().await;
+    config.auth_password = password.filter(|p| !p.is_empty());
+    state.video_info_cache.lock().await.clear();
+    Ok(())
+}
 
-    state.downloads.lock().await.insert(download_id.clone(), download.clone());
+/// Where to POST tasks that reach a terminal status
+#[derive(Clone)]
+struct CompletionWebhook {
+    url: String,
+    secret: Option<String>,
+}
 
-    // Parse format string - can be "quality" or "quality:container"
-    let (quality, container) = if format.contains(':') {
-        let parts: Vec<&str> = format.split(':').collect();
-        (parts[0].to_string(), Some(parts[1].to_string()))
-    } else {
-        (format.clone(), None)
-    };
+impl CompletionWebhook {
+    fn from_config(config: &AppConfig) -> Option<Self> {
+        let url = config.completion_webhook.as_deref()?.trim();
+        if url.is_empty() {
+            return None;
+        }
+        Some(Self {
+            url: url.to_string(),
+            secret: config.completion_webhook_secret.clone().filter(|s| !s.is_empty()),
+        })
+    }
 
-    let format_string = YtDlp::get_format_presets()
-        .get(&quality)
-        .cloned()
-        .unwrap_or_else(|| quality.clone());
-
-    // Determine container format
-    let is_audio_only = quality == "audio" || quality == "mp3";
-    let container_format = match &container {
-        Some(c) if c == "original" => None, // Original format doesn't need remux
-        Some(c) => Some(c.clone()),
-        None if is_audio_only => None, // Audio-only doesn't need container
-        None => Some(config.default_format.clone()),
-    };
+    /// Best-effort POST of the task as JSON; failures are only logged
+    fn fire(&self, task: Download) {
+        let webhook = self.clone();
+        tokio::spawn(async move {
+            let mut request = network::http_client()
+                .post(&webhook.url)
+                .header("User-Agent", "Zinc-App")
+                .timeout(std::time::Duration::from_secs(10))
+                .json(&task);
+            if let Some(secret) = &webhook.secret {
+                request = request.bearer_auth(secret);
+            }
 
-    let options = DownloadOptions {
-        format: format_string,
-        output_dir: config.output_dir.clone(),
-        filename_template: None,
-        container_format,
-        generate_subtitles,
-        whisper_model: Some(transcription_model.clone()),
-        source_address: config.network_interface.clone(),
-        cookies_browser: config.cookies_browser.clone(),
-    };
+            match request.send().await {
+                Ok(response) if response.status().is_success() => {}
+                Ok(response) => log::warn!(
+                    "Completion webhook for {} returned status {}",
+                    task.id,
+                    response.status()
+                ),
+                Err(e) => log::warn!("Completion webhook for {} failed: {}", task.id, e),
+            }
+        });
+    }
+}
 
-    drop(config);
+/// A post_download_command that runs longer than this is killed
+const POST_DOWNLOAD_HOOK_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Template placeholders and the environment variables holding their values
+const HOOK_PLACEHOLDERS: [(&str, &str); 3] = [
+    ("{path}", "ZINC_PATH"),
+    ("{title}", "ZINC_TITLE"),
+    ("{status}", "ZINC_STATUS"),
+];
+
+/// User shell command run when a task finishes (config.post_download_command)
+///
+/// It runs with the user's full privileges, so it should only ever be a command the
+/// user wrote. Titles come from websites, so {path}, {title} and {status} are never
+/// pasted into the command text: they expand to references to the ZINC_PATH,
+/// ZINC_TITLE and ZINC_STATUS environment variables, quoted to suit where the
+/// placeholder sits, so the shell never re-parses a value
+#[derive(Clone)]
+struct PostDownloadHook {
+    template: String,
+    tasks: Arc<TaskStore>,
+}
 
-    let (progress_tx, mut progress_rx) = mpsc::channel::<DownloadProgress>(100);
-    let (cancel_tx, cancel_rx) = watch::channel(false);
+impl PostDownloadHook {
+    fn from_config(config: &AppConfig, tasks: &Arc<TaskStore>) -> Option<Self> {
+        let template = config.post_download_command.as_deref()?.trim();
+        if template.is_empty() {
+            return None;
+        }
+        Some(Self {
+            template: template.to_string(),
+            tasks: Arc::clone(tasks),
+        })
+    }
 
-    // Store the cancel sender so we can signal cancellation later
-    state.cancel_senders.lock().await.insert(download_id.clone(), cancel_tx);
+    fn command_line(&self) -> String {
+        hook_command_line(&self.template, cfg!(target_os = "windows"))
+    }
 
-    let app_clone = app.clone();
-    let download_id_clone = download_id.clone();
-    let state_clone = Arc::clone(&state.inner());
-
-    tokio::spawn(async move {
-        while let Some(progress) = progress_rx.recv().await {
-            let mut downloads = state_clone.downloads.lock().await;
-            if let Some(download) = downloads.get_mut(&progress.download_id) {
-                download.progress = progress.progress;
-                download.speed = progress.speed.clone();
-                download.eta = progress.eta.clone();
-                download.status = progress.status.clone();
-                if let Some(filename) = &progress.filename {
-                    download.output_path = Some(filename.clone());
+    /// Run the command in the background; its output goes to the task log and a
+    /// failure is only logged, never reflected in the task's status
+    fn fire(&self, task: Download) {
+        let hook = self.clone();
+        tokio::spawn(async move {
+            let lines = match hook.run(&task).await {
+                Ok(lines) => lines,
+                Err(e) => {
+                    log::warn!("Post-download command for {} failed: {}", task.id, e);
+                    vec![format!("post_download_command failed: {}", e)]
                 }
+            };
+            hook.tasks.append_log(&task.id, lines).await;
+        });
+    }
 
-                let _ = app_clone.emit("download-progress", download.clone());
+    async fn run(&self, task: &Download) -> Result<Vec<String>, String> {
+        // cmd can't escape a quote inside a quoted %VAR%, so values lose theirs on Windows
+        let value = |s: &str| {
+            if cfg!(target_os = "windows") {
+                s.replace('"', "'")
+            } else {
+                s.to_string()
             }
+        };
+
+        #[cfg(target_os = "windows")]
+        let mut cmd = {
+            let mut cmd = tokio::process::Command::new("cmd");
+            cmd.arg("/C").raw_arg(self.command_line());
+            cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+            cmd
+        };
+        #[cfg(not(target_os = "windows"))]
+        let mut cmd = {
+            let mut cmd = tokio::process::Command::new("sh");
+            cmd.arg("-c").arg(self.command_line());
+            cmd
+        };
+
+        cmd.env("ZINC_PATH", value(task.output_path.as_deref().unwrap_or_default()))
+            .env("ZINC_TITLE", value(&task.title))
+            .env("ZINC_STATUS", task.status.name())
+            .env("ZINC_TASK_ID", &task.id)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true);
+
+        let output = tokio::time::timeout(POST_DOWNLOAD_HOOK_TIMEOUT, cmd.output())
+            .await
+            .map_err(|_| format!("timed out after {}s", POST_DOWNLOAD_HOOK_TIMEOUT.as_secs()))?
+            .map_err(|e| format!("could not start: {}", e))?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let mut lines: Vec<String> = stdout
+            .lines()
+            .chain(stderr.lines())
+            .filter(|line| !line.trim().is_empty())
+            .map(String::from)
+            .collect();
+        if !output.status.success() {
+            log::warn!("Post-download command for {} exited with {}", task.id, output.status);
+            lines.push(format!("post_download_command exited with {}", output.status));
         }
-    });
+        Ok(lines)
+    }
+}
 
-    let state_clone = Arc::clone(&state.inner());
-    let app_clone = app.clone();
+/// The template with each placeholder swapped for a variable reference that stays quoted
+/// whether the user wrote it bare (`{path}`) or inside their own quotes (`"{path}"`,
+/// `"D:\nas\{title}.mp4"`): quotes the user opened are tracked rather than doubled
+fn hook_command_line(template: &str, windows: bool) -> String {
+    let reference = |var: &str, quote: Option<char>| match (windows, quote) {
+        (true, Some(_)) => format!("%{}%", var),
+        (true, None) => format!("\"%{}%\"", var),
+        (false, Some('"')) => format!("${{{}}}", var),
+        // Nothing expands inside '...': close it, expand quoted, reopen
+        (false, Some(_)) => format!("'\"${{{}}}\"'", var),
+        (false, None) => format!("\"${{{}}}\"", var),
+    };
 
-    tokio::spawn(async move {
-        {
-            let mut downloads = state_clone.downloads.lock().await;
-            if let Some(download) = downloads.get_mut(&download_id_clone) {
-                download.status = "downloading".to_string();
-                let _ = app_clone.emit("download-progress", download.clone());
-            }
+    let mut command = String::with_capacity(template.len());
+    let mut quote: Option<char> = None;
+    let mut chars = template.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if let Some((placeholder, var)) = HOOK_PLACEHOLDERS.iter().find(|(p, _)| template[i..].starts_with(p)) {
+            command.push_str(&reference(var, quote));
+            chars.nth(placeholder.len() - 2);
+            continue;
         }
-
-        let cancel_rx_for_transcription = cancel_rx.clone();
-        match YtDlp::start_download(&url, options.clone(), progress_tx, download_id_clone.clone(), cancel_rx).await {
-            Ok(path) => {
-                let path_str = path.to_string_lossy().to_string();
-
-                // Check if subtitle generation is enabled
-                log::info!("Download completed. generate_subtitles={}, is_audio_only={}", options.generate_subtitles, is_audio_only);
-                if options.generate_subtitles && !is_audio_only {
-                    // Update status to transcribing
-                    {
-                        let mut downloads = state_clone.downloads.lock().await;
-                        if let Some(download) = downloads.get_mut(&download_id_clone) {
-                            download.status = "transcribing".to_string();
-                            download.output_path = Some(path_str.clone());
-                            let _ = app_clone.emit("download-progress", download.clone());
-                        }
-                    }
-
-                    // Create progress channel for transcription
-                    let (transcribe_tx, mut transcribe_rx) = mpsc::channel::<TranscribeProgress>(100);
-
-                    let app_for_transcribe = app_clone.clone();
-                    let download_id_for_transcribe = download_id_clone.clone();
-                    let state_for_transcribe = state_clone.clone();
-
-                    // Spawn task to forward transcription progress
-                    tokio::spawn(async move {
-                        while let Some(progress) = transcribe_rx.recv().await {
-                            // Skip the "complete" stage - we handle completion in the main flow
-                            if progress.stage == "complete" {
-                                continue;
-                            }
-                            let mut downloads = state_for_transcribe.downloads.lock().await;
-                            if let Some(download) = downloads.get_mut(&download_id_for_transcribe) {
-                                // Don't overwrite if already completed
-                                if download.status == "completed" {
-                                    continue;
-                                }
-                                download.status = format!("transcribing:{}", progress.stage);
-                                download.transcription_progress = Some(progress.progress);
-                                download.transcription_message = Some(progress.message.clone());
-                                let _ = app_for_transcribe.emit("transcribe-progress", &progress);
-                                let _ = app_for_transcribe.emit("download-progress", download.clone());
-                            }
-                        }
-                    });
-
-                    let video_path = PathBuf::from(&path_str);
-                    let transcription_manager = TranscriptionManager::new();
-
-                    log::info!("Starting transcription for: {:?} with engine: {}, model: {}, style: {}",
-                        video_path, transcription_engine, transcription_model, transcription_style);
-
-                    match transcription_manager.process_video(
-                        &video_path,
-                        &transcription_engine,
-                        &transcription_model,
-                        None, // Language is auto-detected by all engines
-                        &transcription_style,
-                        transcribe_tx,
-                        cancel_rx_for_transcription,
-                    ).await {
-                        Ok(result) => {
-                            log::info!("Transcription successful: {:?}", result);
-                            let mut downloads = state_clone.downloads.lock().await;
-                            if let Some(download) = downloads.get_mut(&download_id_clone) {
-                                download.status = "completed".to_string();
-                                download.progress = 100.0;
-                                log::info!("Emitting download-progress with status: completed for id: {}", download.id);
-                                let emit_result = app_clone.emit("download-progress", download.clone());
-                                log::info!("Emit result: {:?}", emit_result);
-                            } else {
-                                log::warn!("Download not found in state after transcription: {}", download_id_clone);
-                            }
-                        }
-                        Err(e) => {
-                            // Transcription failed, but download succeeded
-                            // Mark as completed with a warning
-                            log::error!("Transcription failed: {}", e);
-                            let mut downloads = state_clone.downloads.lock().await;
-                            if let Some(download) = downloads.get_mut(&download_id_clone) {
-                                download.status = "completed".to_string();
-                                download.progress = 100.0;
-                                download.error = Some(format!("Subtitle generation failed: {}", e));
-                                let _ = app_clone.emit("download-progress", download.clone());
-                            }
-                        }
-                    }
-                } else {
-                    let mut downloads = state_clone.downloads.lock().await;
-                    if let Some(download) = downloads.get_mut(&download_id_clone) {
-                        download.status = "completed".to_string();
-                        download.progress = 100.0;
-                        download.output_path = Some(path_str);
-                        let _ = app_clone.emit("download-progress", download.clone());
-                    }
+        command.push(c);
+        match (c, quote) {
+            ('"', None) => quote = Some('"'),
+            ('\'', None) if !windows => quote = Some('\''),
+            ('"', Some('"')) | ('\'', Some('\'')) => quote = None,
+            // sh: a backslash outside '...' makes the next character literal
+            ('\\', q) if !windows && q != Some('\'') => {
+                if let Some((_, escaped)) = chars.next() {
+                    command.push(escaped);
                 }
             }
-            Err(e) => {
-                let mut downloads = state_clone.downloads.lock().await;
-                if let Some(download) = downloads.get_mut(&download_id_clone) {
-                    // Only set error status if not already cancelled
-                    if download.status != "cancelled" {
-                        download.status = "error".to_string();
-                        download.error = Some(e);
-                    }
-                    let _ = app_clone.emit("download-progress", download.clone());
-                }
+            _ => {}
+        }
+    }
+    command
+}
+
+/// Forwards pipeline updates to the frontend as Tauri events
+struct TauriObserver {
+    app: AppHandle,
+    webhook: Option<CompletionWebhook>,
+    hook: Option<PostDownloadHook>,
+}
+
+impl PipelineObserver for TauriObserver {
+    fn task_updated(&self, task: &Download) {
+        let _ = self.app.emit("download-progress", task.clone());
+    }
+
+    fn task_finished(&self, task: &Download) {
+        if let Some(webhook) = self.webhook.as_ref().filter(|_| !network::is_offline()) {
+            if matches!(task.status, TaskStatus::Completed | TaskStatus::Error) {
+                webhook.fire(task.clone());
             }
         }
+        if let Some(hook) = &self.hook {
+            hook.fire(task.clone());
+        }
+    }
 
-        // Clean up cancel sender
-        state_clone.cancel_senders.lock().await.remove(&download_id_clone);
-    });
+    fn transcribe_progress(&self, progress: &TranscribeProgress) {
+        let _ = self.app.emit("transcribe-progress", progress);
+    }
+}
+
+/// Pipeline over the app's task store that reports through Tauri events
+async fn pipeline(app: &AppHandle, state: &AppState) -> Pipeline {
+    let config = state.config.lock().await;
+    let observer = TauriObserver {
+        app: app.clone(),
+        webhook: CompletionWebhook::from_config(&config),
+        hook: PostDownloadHook::from_config(&config, &state.tasks),
+    };
+    drop(config);
+    Pipeline::new(Arc::clone(&state.tasks), Arc::new(observer))
+}
+
+/// How long child processes get to exit after cancellation when the window closes
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(3);
+
+/// Stop running tasks and clean up their partial files before the app exits
+pub async fn shutdown(app: &AppHandle, state: &AppState) {
+    let pipeline = pipeline(app, state).await;
+    if pipeline.has_active_tasks().await {
+        log::info!("Window closing with active tasks, stopping them");
+        pipeline.shutdown(SHUTDOWN_GRACE).await;
+    }
+}
 
-    Ok(download_id)
+#[tauri::command]
+pub async fn start_download(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    request: DownloadRequest,
+) -> Result<String, AppError> {
+    let pipeline = pipeline(&app, &state).await;
+    let config = state.config.lock().await;
+    pipeline.start_download(&config, request).await
 }
 
 #[tauri::command]
@@ -326,30 +519,197 @@ pub async fn cancel_download(
     state: State<'_, Arc<AppState>>,
     download_id: String,
 ) -> Result<(), String> {
-    // Send cancellation signal to the download task
-    if let Some(cancel_tx) = state.cancel_senders.lock().await.get(&download_id) {
-        let _ = cancel_tx.send(true);
+    pipeline(&app, &state).await.cancel(&download_id).await;
+    Ok(())
+}
+
+/// Stop every running and pending task. Returns how many were stopped
+#[tauri::command]
+pub async fn cancel_all(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+) -> Result<usize, String> {
+    Ok(pipeline(&app, &state).await.cancel_all().await)
+}
+
+#[tauri::command]
+pub async fn get_downloads(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<Download>, String> {
+    let downloads = state.tasks.downloads.lock().await;
+    Ok(downloads.values().cloned().collect())
+}
+
+/// Task counts and session totals; disk usage is only scanned when include_disk_usage is set
+#[tauri::command]
+pub async fn get_app_stats(
+    state: State<'_, Arc<AppState>>,
+    include_disk_usage: Option<bool>,
+) -> Result<AppStats, String> {
+    let mut by_status = HashMap::new();
+    for download in state.tasks.downloads.lock().await.values() {
+        // Fold "transcribing:<stage>" into one bucket
+        *by_status.entry(download.status.name().to_string()).or_insert(0) += 1;
     }
 
-    // Update the download status
-    let mut downloads = state.downloads.lock().await;
-    if let Some(download) = downloads.get_mut(&download_id) {
-        download.status = "cancelled".to_string();
-        let _ = app.emit("download-progress", download.clone());
+    let disk_usage = if include_disk_usage.unwrap_or(false) {
+        let usage = tokio::task::spawn_blocking(compute_disk_usage)
+            .await
+            .map_err(|e| format!("Disk usage task failed: {}", e))??;
+        Some(usage)
+    } else {
+        None
+    };
+
+    Ok(AppStats {
+        by_status,
+        totals: state.tasks.counters.totals(),
+        disk_usage,
+    })
+}
+
+/// Get the captured yt-dlp/ffmpeg stderr lines for a task
+#[tauri::command]
+pub async fn get_download_log(
+    state: State<'_, Arc<AppState>>,
+    download_id: String,
+) -> Result<Vec<String>, String> {
+    let downloads = state.tasks.downloads.lock().await;
+    downloads
+        .get(&download_id)
+        .map(|d| d.log_tail.clone())
+        .ok_or_else(|| format!("Download not found: {}", download_id))
+}
+
+/// First cues of a finished task's subtitles, for a quick check in the UI
+/// Reads the .srt sidecar when there is one, otherwise the embedded track
+#[tauri::command]
+pub async fn get_subtitle_preview(
+    state: State<'_, Arc<AppState>>,
+    task_id: String,
+    max_lines: Option<usize>,
+) -> Result<Vec<SubtitleCue>, AppError> {
+    let output_path = completed_output_path(&state, &task_id, "Subtitles are available").await?;
+    let mut cues = parse_srt(&media::read_subtitles(&output_path).await?);
+    cues.truncate(max_lines.unwrap_or(10));
+    Ok(cues)
+}
+
+/// Output file of a completed task; `what` starts the error for unfinished tasks
+async fn completed_output_path(state: &AppState, task_id: &str, what: &str) -> Result<PathBuf, AppError> {
+    let downloads = state.tasks.downloads.lock().await;
+    let task = downloads
+        .get(task_id)
+        .ok_or_else(|| AppError::from(format!("Download not found: {}", task_id)))?;
+    if task.status != TaskStatus::Completed {
+        return Err(format!("{} once the task has completed", what).into());
     }
+    task.output_path
+        .clone()
+        .map(PathBuf::from)
+        .ok_or_else(|| "Task has no output file".into())
+}
 
-    // Clean up the cancel sender
-    state.cancel_senders.lock().await.remove(&download_id);
+fn write_clipboard(app: &AppHandle, text: String) -> Result<(), AppError> {
+    app.clipboard()
+        .write_text(text)
+        .map_err(|e| format!("Failed to copy to the clipboard: {}", e).into())
+}
 
-    Ok(())
+/// Put text on the system clipboard
+#[tauri::command]
+pub async fn copy_to_clipboard(app: AppHandle, text: String) -> Result<(), AppError> {
+    write_clipboard(&app, text)
 }
 
+/// Copy a completed task's output path to the clipboard
 #[tauri::command]
-pub async fn get_downloads(
+pub async fn copy_output_path(
+    app: AppHandle,
     state: State<'_, Arc<AppState>>,
-) -> Result<Vec<Download>, String> {
-    let downloads = state.downloads.lock().await;
-    Ok(downloads.values().cloned().collect())
+    task_id: String,
+) -> Result<(), AppError> {
+    let path = completed_output_path(&state, &task_id, "The output path can be copied").await?;
+    write_clipboard(&app, path.to_string_lossy().to_string())
+}
+
+/// Copy a completed task's transcript to the clipboard as plain text
+#[tauri::command]
+pub async fn copy_transcript(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    task_id: String,
+) -> Result<(), AppError> {
+    let output_path = completed_output_path(&state, &task_id, "The transcript can be copied").await?;
+    write_clipboard(&app, transcript_text(&output_path).await?)
+}
+
+/// A transcript as plain text, one cue per line without timings
+async fn transcript_text(output_path: &Path) -> Result<String, AppError> {
+
+    // LRC lyrics replace their SRT; drop the [mm:ss.xx] tag from each line instead
+    if output_path.extension().is_some_and(|e| e.eq_ignore_ascii_case("lrc")) {
+        let content = tokio::fs::read_to_string(output_path)
+            .await
+            .map_err(|e| AppError::from(format!("Failed to read lyrics: {}", e)))?;
+        return Ok(content
+            .lines()
+            .map(|line| line.split_once(']').map_or(line, |(_, text)| text))
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n"));
+    }
+
+    let cues = parse_srt(&media::read_subtitles(output_path).await?);
+    if cues.is_empty() {
+        return Err("No transcript found for this task".into());
+    }
+    Ok(cues.into_iter().map(|cue| cue.text).collect::<Vec<_>>().join("\n"))
+}
+
+/// Re-embed a completed task's subtitles from edited cues (same shape as the preview)
+#[tauri::command]
+pub async fn update_subtitles(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    task_id: String,
+    cues: Vec<SubtitleCue>,
+) -> Result<(), AppError> {
+    pipeline(&app, &state).await.update_subtitles(&task_id, cues).await
+}
+
+/// Cut named clips from a completed download with ffmpeg stream copy; each clip is its own task
+#[tauri::command]
+pub async fn create_clips(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    task_id: String,
+    clips: Vec<ClipRequest>,
+) -> Result<Vec<String>, AppError> {
+    pipeline(&app, &state).await.create_clips(&task_id, clips).await
+}
+
+/// Rewrite a completed download into another container, re-encoding only what it can't hold
+#[tauri::command]
+pub async fn remux(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    task_id: String,
+    target_container: String,
+) -> Result<String, AppError> {
+    pipeline(&app, &state).await.remux(&task_id, &target_container).await
+}
+
+/// Burn word-highlighted karaoke subtitles into a copy of a completed download, styled by config.karaoke
+#[tauri::command]
+pub async fn burn_karaoke(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    task_id: String,
+) -> Result<String, AppError> {
+    let style = state.config.lock().await.karaoke.clone();
+    pipeline(&app, &state).await.burn_karaoke(&task_id, &style).await
 }
 
 #[tauri::command]
@@ -357,7 +717,7 @@ pub async fn clear_download(
     state: State<'_, Arc<AppState>>,
     download_id: String,
 ) -> Result<(), String> {
-    state.downloads.lock().await.remove(&download_id);
+    state.tasks.downloads.lock().await.remove(&download_id);
     Ok(())
 }
 
@@ -365,8 +725,8 @@ pub async fn clear_download(
 pub async fn clear_completed_downloads(
     state: State<'_, Arc<AppState>>,
 ) -> Result<(), String> {
-    let mut downloads = state.downloads.lock().await;
-    downloads.retain(|_, d| d.status != "completed" && d.status != "error" && d.status != "cancelled");
+    let mut downloads = state.tasks.downloads.lock().await;
+    downloads.retain(|_, d| !d.status.is_finished());
     Ok(())
 }
 
@@ -380,10 +740,83 @@ pub async fn get_config(
 #[tauri::command]
 pub async fn update_config(
     state: State<'_, Arc<AppState>>,
-    config: AppConfig,
+    mut config: AppConfig,
+) -> Result<(), String> {
+    // Pasted paths often carry stray whitespace
+    config.output_dir = PathBuf::from(config.output_dir.to_string_lossy().trim());
+    // Model ids differ per engine; a model left over from the previous engine is swapped
+    // for the one last used with this engine
+    let engine = config.transcription_engine.clone();
+    let remembered = config.engine_models.get(&engine).cloned();
+    if let Some(model) = TranscriptionManager::new()
+        .resolve_model(&engine, &[Some(&config.transcription_model), remembered.as_deref()])
+        .await
+    {
+        config.transcription_model = model;
+    }
+    config.engine_models.insert(engine, config.transcription_model.clone());
+    prepare_output_dir(&config.output_dir)?;
+    if let Some(browser) = &config.cookies_browser {
+        YtDlp::validate_cookies_browser(browser)?;
+    }
+    YtDlp::validate_codec_preference(
+        config.preferred_vcodec.as_deref(),
+        config.preferred_acodec.as_deref(),
+    )?;
+    if let Some(sort) = &config.format_sort {
+        YtDlp::validate_format_sort(sort)?;
+    }
+    if let Some(size) = &config.max_filesize {
+        YtDlp::validate_max_filesize(size)?;
+    }
+    if config.min_confidence.is_some_and(|c| !(0.0..=1.0).contains(&c)) {
+        return Err("Minimum confidence must be between 0 and 1".to_string());
+    }
+    config.validate_cue_limits()?;
+    config.validate_sherpa_settings()?;
+    config.karaoke.validate()?;
+    config.validate_subtitle_failure_policy()?;
+    config.validate_log_levels()?;
+    for mirror in [&config.model_mirror_base, &config.ytdlp_mirror_base].into_iter().flatten() {
+        network::validate_mirror_base(mirror)?;
+    }
+    if let Some(proxy) = config.proxy.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+        network::validate_proxy(proxy)?;
+    }
+    if let Some(url) = config.completion_webhook.as_deref().map(str::trim).filter(|u| !u.is_empty()) {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(format!("Webhook URL must start with http:// or https://: {}", url));
+        }
+    }
+    config.save()?;
+    network::apply_config(&config);
+    logging::set_level(logging::parse_level(&config.log_level)?);
+    let mut current = state.config.lock().await;
+    // The password is never sent to the frontend, so the incoming config can't carry it
+    config.auth_password = current.auth_password.clone();
+    if current.cookies_browser != config.cookies_browser
+        || current.auth_username != config.auth_username
+        || current.use_netrc != config.use_netrc
+    {
+        // Signed-in fetches can see formats an anonymous one couldn't
+        state.video_info_cache.lock().await.clear();
+    }
+    *current = config;
+    Ok(())
+}
+
+/// Change the global log level now and save it; per-module levels apply from the next launch
+#[tauri::command]
+pub async fn set_log_level(
+    state: State<'_, Arc<AppState>>,
+    level: String,
 ) -> Result<(), String> {
+    let filter = logging::parse_level(&level)?;
+    let mut config = state.config.lock().await;
+    config.log_level = level.trim().to_lowercase();
     config.save()?;
-    *state.config.lock().await = config;
+    logging::set_level(filter);
+    log::info!("Log level set to {}", config.log_level);
     Ok(())
 }
 
@@ -419,9 +852,86 @@ pub async fn open_folder(path: String) -> Result<(), String> {
     }
 }
 
+/// Open a folder in the file manager, creating it first on a fresh install
+fn open_dir(dir: PathBuf) -> Result<(), String> {
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    opener::open(&dir).map_err(|e| format!("Failed to open folder: {}", e))
+}
+
+/// Open the folder all engines keep their models in
+#[tauri::command]
+pub async fn open_models_dir() -> Result<(), String> {
+    open_dir(WhisperManager::get_models_dir()?)
+}
+
+/// Open the folder holding config.json
+#[tauri::command]
+pub async fn open_config_dir() -> Result<(), String> {
+    open_dir(AppConfig::config_dir()?)
+}
+
+fn gpu_info(nvidia: bool) -> GpuInfo {
+    if nvidia {
+        GpuInfo { vendor: Some("nvidia".to_string()), available: true }
+    } else if cfg!(target_os = "macos") && cfg!(target_arch = "aarch64") {
+        // Apple Silicon always has Metal
+        GpuInfo { vendor: Some("apple".to_string()), available: true }
+    } else {
+        GpuInfo { vendor: None, available: false }
+    }
+}
+
+/// Re-run the GPU probe, which is otherwise done once per session
 #[tauri::command]
-pub fn get_format_presets() -> HashMap<String, String> {
-    YtDlp::get_format_presets()
+pub async fn refresh_gpu_status() -> Result<GpuInfo, String> {
+    let nvidia = refresh_nvidia_gpu().await;
+    Ok(gpu_info(nvidia))
+}
+
+/// Collect every subsystem's status in one report. Local checks only, so it works offline
+#[tauri::command]
+pub async fn get_diagnostics(state: State<'_, Arc<AppState>>) -> Result<Diagnostics, String> {
+    let offline = state.config.lock().await.offline;
+    let manager = TranscriptionManager::new();
+
+    let (ytdlp, deno, whisper, sherpa_installed, ffmpeg, engines) = tokio::join!(
+        YtDlpManager::check_status_fast(),
+        DenoManager::check_status(),
+        WhisperManager::check_status(),
+        SherpaManager::is_installed(),
+        Whisper::get_ffmpeg_info(),
+        manager.get_engines(),
+    );
+
+    let nvidia = check_nvidia_gpu().await;
+    let gpu = gpu_info(nvidia);
+
+    Ok(Diagnostics {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        offline,
+        ytdlp,
+        deno,
+        whisper,
+        sherpa_installed,
+        ffmpeg,
+        gpu,
+        engines,
+    })
+}
+
+#[tauri::command]
+pub async fn get_format_presets(
+    state: State<'_, Arc<AppState>>,
+) -> Result<HashMap<String, String>, String> {
+    let config = state.config.lock().await;
+    Ok(YtDlp::get_format_presets(
+        config.preferred_vcodec.as_deref(),
+        config.preferred_acodec.as_deref(),
+        config.max_filesize.as_deref(),
+    ))
 }
 
 // yt-dlp manager commands
@@ -492,6 +1002,11 @@ pub async fn check_ytdlp_update(
         Err(_) => return Ok(None),
     };
 
+    // Nothing to compare against without the network
+    if network::is_offline() {
+        return Ok(None);
+    }
+
     let latest = YtDlpManager::get_latest_version(&channel).await?;
 
     if current != latest {
@@ -521,7 +1036,7 @@ pub async fn install_whisper(app: AppHandle) -> Result<String, String> {
 }
 
 #[tauri::command]
-pub async fn download_whisper_model(app: AppHandle, model: String) -> Result<(), String> {
+pub async fn download_whisper_model(app: AppHandle, model: String) -> Result<(), AppError> {
     let app_clone = app.clone();
 
     WhisperManager::download_model(&model, move |progress: WhisperInstallProgress| {
@@ -542,6 +1057,11 @@ pub async fn check_ffmpeg() -> Result<bool, String> {
     Ok(Whisper::check_ffmpeg().await)
 }
 
+#[tauri::command]
+pub async fn get_ffmpeg_info() -> Result<FfmpegInfo, String> {
+    Ok(Whisper::get_ffmpeg_info().await)
+}
+
 // Transcription engine commands
 
 #[tauri::command]
@@ -556,220 +1076,278 @@ pub async fn get_engine_models(engine_id: String) -> Result<Vec<TranscriptionMod
     Ok(manager.get_engine_models(&engine_id).await)
 }
 
+/// Key for an engine install (no model) or model download in model_cancel_senders
+fn model_download_key(engine_id: &str, model_id: Option<&str>) -> String {
+    format!("{}:{}", engine_id, model_id.unwrap_or(""))
+}
+
 #[tauri::command]
-pub async fn install_transcription_engine(app: AppHandle, engine_id: String) -> Result<(), String> {
+pub async fn install_transcription_engine(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    engine_id: String,
+) -> Result<(), AppError> {
     let app_clone = app.clone();
     let manager = TranscriptionManager::new();
 
-    manager
+    let key = model_download_key(&engine_id, None);
+    let (cancel_tx, cancel_rx) = watch::channel(false);
+    state.model_cancel_senders.lock().await.insert(key.clone(), cancel_tx);
+
+    let result = manager
         .install_engine(&engine_id, move |progress: TranscriptionInstallProgress| {
             let _ = app_clone.emit("transcription-install-progress", &progress);
-        })
-        .await
+        }, cancel_rx)
+        .await;
+
+    state.model_cancel_senders.lock().await.remove(&key);
+    result
 }
 
 #[tauri::command]
 pub async fn download_transcription_model(
     app: AppHandle,
+    state: State<'_, Arc<AppState>>,
     engine_id: String,
     model_id: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let app_clone = app.clone();
     let manager = TranscriptionManager::new();
 
-    manager
+    let key = model_download_key(&engine_id, Some(&model_id));
+    let (cancel_tx, cancel_rx) = watch::channel(false);
+    state.model_cancel_senders.lock().await.insert(key.clone(), cancel_tx);
+
+    let result = manager
         .download_model(&engine_id, &model_id, move |progress: TranscriptionInstallProgress| {
             let _ = app_clone.emit("transcription-install-progress", &progress);
-        })
+        }, cancel_rx)
+        .await;
+
+    state.model_cancel_senders.lock().await.remove(&key);
+    result
+}
+
+/// Transcribe the first `seconds` (default 60) of a file or a task's file and return the text
+/// `source` is a task id or a file path
+#[tauri::command]
+pub async fn preview_transcription(
+    state: State<'_, Arc<AppState>>,
+    source: String,
+    engine: String,
+    model: String,
+    language: Option<String>,
+    seconds: Option<f64>,
+) -> Result<String, AppError> {
+    let task_path = state
+        .tasks
+        .downloads
+        .lock()
+        .await
+        .get(&source)
+        .and_then(|task| task.source_path.clone().or_else(|| task.output_path.clone()));
+    let path = PathBuf::from(task_path.unwrap_or(source));
+    if !path.is_file() {
+        return Err(AppError::from(format!("File not found: {}", path.display())));
+    }
+
+    let seconds = seconds.unwrap_or(60.0).clamp(5.0, 600.0);
+    let settings = TranscriptionSettings::from(&*state.config.lock().await);
+    TranscriptionManager::new()
+        .with_settings(settings)
+        .preview_transcription(&path, &engine, &model, language.as_deref(), seconds)
         .await
 }
 
+/// Shift an existing .srt file's cues, or a video's embedded subtitles, by `offset_ms`
+/// Negative offsets show subtitles earlier. Returns the rewritten file
 #[tauri::command]
-pub async fn get_transcription_speed_multiplier(
-    engine_id: String,
-    model_id: String,
-    use_gpu: bool,
-) -> Result<f64, String> {
-    let manager = TranscriptionManager::new();
-    Ok(manager.get_speed_multiplier(&engine_id, &model_id, use_gpu))
+pub async fn shift_subtitles(path: String, offset_ms: i64) -> Result<String, AppError> {
+    let path = PathBuf::from(path.trim());
+    if !path.is_file() {
+        return Err(AppError::from(format!("File not found: {}", path.display())));
+    }
+
+    let (progress_tx, _) = mpsc::channel(1);
+    let (_cancel_tx, cancel_rx) = watch::channel(false);
+    let result = TranscriptionManager::new()
+        .shift_subtitles(&path, offset_ms, progress_tx, cancel_rx)
+        .await?;
+    Ok(result.to_string_lossy().to_string())
 }
 
-// Local file transcription - unified with downloads system
+/// Result of repair_model: what was deleted before the fresh download
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelRepair {
+    pub removed: Vec<String>, // Paths deleted; empty when nothing was on disk
+    pub downloaded: String,   // Model id fetched again
+}
 
-/// Add a local file for transcription (creates a pending task)
+/// Delete a model's files (including half-extracted leftovers) and download it again
+/// Progress is emitted as "transcription-install-progress", like download_transcription_model
 #[tauri::command]
-pub async fn add_local_transcription(
+pub async fn repair_model(
     app: AppHandle,
     state: State<'_, Arc<AppState>>,
-    file_path: String,
-    title: String,
-    engine: String,
-    model: String,
-    _style: String,
-) -> Result<String, String> {
-    let video_path = PathBuf::from(&file_path);
-
-    if !video_path.exists() {
-        return Err(format!("File not found: {}", file_path));
-    }
-
-    let task_id = Uuid::new_v4().to_string();
-
-    let download = Download {
-        id: task_id.clone(),
-        url: String::new(),
-        title,
-        thumbnail: None,
-        status: "pending".to_string(),
-        progress: 0.0,
-        speed: None,
-        eta: None,
-        output_path: Some(file_path.clone()),
-        format: String::new(),
-        error: None,
-        duration: None,
-        whisper_model: Some(model),
-        transcription_engine: Some(engine),
-        transcription_progress: None,
-        transcription_message: None,
-        task_type: "local_transcribe".to_string(),
-        source_path: Some(file_path),
-    };
+    engine_id: String,
+    model_id: String,
+) -> Result<ModelRepair, AppError> {
+    let app_clone = app.clone();
+    let manager = TranscriptionManager::new();
+
+    let key = model_download_key(&engine_id, Some(&model_id));
+    let (cancel_tx, cancel_rx) = watch::channel(false);
+    state.model_cancel_senders.lock().await.insert(key.clone(), cancel_tx);
 
-    state.downloads.lock().await.insert(task_id.clone(), download.clone());
-    let _ = app.emit("download-progress", download);
+    let result = manager
+        .repair_model(&engine_id, &model_id, move |progress: TranscriptionInstallProgress| {
+            let _ = app_clone.emit("transcription-install-progress", &progress);
+        }, cancel_rx)
+        .await;
 
-    Ok(task_id)
+    state.model_cancel_senders.lock().await.remove(&key);
+    Ok(ModelRepair { removed: result?, downloaded: model_id })
 }
 
-/// Start transcription for a pending local transcription task
+/// Whether speaker diarization (binary and models) is installed
 #[tauri::command]
-pub async fn start_local_transcription(
+pub async fn get_diarization_status() -> Result<bool, String> {
+    Ok(diarization::is_available().await)
+}
+
+/// Download the speaker diarization models, emitting "diarization-install-progress".
+/// Cancel with cancel_model_download("diarization")
+#[tauri::command]
+pub async fn install_diarization(
     app: AppHandle,
     state: State<'_, Arc<AppState>>,
-    task_id: String,
-) -> Result<(), String> {
-    // Get task info
-    let (file_path, engine_id, model_id, style) = {
-        let downloads = state.downloads.lock().await;
-        let task = downloads.get(&task_id)
-            .ok_or_else(|| format!("Task not found: {}", task_id))?;
-
-        if task.task_type != "local_transcribe" {
-            return Err("Not a local transcription task".to_string());
-        }
-        if task.status != "pending" {
-            return Err(format!("Task is not pending, status: {}", task.status));
-        }
+) -> Result<(), AppError> {
+    let key = model_download_key("diarization", None);
+    let (cancel_tx, cancel_rx) = watch::channel(false);
+    state.model_cancel_senders.lock().await.insert(key.clone(), cancel_tx);
 
-        let source = task.source_path.clone()
-            .ok_or_else(|| "No source path".to_string())?;
-        let engine = task.transcription_engine.clone()
-            .ok_or_else(|| "No engine specified".to_string())?;
-        let model = task.whisper_model.clone()
-            .ok_or_else(|| "No model specified".to_string())?;
+    let result = diarization::install(move |progress: TranscriptionInstallProgress| {
+        let _ = app.emit("diarization-install-progress", &progress);
+    }, cancel_rx)
+    .await;
 
-        (source, engine, model, "sentence".to_string())  // TODO: store style in Download
-    };
+    state.model_cancel_senders.lock().await.remove(&key);
+    result
+}
 
-    let video_path = PathBuf::from(&file_path);
+/// Download several models one after another, emitting combined "model-queue-progress" events.
+/// Cancelling the current model stops the queue; other failures are reported at the end.
+#[tauri::command]
+pub async fn download_models(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    requests: Vec<(String, String)>,
+) -> Result<(), AppError> {
+    let manager = TranscriptionManager::new();
+    let total = requests.len();
+    let mut failures = Vec::new();
+
+    for (index, (engine_id, model_id)) in requests.into_iter().enumerate() {
+        let key = model_download_key(&engine_id, Some(&model_id));
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+        state.model_cancel_senders.lock().await.insert(key.clone(), cancel_tx);
+
+        let app_clone = app.clone();
+        let (engine_clone, model_clone) = (engine_id.clone(), model_id.clone());
+        let result = manager
+            .download_model(&engine_id, &model_id, move |progress: TranscriptionInstallProgress| {
+                let _ = app_clone.emit("model-queue-progress", ModelQueueProgress {
+                    engine_id: engine_clone.clone(),
+                    model_id: model_clone.clone(),
+                    index,
+                    total,
+                    model_percentage: progress.percentage,
+                    overall_percentage: (index as f64 * 100.0 + progress.percentage) / total as f64,
+                    stage: progress.stage,
+                });
+            }, cancel_rx)
+            .await;
 
-    // Create cancel channel
-    let (cancel_tx, cancel_rx) = watch::channel(false);
-    state.cancel_senders.lock().await.insert(task_id.clone(), cancel_tx);
+        state.model_cancel_senders.lock().await.remove(&key);
 
-    // Update status to transcribing
-    {
-        let mut downloads = state.downloads.lock().await;
-        if let Some(download) = downloads.get_mut(&task_id) {
-            download.status = "transcribing:extracting".to_string();
-            let _ = app.emit("download-progress", download.clone());
+        match result {
+            Ok(()) => {}
+            Err(e @ AppError::Cancelled(_)) => return Err(e),
+            Err(e) => {
+                log::warn!("Queued download of {}/{} failed: {}", engine_id, model_id, e);
+                failures.push(format!("{}/{}: {}", engine_id, model_id, e));
+            }
         }
     }
 
-    let state_clone = Arc::clone(&state.inner());
-    let app_clone = app.clone();
-    let task_id_clone = task_id.clone();
-
-    tokio::spawn(async move {
-        // Create progress channel for transcription
-        let (transcribe_tx, mut transcribe_rx) = mpsc::channel::<TranscribeProgress>(100);
-
-        let app_for_transcribe = app_clone.clone();
-        let task_id_for_progress = task_id_clone.clone();
-        let state_for_progress = state_clone.clone();
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Some models failed to download: {}", failures.join("; ")).into())
+    }
+}
 
-        // Spawn task to forward transcription progress
-        tokio::spawn(async move {
-            while let Some(progress) = transcribe_rx.recv().await {
-                // Skip the "complete" stage - we handle completion in the main flow
-                if progress.stage == "complete" {
-                    continue;
-                }
-                let mut downloads = state_for_progress.downloads.lock().await;
-                if let Some(download) = downloads.get_mut(&task_id_for_progress) {
-                    // Don't overwrite if already completed
-                    if download.status == "completed" {
-                        continue;
-                    }
-                    download.status = format!("transcribing:{}", progress.stage);
-                    download.transcription_progress = Some(progress.progress);
-                    download.transcription_message = Some(progress.message.clone());
-                    let _ = app_for_transcribe.emit("transcribe-progress", &progress);
-                    let _ = app_for_transcribe.emit("download-progress", download.clone());
-                }
-            }
-        });
+/// Cancel an in-flight model download, or the engine install when model_id is omitted
+#[tauri::command]
+pub async fn cancel_model_download(
+    state: State<'_, Arc<AppState>>,
+    engine_id: String,
+    model_id: Option<String>,
+) -> Result<(), String> {
+    let key = model_download_key(&engine_id, model_id.as_deref());
+    if let Some(cancel_tx) = state.model_cancel_senders.lock().await.get(&key) {
+        let _ = cancel_tx.send(true);
+    }
+    Ok(())
+}
 
-        let transcription_manager = TranscriptionManager::new();
+#[tauri::command]
+pub async fn get_transcription_speed_multiplier(
+    engine_id: String,
+    model_id: String,
+    use_gpu: bool,
+) -> Result<f64, String> {
+    let manager = TranscriptionManager::new();
+    Ok(manager.get_speed_multiplier(&engine_id, &model_id, use_gpu))
+}
 
-        log::info!(
-            "Starting local file transcription for: {:?} with engine: {}, model: {}",
-            video_path,
-            engine_id,
-            model_id
-        );
+// Local file transcription - unified with downloads system
 
-        match transcription_manager
-            .process_video(
-                &video_path,
-                &engine_id,
-                &model_id,
-                None, // Language is auto-detected
-                &style,
-                transcribe_tx,
-                cancel_rx,
-            )
-            .await
-        {
-            Ok(result) => {
-                log::info!("Local file transcription successful: {:?}", result);
-                let mut downloads = state_clone.downloads.lock().await;
-                if let Some(download) = downloads.get_mut(&task_id_clone) {
-                    download.status = "completed".to_string();
-                    download.progress = 100.0;
-                    let _ = app_clone.emit("download-progress", download.clone());
-                }
-            }
-            Err(e) => {
-                log::error!("Local file transcription failed: {}", e);
-                let mut downloads = state_clone.downloads.lock().await;
-                if let Some(download) = downloads.get_mut(&task_id_clone) {
-                    // Only set error status if not already cancelled
-                    if download.status != "cancelled" {
-                        download.status = "error".to_string();
-                        download.error = Some(e);
-                    }
-                    let _ = app_clone.emit("download-progress", download.clone());
-                }
-            }
-        }
+/// Add a local file for transcription (creates a pending task)
+#[tauri::command]
+pub async fn add_local_transcription(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    request: LocalTranscriptionRequest,
+) -> Result<String, AppError> {
+    pipeline(&app, &state).await.add_local_transcription(request).await
+}
 
-        // Clean up cancel sender
-        state_clone.cancel_senders.lock().await.remove(&task_id_clone);
-    });
+/// Start transcription for a pending local transcription task
+#[tauri::command]
+pub async fn start_local_transcription(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    task_id: String,
+    override_duration_guard: Option<bool>,
+) -> Result<(), AppError> {
+    let pipeline = pipeline(&app, &state).await;
+    let config = state.config.lock().await.clone();
+    pipeline
+        .start_local_transcription(&config, &task_id, override_duration_guard.unwrap_or(false))
+        .await
+}
 
-    Ok(())
+/// Download a direct audio/video link and transcribe it
+#[tauri::command]
+pub async fn start_remote_transcription(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    request: RemoteTranscriptionRequest,
+) -> Result<String, AppError> {
+    let pipeline = pipeline(&app, &state).await;
+    let config = state.config.lock().await;
+    pipeline.start_remote_transcription(&config, request).await
 }
 
 /// Update transcription settings for a pending task
@@ -781,25 +1359,32 @@ pub async fn update_transcription_settings(
     engine: Option<String>,
     model: Option<String>,
     style: Option<String>,
+    language: Option<String>,
 ) -> Result<(), String> {
-    let mut downloads = state.downloads.lock().await;
+    let mut downloads = state.tasks.downloads.lock().await;
     let download = downloads.get_mut(&task_id)
         .ok_or_else(|| format!("Task not found: {}", task_id))?;
 
-    if download.status != "pending" {
+    if download.status != TaskStatus::Pending {
         return Err("Can only update settings for pending tasks".to_string());
     }
 
+    // Check the language against the engine the task will end up with, before changing anything
+    let language = TranscriptionManager::new().validate_language(
+        engine.as_deref().or(download.transcription_engine.as_deref()).unwrap_or_default(),
+        language.as_deref().or(download.transcription_language.as_deref()),
+    )?;
+
     if let Some(e) = engine {
         download.transcription_engine = Some(e);
     }
     if let Some(m) = model {
         download.whisper_model = Some(m);
     }
-    // Note: style is not currently stored in Download struct,
-    // could add it in a future enhancement
-
-    let _ = style; // Acknowledge unused for now
+    if let Some(s) = style {
+        download.transcription_style = Some(s);
+    }
+    download.transcription_language = language;
 
     let _ = app.emit("download-progress", download.clone());
 
@@ -825,9 +1410,190 @@ pub async fn install_deno(app: AppHandle) -> Result<String, String> {
     Ok(version)
 }
 
+// ffmpeg manager commands
+
+#[tauri::command]
+pub async fn install_ffmpeg(app: AppHandle) -> Result<String, String> {
+    let app_clone = app.clone();
+
+    let path = FfmpegManager::install(move |progress: InstallProgress| {
+        let _ = app_clone.emit("ffmpeg-install-progress", progress);
+    })
+    .await?;
+
+    Ok(path)
+}
+
+// Bulk update
+
+const UPDATE_TOOLS: [&str; 4] = ["yt-dlp", "deno", "whisper", "sherpa-onnx"];
+
+/// Installed and target version for a tool; None when it isn't installed
+async fn tool_versions(tool: &str, channel: &str) -> Result<Option<(String, String)>, String> {
+    match tool {
+        "yt-dlp" => match YtDlpManager::get_installed_version().await {
+            Ok(current) => Ok(Some((current, YtDlpManager::get_latest_version(channel).await?))),
+            Err(_) => Ok(None),
+        },
+        "deno" => match DenoManager::get_installed_version().await {
+            Ok(current) => Ok(Some((current, DenoManager::get_latest_version().await?))),
+            Err(_) => Ok(None),
+        },
+        "whisper" => {
+            if !WhisperManager::get_binary_path()?.exists() {
+                return Ok(None);
+            }
+            // An unknown tag can't be compared, so it's treated as outdated
+            let current = WhisperManager::installed_tag().await.unwrap_or_default();
+            Ok(Some((current, WhisperManager::get_latest_version().await?)))
+        }
+        _ => {
+            if !SherpaManager::is_installed().await {
+                return Ok(None);
+            }
+            // Only ever move to the pinned release; newer upstream builds are untested
+            let current = SherpaManager::installed_tag().await.unwrap_or_default();
+            Ok(Some((current, SherpaManager::pinned_version().to_string())))
+        }
+    }
+}
+
+/// Emit "update-all-progress" for the tool at `step`
+fn emit_update_progress(app: &AppHandle, tool: &str, step: usize, percentage: f64, stage: String) {
+    let _ = app.emit("update-all-progress", UpdateAllProgress {
+        tool: tool.to_string(),
+        step,
+        total_steps: UPDATE_TOOLS.len(),
+        percentage,
+        stage,
+    });
+}
+
+/// Check yt-dlp, Deno, whisper.cpp and sherpa-onnx and update the outdated ones, emitting
+/// "update-all-progress". Tools that aren't installed are skipped, and one failure doesn't stop the rest.
+#[tauri::command]
+pub async fn update_all(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+) -> Result<UpdateSummary, AppError> {
+    network::ensure_online("updating tools")?;
+
+    let channel = state.config.lock().await.ytdlp_channel.clone();
+    let mut summary = UpdateSummary::default();
+
+    for (index, tool) in UPDATE_TOOLS.into_iter().enumerate() {
+        let step = index + 1;
+        emit_update_progress(&app, tool, step, 0.0, format!("Checking {}...", tool));
+
+        let (current, target) = match tool_versions(tool, &channel).await {
+            Ok(Some(versions)) => versions,
+            Ok(None) => {
+                summary.not_installed.push(tool.to_string());
+                continue;
+            }
+            Err(e) => {
+                log::warn!("Could not check {} for updates: {}", tool, e);
+                summary.failed.push(format!("{}: {}", tool, e));
+                continue;
+            }
+        };
+
+        if current == target {
+            summary.up_to_date.push(format!("{} {}", tool, current));
+            continue;
+        }
+
+        log::info!("Updating {} from {:?} to {}", tool, current, target);
+        emit_update_progress(&app, tool, step, 0.0, format!("Updating {} to {}...", tool, target));
+
+        let app_clone = app.clone();
+        let result = match tool {
+            "yt-dlp" => YtDlpManager::update(&channel, move |progress: InstallProgress| {
+                emit_update_progress(&app_clone, tool, step, progress.percentage, "Downloading yt-dlp...".to_string());
+            })
+            .await,
+            "deno" => DenoManager::install(move |progress: InstallProgress| {
+                emit_update_progress(&app_clone, tool, step, progress.percentage, "Downloading Deno...".to_string());
+            })
+            .await
+            .map(|_| target.clone()),
+            "whisper" => WhisperManager::install(move |progress: WhisperInstallProgress| {
+                emit_update_progress(&app_clone, tool, step, progress.percentage, progress.stage);
+            })
+            .await,
+            _ => {
+                // Not cancellable: the sender lives until the install returns
+                let (_cancel_tx, cancel_rx) = watch::channel(false);
+                SherpaManager::install(Box::new(move |progress: TranscriptionInstallProgress| {
+                    emit_update_progress(&app_clone, tool, step, progress.percentage, progress.stage);
+                }), cancel_rx)
+                .await
+                .map_err(String::from)
+            }
+        };
+
+        match result {
+            Ok(version) => summary.updated.push(format!("{} {}", tool, version)),
+            Err(e) => {
+                log::warn!("Updating {} failed: {}", tool, e);
+                summary.failed.push(format!("{}: {}", tool, e));
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
 // Network interface commands
 
 #[tauri::command]
 pub async fn list_network_interfaces() -> Result<Vec<NetworkInterface>, String> {
     network::get_network_interfaces()
 }
+
+/// Check that downloads bound to an interface's address actually reach the internet
+#[tauri::command]
+pub async fn test_interface(ipv4: String) -> Result<InterfaceTest, AppError> {
+    Ok(network::test_interface(&ipv4).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::hook_command_line;
+
+    #[test]
+    fn bare_placeholders_are_quoted() {
+        assert_eq!(hook_command_line("mv {path} /nas", false), "mv \"${ZINC_PATH}\" /nas");
+        assert_eq!(hook_command_line("move {path} D:\\nas", true), "move \"%ZINC_PATH%\" D:\\nas");
+    }
+
+    #[test]
+    fn user_quotes_are_not_doubled() {
+        assert_eq!(hook_command_line("cp \"{path}\" /nas", false), "cp \"${ZINC_PATH}\" /nas");
+        assert_eq!(
+            hook_command_line("copy \"{path}\" \"D:\\nas\\{title}.mp4\"", true),
+            "copy \"%ZINC_PATH%\" \"D:\\nas\\%ZINC_TITLE%.mp4\""
+        );
+    }
+
+    #[test]
+    fn single_quotes_are_reopened_around_the_variable() {
+        assert_eq!(
+            hook_command_line("echo '{title} done'", false),
+            "echo ''\"${ZINC_TITLE}\"' done'"
+        );
+    }
+
+    #[test]
+    fn escaped_quotes_do_not_toggle_quoting() {
+        assert_eq!(
+            hook_command_line("echo \\\" {status}", false),
+            "echo \\\" \"${ZINC_STATUS}\""
+        );
+    }
+
+    #[test]
+    fn templates_without_placeholders_are_unchanged() {
+        assert_eq!(hook_command_line("notify-send done", false), "notify-send done");
+    }
+}
@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+use crate::transcription::karaoke::KaraokeStyle;
+use crate::ytdlp::Credentials;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
@@ -16,11 +21,85 @@ pub struct AppConfig {
     #[serde(default = "default_transcription_model")]
     pub transcription_model: String,
     #[serde(default)]
+    pub engine_models: HashMap<String, String>, // Engine id -> last model picked for it, restored when switching back
+    #[serde(default)]
     pub network_interface: Option<String>, // Stores IPv4 address or None for any interface
     #[serde(default = "default_ytdlp_channel")]
     pub ytdlp_channel: String,
     #[serde(default)]
-    pub cookies_browser: Option<String>,
+    pub cookies_browser: Option<String>, // "browser" or "browser:profile"
+    #[serde(default = "default_restrict_filenames")]
+    pub restrict_filenames: bool, // ASCII-only names via --restrict-filenames; off keeps non-Latin titles
+    #[serde(default)]
+    pub embed_native_subs: bool, // Let yt-dlp fetch the site's own subtitles and embed them while merging
+    #[serde(default)]
+    pub auth_username: Option<String>, // Passed to yt-dlp as --username
+    #[serde(skip)]
+    pub auth_password: Option<String>, // Kept in memory for the session; never saved to config.json
+    #[serde(default)]
+    pub use_netrc: bool, // Let yt-dlp read logins from ~/.netrc instead
+    #[serde(default)]
+    pub preferred_vcodec: Option<String>, // "h264", "hevc", "vp9" or "av1"
+    #[serde(default)]
+    pub preferred_acodec: Option<String>, // "aac", "opus" or "mp3"
+    #[serde(default)]
+    pub format_sort: Option<Vec<String>>, // yt-dlp --format-sort fields; orders formats within the preset's selector
+    #[serde(default)]
+    pub max_filesize: Option<String>, // yt-dlp --max-filesize, e.g. "200M"; presets prefer streams under it
+    #[serde(default)]
+    pub completion_webhook: Option<String>, // URL to POST finished tasks to
+    #[serde(default)]
+    pub completion_webhook_secret: Option<String>, // Sent as a bearer token with the webhook
+    #[serde(default)]
+    pub post_download_command: Option<String>, // Shell command run after each finished task; {path}, {title}, {status}
+    #[serde(default)]
+    pub min_confidence: Option<f32>, // 0-1; whisper_rs drops segments below this
+    #[serde(default)]
+    pub sherpa_decoding_method: Option<String>, // "greedy_search" (default) or "modified_beam_search"; Zipformer only
+    #[serde(default)]
+    pub sherpa_max_active_paths: Option<u32>, // Beam width for modified_beam_search (sherpa's default is 4)
+    #[serde(default)]
+    pub sherpa_feature_dim: Option<u32>, // Fbank feature dimension (sherpa's default is 80); Zipformer only
+    #[serde(default)]
+    pub max_cue_chars: Option<usize>, // Longer sentence cues are split on word boundaries
+    #[serde(default)]
+    pub max_cue_duration_secs: Option<f64>,
+    #[serde(default)]
+    pub subtitle_offset_ms: i64, // Added to every generated cue; negative shows subtitles earlier
+    #[serde(default)]
+    pub karaoke: KaraokeStyle, // Font and colors of burned-in karaoke subtitles
+    #[serde(default)]
+    pub offline: bool, // Never contact the network; only installed binaries and models are used
+    #[serde(default)]
+    pub model_mirror_base: Option<String>, // Replaces the HuggingFace/GitHub host for model downloads
+    #[serde(default)]
+    pub hf_token: Option<String>, // Bearer token for gated HuggingFace models; only sent to huggingface.co
+    #[serde(default)]
+    pub ytdlp_mirror_base: Option<String>, // Replaces the GitHub host for yt-dlp downloads
+    #[serde(default)]
+    pub proxy: Option<String>, // http(s) proxy for yt-dlp and every model/binary download
+    #[serde(default)]
+    pub max_transcription_minutes: Option<u32>, // Longer inputs need explicit confirmation; None means no limit
+    #[serde(default = "default_download_retries")]
+    pub download_retries: u32, // Extra attempts for model/binary downloads that fail to connect or time out
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    #[serde(default = "default_read_timeout_secs")]
+    pub read_timeout_secs: u64, // A download with no data for this long is treated as stalled
+    #[serde(default = "default_fallback_models")]
+    pub fallback_models: Vec<String>, // Largest first; retried in order when a model runs out of memory
+    #[serde(default = "default_subtitle_failure_policy")]
+    pub subtitle_failure_policy: String, // "warn_but_keep", "fail_task" or "retry_once"
+    #[serde(default)]
+    pub diarize: bool, // Prefix cues with "Speaker N:" when more than one voice is detected
+    #[serde(default)]
+    pub tighten_cues: bool, // Snap whisper_rs cues to token timestamps; other engines are unaffected
+    #[serde(default = "default_log_level")]
+    pub log_level: String, // "off", "error", "warn", "info", "debug" or "trace"
+    #[serde(default)]
+    pub module_log_levels: HashMap<String, String>, // Module path prefix -> level, e.g. "zinc::transcription": "debug"
+    #[serde(default)]
+    pub normalize_audio: bool, // Run loudnorm on extracted audio; helps quiet recordings at the cost of a slower pass
 }
 
 fn default_whisper_model() -> String {
@@ -36,14 +115,128 @@ fn default_transcription_model() -> String {
 }
 
 fn default_ytdlp_channel() -> String {
-    "nightly".to_string()
+    "stable".to_string()
+}
+
+fn default_download_retries() -> u32 {
+    3
+}
+
+fn default_connect_timeout_secs() -> u64 {
+    15
+}
+
+fn default_read_timeout_secs() -> u64 {
+    30
+}
+
+fn default_log_level() -> String {
+    // Release builds only report problems unless the user asks for more
+    if cfg!(debug_assertions) { "info" } else { "warn" }.to_string()
+}
+
+fn default_restrict_filenames() -> bool {
+    true
+}
+
+fn default_subtitle_failure_policy() -> String {
+    "warn_but_keep".to_string()
+}
+
+/// Make sure an output folder exists and can be written to, creating it if missing
+pub fn prepare_output_dir(dir: &Path) -> Result<(), AppError> {
+    if dir.as_os_str().is_empty() {
+        return Err("Output folder is empty".into());
+    }
+    check_output_volume(dir)?;
+    if dir.exists() {
+        if !dir.is_dir() {
+            return Err(format!("Output folder is not a directory: {}", dir.display()).into());
+        }
+    } else {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create output folder {}: {}", dir.display(), e))?;
+    }
+
+    // Permissions alone don't tell the whole story on Windows; try an actual write
+    let probe = dir.join(format!(".zinc-write-test-{}", std::process::id()));
+    std::fs::write(&probe, b"")
+        .map_err(|e| format!("Output folder is not writable {}: {}", dir.display(), e))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+/// Fail when the drive holding `dir` isn't there, instead of creating the folder
+/// somewhere unexpected. Windows: the drive letter or network share must be reachable
+#[cfg(target_os = "windows")]
+fn check_output_volume(dir: &Path) -> Result<(), AppError> {
+    use std::path::Component;
+
+    // Relative paths live on the current drive, which is always present
+    let Some(Component::Prefix(prefix)) = dir.components().next() else {
+        return Ok(());
+    };
+    let root = PathBuf::from(prefix.as_os_str()).join("\\");
+    if std::fs::metadata(&root).is_err() {
+        return Err(AppError::OutputUnavailable(format!(
+            "Output drive not available: {} is not connected",
+            root.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Mount points removable and network drives appear under
+#[cfg(not(target_os = "windows"))]
+const REMOVABLE_MOUNT_BASES: &[&str] = &["/media", "/run/media", "/mnt", "/Volumes"];
+
+/// Unix: a missing folder under a removable mount base must sit on a live mount,
+/// otherwise creating it would fill the bare mount point on the system disk
+#[cfg(not(target_os = "windows"))]
+fn check_output_volume(dir: &Path) -> Result<(), AppError> {
+    use std::os::unix::fs::MetadataExt;
+
+    if dir.exists() {
+        return Ok(());
+    }
+    let Some(base) = REMOVABLE_MOUNT_BASES.iter().map(Path::new).find(|b| dir.starts_with(b)) else {
+        return Ok(());
+    };
+
+    // A directory is a mount point when it sits on a different device than its parent
+    let is_mount_point = |path: &Path| match (std::fs::metadata(path), path.parent().map(std::fs::metadata)) {
+        (Ok(meta), Some(Ok(parent))) => meta.dev() != parent.dev(),
+        _ => false,
+    };
+    let mounted = dir
+        .ancestors()
+        .take_while(|a| *a != base)
+        .any(is_mount_point);
+    if !mounted {
+        return Err(AppError::OutputUnavailable(format!(
+            "Output drive not available: nothing is mounted for {}",
+            dir.display()
+        )));
+    }
+    Ok(())
+}
+
+/// What to do with a finished download when its subtitles fail
+pub const SUBTITLE_FAILURE_POLICIES: &[&str] = &["warn_but_keep", "fail_task", "retry_once"];
+
+fn default_fallback_models() -> Vec<String> {
+    ["large-v3", "medium", "small", "base"].map(String::from).to_vec()
+}
+
+pub fn default_output_dir() -> PathBuf {
+    dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .unwrap_or_else(|| PathBuf::from("."))
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
-        let output_dir = dirs::download_dir()
-            .or_else(dirs::home_dir)
-            .unwrap_or_else(|| PathBuf::from("."));
+        let output_dir = default_output_dir();
 
         Self {
             output_dir,
@@ -54,14 +247,60 @@ impl Default for AppConfig {
             whisper_model: default_whisper_model(),
             transcription_engine: default_transcription_engine(),
             transcription_model: default_transcription_model(),
+            engine_models: HashMap::new(),
             network_interface: None,
             ytdlp_channel: default_ytdlp_channel(),
             cookies_browser: None,
+            restrict_filenames: default_restrict_filenames(),
+            embed_native_subs: false,
+            auth_username: None,
+            auth_password: None,
+            use_netrc: false,
+            preferred_vcodec: None,
+            preferred_acodec: None,
+            format_sort: None,
+            max_filesize: None,
+            completion_webhook: None,
+            completion_webhook_secret: None,
+            post_download_command: None,
+            min_confidence: None,
+            sherpa_decoding_method: None,
+            sherpa_max_active_paths: None,
+            sherpa_feature_dim: None,
+            max_cue_chars: None,
+            max_cue_duration_secs: None,
+            subtitle_offset_ms: 0,
+            karaoke: KaraokeStyle::default(),
+            offline: false,
+            model_mirror_base: None,
+            hf_token: None,
+            ytdlp_mirror_base: None,
+            proxy: None,
+            max_transcription_minutes: None,
+            download_retries: default_download_retries(),
+            connect_timeout_secs: default_connect_timeout_secs(),
+            read_timeout_secs: default_read_timeout_secs(),
+            fallback_models: default_fallback_models(),
+            subtitle_failure_policy: default_subtitle_failure_policy(),
+            diarize: false,
+            tighten_cues: false,
+            log_level: default_log_level(),
+            module_log_levels: HashMap::new(),
+            normalize_audio: false,
         }
     }
 }
 
 impl AppConfig {
+    /// Site login handed to yt-dlp for info fetches and downloads
+    pub fn credentials(&self) -> Credentials {
+        Credentials {
+            username: self.auth_username.clone(),
+            password: self.auth_password.clone(),
+            use_netrc: self.use_netrc,
+        }
+    }
+
     pub fn load() -> Self {
         if let Some(config_dir) = dirs::config_dir() {
             let config_path = config_dir.join("zinc").join("config.json");
@@ -80,6 +319,63 @@ impl AppConfig {
                             // Save the migrated config
                             let _ = config.save();
                         }
+                        // Drop a hand-edited cookies browser yt-dlp would reject
+                        if let Some(browser) = &config.cookies_browser {
+                            if let Err(e) = crate::ytdlp::YtDlp::validate_cookies_browser(browser) {
+                                log::warn!("{}; ignoring cookies_browser", e);
+                                config.cookies_browser = None;
+                            }
+                        }
+                        // Same for codec preferences the format selector can't express
+                        if let Err(e) = crate::ytdlp::YtDlp::validate_codec_preference(
+                            config.preferred_vcodec.as_deref(),
+                            config.preferred_acodec.as_deref(),
+                        ) {
+                            log::warn!("{}; ignoring codec preferences", e);
+                            config.preferred_vcodec = None;
+                            config.preferred_acodec = None;
+                        }
+                        if let Some(e) = config.format_sort.as_deref().and_then(|s| crate::ytdlp::YtDlp::validate_format_sort(s).err()) {
+                            log::warn!("{}; ignoring format_sort", e);
+                            config.format_sort = None;
+                        }
+                        if let Some(e) = config.max_filesize.as_deref().and_then(|s| crate::ytdlp::YtDlp::validate_max_filesize(s).err()) {
+                            log::warn!("{}; ignoring max_filesize", e);
+                            config.max_filesize = None;
+                        }
+                        if config.min_confidence.is_some_and(|c| !(0.0..=1.0).contains(&c)) {
+                            log::warn!("min_confidence must be between 0 and 1; ignoring it");
+                            config.min_confidence = None;
+                        }
+                        for mirror in [&mut config.model_mirror_base, &mut config.ytdlp_mirror_base] {
+                            if let Some(e) = mirror.as_deref().and_then(|m| crate::network::validate_mirror_base(m).err()) {
+                                log::warn!("{}; ignoring it", e);
+                                *mirror = None;
+                            }
+                        }
+                        if let Some(e) = config.proxy.as_deref().and_then(|p| crate::network::validate_proxy(p).err()) {
+                            log::warn!("{}; ignoring it", e);
+                            config.proxy = None;
+                        }
+                        if let Err(e) = config.validate_subtitle_failure_policy() {
+                            log::warn!("{}; using the default", e);
+                            config.subtitle_failure_policy = default_subtitle_failure_policy();
+                        }
+                        if let Err(e) = config.validate_sherpa_settings() {
+                            log::warn!("{}; using sherpa's defaults", e);
+                            config.sherpa_decoding_method = None;
+                            config.sherpa_max_active_paths = None;
+                            config.sherpa_feature_dim = None;
+                        }
+                        if let Err(e) = config.karaoke.validate() {
+                            log::warn!("{}; using the default karaoke style", e);
+                            config.karaoke = KaraokeStyle::default();
+                        }
+                        if let Err(e) = config.validate_cue_limits() {
+                            log::warn!("{}; ignoring cue limits", e);
+                            config.max_cue_chars = None;
+                            config.max_cue_duration_secs = None;
+                        }
                         return config;
                     }
                 }
@@ -88,10 +384,67 @@ impl AppConfig {
         Self::default()
     }
 
+    /// Reject cue limits too small to produce readable subtitles
+    pub fn validate_cue_limits(&self) -> Result<(), String> {
+        if self.max_cue_chars.is_some_and(|c| c < 10) {
+            return Err("Maximum cue length must be at least 10 characters".to_string());
+        }
+        if self.max_cue_duration_secs.is_some_and(|d| !d.is_finite() || d < 1.0) {
+            return Err("Maximum cue duration must be at least 1 second".to_string());
+        }
+        Ok(())
+    }
+
+    /// Reject sherpa decoding settings sherpa-onnx-offline would refuse
+    pub fn validate_sherpa_settings(&self) -> Result<(), String> {
+        use crate::sherpa_manager::SHERPA_DECODING_METHODS;
+
+        if let Some(method) = &self.sherpa_decoding_method {
+            if !SHERPA_DECODING_METHODS.contains(&method.as_str()) {
+                return Err(format!(
+                    "Unknown sherpa decoding method '{}'. Supported: {}",
+                    method,
+                    SHERPA_DECODING_METHODS.join(", ")
+                ));
+            }
+        }
+        if self.sherpa_max_active_paths.is_some_and(|p| !(1..=64).contains(&p)) {
+            return Err("Max active paths must be between 1 and 64".to_string());
+        }
+        if self.sherpa_feature_dim.is_some_and(|d| !(1..=512).contains(&d)) {
+            return Err("Feature dimension must be between 1 and 512".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn validate_log_levels(&self) -> Result<(), String> {
+        crate::logging::parse_level(&self.log_level)?;
+        for (module, level) in &self.module_log_levels {
+            crate::logging::parse_level(level).map_err(|e| format!("{} (module '{}')", e, module))?;
+        }
+        Ok(())
+    }
+
+    pub fn validate_subtitle_failure_policy(&self) -> Result<(), String> {
+        if !SUBTITLE_FAILURE_POLICIES.contains(&self.subtitle_failure_policy.as_str()) {
+            return Err(format!(
+                "Unknown subtitle failure policy '{}'. Supported: {}",
+                self.subtitle_failure_policy,
+                SUBTITLE_FAILURE_POLICIES.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    /// Folder holding config.json
+    pub fn config_dir() -> Result<PathBuf, String> {
+        dirs::config_dir()
+            .map(|dir| dir.join("zinc"))
+            .ok_or_else(|| "Could not find config directory".to_string())
+    }
+
     pub fn save(&self) -> Result<(), String> {
-        let config_dir = dirs::config_dir()
-            .ok_or("Could not find config directory")?
-            .join("zinc");
+        let config_dir = Self::config_dir()?;
 
         std::fs::create_dir_all(&config_dir)
             .map_err(|e| format!("Failed to create config directory: {}", e))?;
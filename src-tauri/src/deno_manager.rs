@@ -1,4 +1,3 @@
-use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::process::Stdio;
@@ -91,6 +90,37 @@ impl DenoManager {
         Ok(version)
     }
 
+    /// Fetch the latest release version from GitHub API, without the leading "v"
+    /// so it compares directly against get_installed_version
+    pub async fn get_latest_version() -> Result<String, String> {
+        crate::network::ensure_online("checking for Deno updates")?;
+
+        let client = crate::network::http_client();
+        let response = client
+            .get("https://api.github.com/repos/denoland/deno/releases/latest")
+            .header("User-Agent", "Zinc-App")
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch latest version: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!(
+                "GitHub API returned status: {}",
+                response.status()
+            ));
+        }
+
+        let json: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse GitHub response: {}", e))?;
+
+        json["tag_name"]
+            .as_str()
+            .map(|s| s.trim_start_matches('v').to_string())
+            .ok_or_else(|| "Could not find tag_name in GitHub response".to_string())
+    }
+
     /// Get the current status of deno
     pub async fn check_status() -> DenoStatus {
         let binary_path = match Self::get_binary_path() {
@@ -143,15 +173,16 @@ impl DenoManager {
             .await
             .map_err(|e| format!("Failed to create bin directory: {}", e))?;
 
+        crate::network::ensure_online("installing Deno")?;
+
         let download_url = Self::get_download_url();
 
-        let client = reqwest::Client::new();
-        let response = client
-            .get(&download_url)
-            .header("User-Agent", "Zinc-App")
-            .send()
-            .await
-            .map_err(|e| format!("Failed to download Deno: {}", e))?;
+        let client = crate::network::http_client();
+        let response = crate::network::send_with_retry(
+            client.get(&download_url).header("User-Agent", "Zinc-App"),
+        )
+        .await
+        .map_err(|e| format!("Failed to download Deno: {}", e))?;
 
         if !response.status().is_success() {
             return Err(format!(
@@ -171,7 +202,7 @@ impl DenoManager {
         let mut downloaded: u64 = 0;
         let mut stream = response.bytes_stream();
 
-        while let Some(chunk) = stream.next().await {
+        while let Some(chunk) = crate::network::next_chunk(&mut stream).await? {
             let chunk = chunk.map_err(|e| format!("Download error: {}", e))?;
             file.write_all(&chunk)
                 .await
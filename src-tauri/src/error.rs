@@ -0,0 +1,83 @@
+use serde::Serialize;
+use std::fmt;
+
+/// Error returned to the frontend as `{ code, message }` so the UI can react
+/// to specific failures without matching on message text
+/// The variant is chosen where the error is raised; plain String errors are Other
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "code", content = "message", rename_all = "snake_case")]
+pub enum AppError {
+    FfmpegMissing(String),
+    ModelMissing(String),
+    Network(String),
+    AuthRequired(String),
+    ConfirmationRequired(String),
+    OutputUnavailable(String),
+    AlreadyQueued(String),
+    Cancelled(String),
+    Unsupported(String),
+    Other(String),
+}
+
+impl AppError {
+    /// Human-readable message, regardless of variant
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::FfmpegMissing(m)
+            | AppError::ModelMissing(m)
+            | AppError::Network(m)
+            | AppError::AuthRequired(m)
+            | AppError::ConfirmationRequired(m)
+            | AppError::OutputUnavailable(m)
+            | AppError::AlreadyQueued(m)
+            | AppError::Cancelled(m)
+            | AppError::Unsupported(m)
+            | AppError::Other(m) => m,
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for AppError {}
+
+/// Errors from the String-based managers and engines carry no code
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::Other(message)
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        AppError::from(message.to_string())
+    }
+}
+
+/// For String-based callers; the code is dropped
+impl From<AppError> for String {
+    fn from(e: AppError) -> Self {
+        match e {
+            AppError::FfmpegMissing(m)
+            | AppError::ModelMissing(m)
+            | AppError::Network(m)
+            | AppError::AuthRequired(m)
+            | AppError::ConfirmationRequired(m)
+            | AppError::OutputUnavailable(m)
+            | AppError::AlreadyQueued(m)
+            | AppError::Cancelled(m)
+            | AppError::Unsupported(m)
+            | AppError::Other(m) => m,
+        }
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(e: reqwest::Error) -> Self {
+        AppError::Network(format!("Network error: {}", e))
+    }
+}
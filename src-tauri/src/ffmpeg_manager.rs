@@ -0,0 +1,256 @@
+use std::path::PathBuf;
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::ytdlp_manager::InstallProgress;
+
+const APP_IDENTIFIER: &str = "com.zinc.app";
+
+pub struct FfmpegManager;
+
+impl FfmpegManager {
+    /// Returns the app's bin directory path
+    pub fn get_bin_dir() -> Result<PathBuf, String> {
+        // Same location as the yt-dlp and deno binaries
+        let base_dir = if cfg!(any(target_os = "windows", target_os = "macos")) {
+            dirs::data_dir()
+        } else {
+            dirs::data_local_dir()
+        };
+
+        base_dir
+            .map(|p| p.join(APP_IDENTIFIER).join("bin"))
+            .ok_or_else(|| "Could not determine app data directory".to_string())
+    }
+
+    /// File name of a tool for the current platform ("ffmpeg" -> "ffmpeg.exe" on Windows)
+    fn binary_name(tool: &str) -> String {
+        if cfg!(target_os = "windows") {
+            format!("{}.exe", tool)
+        } else {
+            tool.to_string()
+        }
+    }
+
+    /// Returns the full path to the managed ffmpeg binary
+    pub fn get_ffmpeg_path() -> Result<PathBuf, String> {
+        Ok(Self::get_bin_dir()?.join(Self::binary_name("ffmpeg")))
+    }
+
+    /// Returns the full path to the managed ffprobe binary
+    pub fn get_ffprobe_path() -> Result<PathBuf, String> {
+        Ok(Self::get_bin_dir()?.join(Self::binary_name("ffprobe")))
+    }
+
+    /// Managed binary if installed, otherwise the bare name for PATH lookup
+    pub fn resolve_binary(tool: &str) -> PathBuf {
+        let name = Self::binary_name(tool);
+        if let Ok(bin_dir) = Self::get_bin_dir() {
+            let managed = bin_dir.join(&name);
+            if managed.exists() {
+                return managed;
+            }
+        }
+        PathBuf::from(name)
+    }
+
+    /// Static build archives for the current platform
+    /// Each zip contains ffmpeg and/or ffprobe somewhere inside
+    fn get_download_urls() -> Vec<&'static str> {
+        if cfg!(target_os = "windows") {
+            vec!["https://github.com/BtbN/FFmpeg-Builds/releases/download/latest/ffmpeg-master-latest-win64-gpl.zip"]
+        } else if cfg!(target_os = "macos") {
+            if cfg!(target_arch = "aarch64") {
+                vec![
+                    "https://ffmpeg.martin-riedl.de/redirect/latest/macos/arm64/release/ffmpeg.zip",
+                    "https://ffmpeg.martin-riedl.de/redirect/latest/macos/arm64/release/ffprobe.zip",
+                ]
+            } else {
+                vec![
+                    "https://ffmpeg.martin-riedl.de/redirect/latest/macos/amd64/release/ffmpeg.zip",
+                    "https://ffmpeg.martin-riedl.de/redirect/latest/macos/amd64/release/ffprobe.zip",
+                ]
+            }
+        } else if cfg!(target_arch = "aarch64") {
+            vec![
+                "https://ffmpeg.martin-riedl.de/redirect/latest/linux/arm64/release/ffmpeg.zip",
+                "https://ffmpeg.martin-riedl.de/redirect/latest/linux/arm64/release/ffprobe.zip",
+            ]
+        } else {
+            vec![
+                "https://ffmpeg.martin-riedl.de/redirect/latest/linux/amd64/release/ffmpeg.zip",
+                "https://ffmpeg.martin-riedl.de/redirect/latest/linux/amd64/release/ffprobe.zip",
+            ]
+        }
+    }
+
+    /// Install static ffmpeg and ffprobe builds into the app bin directory
+    /// Returns the installed ffmpeg path
+    pub async fn install<F>(progress_callback: F) -> Result<String, String>
+    where
+        F: Fn(InstallProgress) + Send + 'static,
+    {
+        crate::network::ensure_online("installing ffmpeg")?;
+
+        let bin_dir = Self::get_bin_dir()?;
+
+        fs::create_dir_all(&bin_dir)
+            .await
+            .map_err(|e| format!("Failed to create bin directory: {}", e))?;
+
+        let urls = Self::get_download_urls();
+        let archive_count = urls.len() as f64;
+        let client = crate::network::http_client();
+        let mut downloaded: u64 = 0;
+
+        for (index, url) in urls.iter().enumerate() {
+            let response = crate::network::send_with_retry(
+                client.get(*url).header("User-Agent", "Zinc-App"),
+            )
+            .await
+            .map_err(|e| format!("Failed to download ffmpeg: {}", e))?;
+
+            if !response.status().is_success() {
+                return Err(format!(
+                    "Download failed with status: {}",
+                    response.status()
+                ));
+            }
+
+            let archive_size = response.content_length();
+            let zip_path = bin_dir.join(format!("ffmpeg_download_{}.zip", index));
+            let mut file = fs::File::create(&zip_path)
+                .await
+                .map_err(|e| format!("Failed to create temp file: {}", e))?;
+
+            let mut archive_downloaded: u64 = 0;
+            let mut stream = response.bytes_stream();
+
+            while let Some(chunk) = crate::network::next_chunk(&mut stream).await? {
+                let chunk = match chunk {
+                    Ok(c) => c,
+                    Err(e) => {
+                        drop(file);
+                        let _ = fs::remove_file(&zip_path).await;
+                        return Err(format!("Download error: {}", e));
+                    }
+                };
+                file.write_all(&chunk)
+                    .await
+                    .map_err(|e| format!("Failed to write file: {}", e))?;
+
+                archive_downloaded += chunk.len() as u64;
+                downloaded += chunk.len() as u64;
+
+                // Each archive gets an equal share of the overall percentage
+                let archive_fraction = archive_size
+                    .map(|t| archive_downloaded as f64 / t as f64)
+                    .unwrap_or(0.0);
+
+                progress_callback(InstallProgress {
+                    downloaded,
+                    total: if urls.len() == 1 { archive_size } else { None },
+                    percentage: (index as f64 + archive_fraction) / archive_count * 100.0,
+                });
+            }
+
+            file.flush()
+                .await
+                .map_err(|e| format!("Failed to flush file: {}", e))?;
+            drop(file);
+
+            let zip_path_clone = zip_path.clone();
+            let bin_dir_clone = bin_dir.clone();
+            let extract_result = tokio::task::spawn_blocking(move || {
+                Self::extract_binaries(&zip_path_clone, &bin_dir_clone)
+            })
+            .await
+            .map_err(|e| format!("Extract task failed: {}", e))?;
+
+            let _ = fs::remove_file(&zip_path).await;
+            extract_result?;
+        }
+
+        let ffmpeg_path = Self::get_ffmpeg_path()?;
+        let ffprobe_path = Self::get_ffprobe_path()?;
+
+        for path in [&ffmpeg_path, &ffprobe_path] {
+            if !path.exists() {
+                return Err(format!(
+                    "ffmpeg download did not contain {}",
+                    path.file_name().unwrap_or_default().to_string_lossy()
+                ));
+            }
+
+            // Set executable permission on Unix
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let mut perms = fs::metadata(path)
+                    .await
+                    .map_err(|e| format!("Failed to get file metadata: {}", e))?
+                    .permissions();
+                perms.set_mode(0o755);
+                fs::set_permissions(path, perms)
+                    .await
+                    .map_err(|e| format!("Failed to set executable permission: {}", e))?;
+            }
+        }
+
+        // Verify the binary actually runs on this machine
+        let mut cmd = Command::new(&ffmpeg_path);
+        cmd.arg("-version");
+
+        #[cfg(target_os = "windows")]
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+        let output = cmd
+            .output()
+            .await
+            .map_err(|e| format!("Failed to execute ffmpeg: {}", e))?;
+        if !output.status.success() {
+            return Err("Installed ffmpeg failed to run".to_string());
+        }
+
+        Ok(ffmpeg_path.to_string_lossy().to_string())
+    }
+
+    /// Pull ffmpeg/ffprobe out of a zip, ignoring the archive's folder layout
+    fn extract_binaries(zip_path: &std::path::Path, bin_dir: &std::path::Path) -> Result<(), String> {
+        let file = std::fs::File::open(zip_path)
+            .map_err(|e| format!("Failed to open zip file: {}", e))?;
+        let mut archive =
+            zip::ZipArchive::new(file).map_err(|e| format!("Failed to read zip: {}", e))?;
+
+        let wanted = [Self::binary_name("ffmpeg"), Self::binary_name("ffprobe")];
+
+        for i in 0..archive.len() {
+            let mut entry = archive
+                .by_index(i)
+                .map_err(|e| format!("Failed to read zip entry: {}", e))?;
+            if entry.is_dir() {
+                continue;
+            }
+
+            let name = entry.name().to_string();
+            let file_name = name.rsplit('/').next().unwrap_or(&name);
+            if !wanted.iter().any(|w| w == file_name) {
+                continue;
+            }
+
+            // Write to a temp name so a failed extract never leaves a truncated binary
+            let target = bin_dir.join(file_name);
+            let temp = bin_dir.join(format!("{}.tmp", file_name));
+            let mut outfile = std::fs::File::create(&temp)
+                .map_err(|e| format!("Failed to create binary file: {}", e))?;
+            std::io::copy(&mut entry, &mut outfile)
+                .map_err(|e| format!("Failed to extract binary: {}", e))?;
+            drop(outfile);
+            std::fs::rename(&temp, &target)
+                .map_err(|e| format!("Failed to install {}: {}", file_name, e))?;
+        }
+
+        Ok(())
+    }
+}
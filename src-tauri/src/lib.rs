@@ -1,7 +1,12 @@
 mod commands;
-mod config;
+pub mod config;
 mod deno_manager;
+mod error;
+mod ffmpeg_manager;
+mod logging;
+mod media;
 mod network;
+pub mod pipeline;
 mod sherpa_manager;
 mod transcription;
 mod transcription_manager;
@@ -10,8 +15,17 @@ mod whisper_manager;
 mod ytdlp;
 mod ytdlp_manager;
 
+// Reached by the integration tests in tests/; not part of the headless API
+#[doc(hidden)]
+pub use transcription::WhisperRsEngine;
+
 use commands::AppState;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use tauri::Manager;
+
+/// Set once the window starts closing, so repeated close clicks don't start a second shutdown
+static CLOSING: AtomicBool = AtomicBool::new(false);
 
 /// On macOS, GUI apps launched from Finder/Spotlight get a minimal PATH
 /// that doesn't include Homebrew or MacPorts paths. This ensures commonly
@@ -46,28 +60,60 @@ pub fn run() {
 
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .setup(|app| {
-            if cfg!(debug_assertions) {
-                app.handle().plugin(
-                    tauri_plugin_log::Builder::default()
-                        .level(log::LevelFilter::Info)
-                        .build(),
-                )?;
-            }
+            app.handle().plugin(logging::plugin(&config::AppConfig::load()))?;
             Ok(())
         })
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                // Hold the window open until running tasks are stopped and cleaned up
+                api.prevent_close();
+                if CLOSING.swap(true, Ordering::SeqCst) {
+                    return;
+                }
+                let app = window.app_handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    let state = Arc::clone(app.state::<Arc<AppState>>().inner());
+                    commands::shutdown(&app, &state).await;
+                    app.exit(0);
+                });
+            }
+        })
         .manage(state)
         .invoke_handler(tauri::generate_handler![
             commands::check_ytdlp,
             commands::get_video_info,
             commands::start_download,
+            commands::preview_download_command,
+            commands::estimate_download_size,
+            commands::get_format_details,
             commands::cancel_download,
+            commands::cancel_all,
             commands::get_downloads,
+            commands::get_download_log,
+            commands::get_subtitle_preview,
+            commands::copy_to_clipboard,
+            commands::copy_output_path,
+            commands::copy_transcript,
+            commands::update_subtitles,
+            commands::create_clips,
+            commands::remux,
+            commands::burn_karaoke,
+            commands::get_app_stats,
+            commands::get_diagnostics,
+            commands::refresh_gpu_status,
+            commands::set_auth_password,
+            commands::get_diarization_status,
+            commands::install_diarization,
             commands::clear_download,
             commands::clear_completed_downloads,
             commands::get_config,
             commands::update_config,
+            commands::set_log_level,
             commands::open_file,
+            commands::open_models_dir,
+            commands::open_config_dir,
             commands::open_folder,
             commands::get_format_presets,
             commands::get_ytdlp_status,
@@ -80,21 +126,31 @@ pub fn run() {
             commands::download_whisper_model,
             commands::get_available_whisper_models,
             commands::check_ffmpeg,
+            commands::get_ffmpeg_info,
             // Transcription engine commands
             commands::get_transcription_engines,
             commands::get_engine_models,
             commands::install_transcription_engine,
             commands::download_transcription_model,
+            commands::repair_model,
+            commands::preview_transcription,
+            commands::shift_subtitles,
+            commands::download_models,
+            commands::cancel_model_download,
             commands::get_transcription_speed_multiplier,
             // Local file transcription (unified with downloads)
             commands::add_local_transcription,
             commands::start_local_transcription,
+            commands::start_remote_transcription,
             commands::update_transcription_settings,
             // Network interface
             commands::list_network_interfaces,
+            commands::test_interface,
             // Deno manager
             commands::get_deno_status,
             commands::install_deno,
+            commands::install_ffmpeg,
+            commands::update_all,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
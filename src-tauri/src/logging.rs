@@ -0,0 +1,72 @@
+use crate::config::AppConfig;
+use log::LevelFilter;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tauri::plugin::TauriPlugin;
+use tauri::Runtime;
+
+/// Global level as `LevelFilter as usize`, read by the log filter on every record
+/// so set_log_level takes effect without restarting
+static LEVEL: AtomicUsize = AtomicUsize::new(LevelFilter::Info as usize);
+
+const LEVELS: [LevelFilter; 6] = [
+    LevelFilter::Off,
+    LevelFilter::Error,
+    LevelFilter::Warn,
+    LevelFilter::Info,
+    LevelFilter::Debug,
+    LevelFilter::Trace,
+];
+
+/// Parse "off", "error", "warn", "info", "debug" or "trace" (any case)
+pub fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    level.trim().parse().map_err(|_| {
+        format!(
+            "Unknown log level '{}'. Supported: off, error, warn, info, debug, trace",
+            level
+        )
+    })
+}
+
+fn current_level() -> LevelFilter {
+    LEVELS
+        .get(LEVEL.load(Ordering::Relaxed))
+        .copied()
+        .unwrap_or(LevelFilter::Info)
+}
+
+/// Change the global level at runtime; per-module levels are untouched
+pub fn set_level(level: LevelFilter) {
+    LEVEL.store(level as usize, Ordering::Relaxed);
+}
+
+/// The log plugin, filtering by config.log_level and config.module_log_levels
+/// A module entry (e.g. "zinc::transcription") wins over the global level for its targets
+pub fn plugin<R: Runtime>(config: &AppConfig) -> TauriPlugin<R> {
+    set_level(parse_level(&config.log_level).unwrap_or(LevelFilter::Info));
+
+    let modules: HashMap<String, LevelFilter> = config
+        .module_log_levels
+        .iter()
+        .filter_map(|(module, level)| match parse_level(level) {
+            Ok(level) => Some((module.clone(), level)),
+            Err(e) => {
+                log::warn!("{}; ignoring level for '{}'", e, module);
+                None
+            }
+        })
+        .collect();
+
+    tauri_plugin_log::Builder::default()
+        // Everything reaches the filter, which applies the (changeable) configured levels
+        .level(LevelFilter::Trace)
+        .filter(move |metadata| {
+            let level = modules
+                .iter()
+                .filter(|(module, _)| metadata.target().starts_with(module.as_str()))
+                .max_by_key(|(module, _)| module.len())
+                .map_or_else(current_level, |(_, level)| *level);
+            metadata.level() <= level
+        })
+        .build()
+}
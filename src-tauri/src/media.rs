@@ -0,0 +1,339 @@
+use crate::error::AppError;
+use crate::ffmpeg_manager::FfmpegManager;
+use base64::Engine;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::io::AsyncReadExt;
+use tokio::process::Command;
+use tokio::sync::watch;
+
+/// Build a Command for ffmpeg, preferring the managed binary over PATH
+/// Console window suppression is already applied on Windows
+pub fn ffmpeg_command() -> Command {
+    configured_command("ffmpeg")
+}
+
+/// Build a Command for ffprobe, preferring the managed binary over PATH
+/// Console window suppression is already applied on Windows
+pub fn ffprobe_command() -> Command {
+    configured_command("ffprobe")
+}
+
+fn configured_command(tool: &str) -> Command {
+    #[allow(unused_mut)]
+    let mut cmd = Command::new(FfmpegManager::resolve_binary(tool));
+
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+    cmd
+}
+
+/// SRT text for a task's output: the .srt sidecar when there is one, otherwise the embedded track
+pub async fn read_subtitles(output_path: &Path) -> Result<String, AppError> {
+    let sidecar = output_path.with_extension("srt");
+    if sidecar.exists() {
+        tokio::fs::read_to_string(&sidecar)
+            .await
+            .map_err(|e| AppError::Other(format!("Failed to read subtitles: {}", e)))
+    } else {
+        extract_subtitles(output_path).await
+    }
+}
+
+/// Width and height of a file's first video stream
+pub async fn video_dimensions(path: &Path) -> Option<(u32, u32)> {
+    let output = ffprobe_command()
+        .args(["-v", "error", "-select_streams", "v:0", "-show_entries", "stream=width,height", "-of", "csv=p=0:s=x"])
+        .arg(path)
+        .stderr(Stdio::null())
+        .output()
+        .await
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let (width, height) = text.lines().next()?.trim().split_once('x')?;
+    Some((width.parse().ok()?, height.parse().ok()?))
+}
+
+/// Read the first subtitle track of a media file as SRT text
+pub async fn extract_subtitles(media_path: &Path) -> Result<String, AppError> {
+    let output = ffmpeg_command()
+        .args(["-v", "error", "-i"])
+        .arg(media_path)
+        .args(["-map", "0:s:0", "-f", "srt", "-"])
+        .output()
+        .await
+        .map_err(|e| AppError::FfmpegMissing(format!("Failed to run ffmpeg: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(AppError::Other(format!(
+            "No subtitle track found: {}",
+            stderr.lines().next().unwrap_or("ffmpeg failed")
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Grab a small JPEG poster frame as a data: URL, so local files get a thumbnail like downloads
+/// None for audio-only files or anything ffmpeg can't decode a frame from
+pub async fn extract_poster_frame(media_path: &Path, duration: Option<f64>) -> Option<String> {
+    // One second in skips black lead-in frames; very short clips use their midpoint
+    let seek = duration.map_or(1.0, |d| (d / 2.0).min(1.0));
+    let output = ffmpeg_command()
+        .args(["-v", "error", "-ss", &format!("{:.3}", seek), "-i"])
+        .arg(media_path)
+        .args(["-frames:v", "1", "-vf", "scale=320:-2", "-f", "image2pipe", "-c:v", "mjpeg", "-"])
+        .output()
+        .await
+        .ok()?;
+
+    if !output.status.success() || output.stdout.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "data:image/jpeg;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(&output.stdout)
+    ))
+}
+
+/// Stream-copy `duration` seconds starting at `start` into a new file
+/// No re-encode, so the cut starts at the keyframe at or before `start`
+pub async fn cut_clip(
+    input: &Path,
+    output: &Path,
+    start: f64,
+    duration: f64,
+    cancel_rx: watch::Receiver<bool>,
+) -> Result<(), AppError> {
+    let mut cmd = ffmpeg_command();
+    cmd.args(["-v", "error", "-n", "-ss", &format!("{:.3}", start), "-i"])
+        .arg(input)
+        .args(["-t", &format!("{:.3}", duration)])
+        .args(["-map", "0", "-c", "copy", "-avoid_negative_ts", "make_zero"]);
+
+    run_ffmpeg(cmd, output, cancel_rx, "Clip").await
+}
+
+/// ffmpeg filter evening out loudness before transcription when normalize_audio is on
+/// It resamples internally, so the -ar/-ac that follow still pin the output format
+pub const LOUDNORM_FILTER: &str = "loudnorm=I=-16:TP=-1.5:LRA=11";
+
+/// First `seconds` of a file's audio as 16kHz mono WAV, the format the engines read
+/// `normalize` applies the same loudness filter as a full extraction
+pub async fn extract_audio_sample(input: &Path, output: &Path, seconds: f64, normalize: bool) -> Result<(), AppError> {
+    let mut cmd = ffmpeg_command();
+    cmd.args(["-v", "error", "-y", "-i"])
+        .arg(input)
+        .args(["-t", &format!("{:.3}", seconds)])
+        .arg("-vn");
+    if normalize {
+        cmd.args(["-af", LOUDNORM_FILTER]);
+    }
+    let result = cmd
+        .args(["-acodec", "pcm_s16le", "-ar", "16000", "-ac", "1"])
+        .arg(output)
+        .output()
+        .await
+        .map_err(|e| AppError::FfmpegMissing(format!("Failed to run ffmpeg: {}", e)))?;
+
+    if !result.status.success() {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        return Err(AppError::Other(format!(
+            "Audio extraction failed: {}",
+            stderr.lines().next().unwrap_or("ffmpeg failed")
+        )));
+    }
+    Ok(())
+}
+
+/// Video and audio codecs a container can hold without re-encoding; None means anything (mkv)
+fn container_codecs(ext: &str) -> Option<(&'static [&'static str], &'static [&'static str])> {
+    match ext {
+        "mp4" | "m4v" | "mov" => Some((
+            &["h264", "hevc", "mpeg4", "av1"],
+            &["aac", "mp3", "ac3", "eac3", "alac"],
+        )),
+        "webm" => Some((&["vp8", "vp9", "av1"], &["opus", "vorbis"])),
+        _ => None,
+    }
+}
+
+/// Codec names of a file's streams of one kind ("v", "a" or "s")
+async fn stream_codecs(path: &Path, kind: &str) -> Result<Vec<String>, AppError> {
+    let output = ffprobe_command()
+        .args(["-v", "error", "-select_streams", kind, "-show_entries", "stream=codec_name", "-of", "csv=p=0"])
+        .arg(path)
+        .stderr(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| AppError::FfmpegMissing(format!("Failed to run ffprobe: {}", e)))?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect())
+}
+
+/// Rewrite `input` into the container implied by `output`'s extension
+/// Streams are copied where the container allows it; video it can't hold is re-encoded
+/// (H.264 for mp4/mov, VP9 for webm), audio likewise (AAC / Opus), and subtitle
+/// tracks are converted to `subtitle_codec`
+pub async fn remux(
+    input: &Path,
+    output: &Path,
+    subtitle_codec: &str,
+    cancel_rx: watch::Receiver<bool>,
+) -> Result<(), AppError> {
+    let ext = output
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    let (video_ok, audio_ok) = match container_codecs(&ext) {
+        Some((video, audio)) => {
+            let fits = |codecs: Vec<String>, allowed: &[&str]| codecs.iter().all(|c| allowed.contains(&c.as_str()));
+            (fits(stream_codecs(input, "v").await?, video), fits(stream_codecs(input, "a").await?, audio))
+        }
+        None => (true, true),
+    };
+    let webm = ext == "webm";
+
+    let mut cmd = ffmpeg_command();
+    cmd.args(["-v", "error", "-n", "-i"])
+        .arg(input)
+        .args(["-map", "0:v?", "-map", "0:a?", "-map", "0:s?"]);
+    match (video_ok, webm) {
+        (true, _) => cmd.args(["-c:v", "copy"]),
+        (false, true) => cmd.args(["-c:v", "libvpx-vp9", "-crf", "32", "-b:v", "0"]),
+        (false, false) => cmd.args(["-c:v", "libx264", "-crf", "20", "-preset", "medium"]),
+    };
+    match (audio_ok, webm) {
+        (true, _) => cmd.args(["-c:a", "copy"]),
+        (false, true) => cmd.args(["-c:a", "libopus", "-b:a", "160k"]),
+        (false, false) => cmd.args(["-c:a", "aac", "-b:a", "192k"]),
+    };
+    cmd.args(["-c:s", subtitle_codec]);
+    if !webm {
+        // Lets players start before the whole file is read
+        cmd.args(["-movflags", "+faststart"]);
+    }
+
+    log::info!(
+        "Remuxing {:?} to .{} (video {}, audio {})",
+        input,
+        ext,
+        if video_ok { "copied" } else { "re-encoded" },
+        if audio_ok { "copied" } else { "re-encoded" }
+    );
+
+    run_ffmpeg(cmd, output, cancel_rx, "Remux").await
+}
+
+/// Run an ffmpeg command writing `output`, killing it once `cancel_rx` is set
+/// A failed or cancelled run removes the partial output; `action` prefixes the error
+async fn run_ffmpeg(
+    mut cmd: Command,
+    output: &Path,
+    mut cancel_rx: watch::Receiver<bool>,
+    action: &str,
+) -> Result<(), AppError> {
+    let mut child = cmd
+        .arg(output)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| AppError::FfmpegMissing(format!("Failed to run ffmpeg: {}", e)))?;
+
+    // Drain stderr in background to prevent pipe buffer deadlock
+    let mut stderr = child.stderr.take().expect("stderr was piped");
+    let stderr_task = tokio::spawn(async move {
+        let mut text = String::new();
+        let _ = stderr.read_to_string(&mut text).await;
+        text
+    });
+
+    let mut cancel_open = true;
+    let status = loop {
+        tokio::select! {
+            status = child.wait() => {
+                break status.map_err(|e| AppError::Other(format!("Failed to wait for ffmpeg: {}", e)))?;
+            }
+            changed = cancel_rx.changed(), if cancel_open => {
+                // A dropped sender can't cancel anymore; keep waiting on ffmpeg
+                if changed.is_err() {
+                    cancel_open = false;
+                } else if *cancel_rx.borrow() {
+                    let _ = child.kill().await;
+                    let _ = tokio::fs::remove_file(output).await;
+                    return Err(AppError::Cancelled("Cancelled".to_string()));
+                }
+            }
+        }
+    };
+
+    if !status.success() {
+        let _ = tokio::fs::remove_file(output).await;
+        let stderr = stderr_task.await.unwrap_or_default();
+        return Err(AppError::Other(format!(
+            "{} failed: {}",
+            action,
+            stderr.lines().next().unwrap_or("ffmpeg failed")
+        )));
+    }
+    Ok(())
+}
+
+/// Burn an ASS script into `input`'s video, writing an H.264 mp4 to `output`
+/// ffmpeg runs in a temp folder holding the script so the filter gets a bare file name;
+/// drive letters and quotes in full paths would need filtergraph escaping
+pub async fn burn_ass(
+    input: &Path,
+    ass: &str,
+    output: &Path,
+    cancel_rx: watch::Receiver<bool>,
+) -> Result<(), AppError> {
+    let work_dir = std::env::temp_dir().join(format!("zinc-burn-{}", uuid::Uuid::new_v4()));
+    tokio::fs::create_dir_all(&work_dir)
+        .await
+        .map_err(|e| AppError::Other(format!("Failed to create temp folder: {}", e)))?;
+    let result = run_burn(input, ass, output, &work_dir, cancel_rx).await;
+    let _ = tokio::fs::remove_dir_all(&work_dir).await;
+    result
+}
+
+async fn run_burn(
+    input: &Path,
+    ass: &str,
+    output: &Path,
+    work_dir: &Path,
+    cancel_rx: watch::Receiver<bool>,
+) -> Result<(), AppError> {
+    tokio::fs::write(work_dir.join("karaoke.ass"), ass)
+        .await
+        .map_err(|e| AppError::Other(format!("Failed to write subtitles: {}", e)))?;
+
+    let mp4_audio = container_codecs("mp4").map_or(&[][..], |(_, audio)| audio);
+    let audio_ok = stream_codecs(input, "a")
+        .await?
+        .iter()
+        .all(|c| mp4_audio.contains(&c.as_str()));
+
+    let mut cmd = ffmpeg_command();
+    cmd.current_dir(work_dir)
+        .args(["-v", "error", "-n", "-i"])
+        .arg(input)
+        .args(["-map", "0:v:0", "-map", "0:a?", "-vf", "ass=karaoke.ass"])
+        .args(["-c:v", "libx264", "-crf", "18", "-preset", "veryfast"]);
+    if audio_ok {
+        cmd.args(["-c:a", "copy"]);
+    } else {
+        cmd.args(["-c:a", "aac", "-b:a", "192k"]);
+    }
+    cmd.args(["-movflags", "+faststart"]);
+
+    log::info!("Burning karaoke subtitles into {:?}", input);
+
+    run_ffmpeg(cmd, output, cancel_rx, "Burn-in").await
+}
@@ -1,4 +1,297 @@
+use crate::config::AppConfig;
+use crate::error::AppError;
+use futures_util::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// Mirrors the `offline` config; when set, nothing may touch the network
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Replacement hosts for model and yt-dlp downloads, from the config
+static MIRRORS: RwLock<Mirrors> = RwLock::new(Mirrors { model: None, ytdlp: None });
+
+struct Mirrors {
+    model: Option<String>,
+    ytdlp: Option<String>,
+}
+
+/// `proxy` config; used by the shared client and passed to yt-dlp
+static PROXY: RwLock<Option<String>> = RwLock::new(None);
+
+/// `hf_token` config; sent only to huggingface.co, never logged
+static HF_TOKEN: RwLock<Option<String>> = RwLock::new(None);
+
+/// Client built from the settings above, rebuilt by apply_config
+/// reqwest clients share their connection pool between clones, so one is enough
+static CLIENT: RwLock<Option<reqwest::Client>> = RwLock::new(None);
+
+/// HTTP limits for model and binary downloads, from the config
+static DOWNLOAD_RETRIES: AtomicU32 = AtomicU32::new(3);
+static CONNECT_TIMEOUT_SECS: AtomicU64 = AtomicU64::new(15);
+static READ_TIMEOUT_SECS: AtomicU64 = AtomicU64::new(30);
+
+/// Apply the network-related config settings process-wide
+/// Called at startup and whenever the config is saved
+pub fn apply_config(config: &AppConfig) {
+    OFFLINE.store(config.offline, Ordering::SeqCst);
+    if let Ok(mut mirrors) = MIRRORS.write() {
+        mirrors.model = config.model_mirror_base.clone();
+        mirrors.ytdlp = config.ytdlp_mirror_base.clone();
+    }
+    DOWNLOAD_RETRIES.store(config.download_retries, Ordering::SeqCst);
+    CONNECT_TIMEOUT_SECS.store(config.connect_timeout_secs.max(1), Ordering::SeqCst);
+    READ_TIMEOUT_SECS.store(config.read_timeout_secs.max(1), Ordering::SeqCst);
+    if let Ok(mut proxy) = PROXY.write() {
+        *proxy = config.proxy.as_deref().map(str::trim).filter(|p| !p.is_empty()).map(str::to_string);
+    }
+    if let Ok(mut token) = HF_TOKEN.write() {
+        *token = config.hf_token.as_deref().map(str::trim).filter(|t| !t.is_empty()).map(str::to_string);
+    }
+    if let Ok(mut client) = CLIENT.write() {
+        *client = Some(build_client());
+    }
+}
+
+/// The shared client for every HTTP request the app makes, with the configured
+/// proxy and connect timeout. Stalls after connecting are caught per chunk by `next_chunk`
+pub fn http_client() -> reqwest::Client {
+    if let Some(client) = CLIENT.read().ok().and_then(|c| c.clone()) {
+        return client;
+    }
+    let client = build_client();
+    if let Ok(mut cached) = CLIENT.write() {
+        cached.get_or_insert_with(|| client.clone());
+    }
+    client
+}
+
+fn build_client() -> reqwest::Client {
+    client_builder().build().unwrap_or_else(|e| {
+        log::warn!("Failed to build HTTP client: {}; using defaults", e);
+        reqwest::Client::new()
+    })
+}
+
+/// Builder with the configured proxy and connect timeout
+fn client_builder() -> reqwest::ClientBuilder {
+    let connect_timeout = Duration::from_secs(CONNECT_TIMEOUT_SECS.load(Ordering::SeqCst));
+    let mut builder = reqwest::Client::builder().connect_timeout(connect_timeout);
+    if let Some(url) = proxy() {
+        match reqwest::Proxy::all(&url) {
+            Ok(p) => builder = builder.proxy(p),
+            Err(e) => log::warn!("Invalid proxy '{}': {}; connecting directly", url, e),
+        }
+    }
+    builder
+}
+
+/// The configured proxy URL, if any
+pub fn proxy() -> Option<String> {
+    PROXY.read().ok().and_then(|p| p.clone())
+}
+
+/// Send a request, retrying connection failures, timeouts and 5xx/429 responses
+/// with exponential backoff (1s, 2s, 4s...). The last error or response is returned as-is
+pub async fn send_with_retry(request: reqwest::RequestBuilder) -> Result<reqwest::Response, reqwest::Error> {
+    let retries = DOWNLOAD_RETRIES.load(Ordering::SeqCst);
+    let mut attempt = 0;
+    loop {
+        // Bodies that can't be cloned (streams) get a single attempt
+        let Some(this_try) = request.try_clone().filter(|_| attempt < retries) else {
+            return request.send().await;
+        };
+
+        let retryable = match this_try.send().await {
+            Ok(response)
+                if response.status().is_server_error()
+                    || response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS =>
+            {
+                format!("status {}", response.status())
+            }
+            Ok(response) => return Ok(response),
+            Err(e) if e.is_connect() || e.is_timeout() => e.to_string(),
+            Err(e) => return Err(e),
+        };
+
+        attempt += 1;
+        let delay = Duration::from_secs(1u64 << (attempt - 1).min(5));
+        log::warn!("Request failed ({}), retry {}/{} in {:?}", retryable, attempt, retries, delay);
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Next item of a download stream, failing if nothing arrives within the read timeout
+/// A stalled connection otherwise leaves the download hanging forever
+pub async fn next_chunk<S, T>(stream: &mut S) -> Result<Option<T>, AppError>
+where
+    S: Stream<Item = T> + Unpin,
+{
+    let secs = READ_TIMEOUT_SECS.load(Ordering::SeqCst);
+    tokio::time::timeout(Duration::from_secs(secs), stream.next())
+        .await
+        .map_err(|_| {
+            AppError::Network(format!(
+                "Download timed out: no data received for {} seconds. Check your connection and try again",
+                secs
+            ))
+        })
+}
+
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::SeqCst)
+}
+
+/// Check a mirror base is usable before it is saved
+pub fn validate_mirror_base(base: &str) -> Result<(), String> {
+    if !base.starts_with("http://") && !base.starts_with("https://") {
+        return Err(format!("Mirror URL must start with http:// or https://: {}", base));
+    }
+    Ok(())
+}
+
+/// Check a proxy URL is usable before it is saved
+pub fn validate_proxy(url: &str) -> Result<(), String> {
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err(format!("Proxy URL must start with http:// or https://: {}", url));
+    }
+    reqwest::Proxy::all(url).map_err(|e| format!("Invalid proxy URL '{}': {}", url, e))?;
+    Ok(())
+}
+
+/// Swap the scheme and host of a URL for the mirror base, keeping the path
+/// e.g. https://huggingface.co/a/b with base https://hf-mirror.com -> https://hf-mirror.com/a/b
+fn rewrite_host(url: &str, base: Option<&str>) -> String {
+    let Some(base) = base.map(str::trim).filter(|b| !b.is_empty()) else {
+        return url.to_string();
+    };
+    let path = url
+        .split_once("://")
+        .and_then(|(_, rest)| rest.find('/').map(|i| &rest[i..]))
+        .unwrap_or("");
+    format!("{}{}", base.trim_end_matches('/'), path)
+}
+
+/// URL for a model or engine runtime download (HuggingFace / GitHub releases)
+pub fn model_url(url: &str) -> String {
+    let mirrors = MIRRORS.read().ok();
+    rewrite_host(url, mirrors.as_ref().and_then(|m| m.model.as_deref()))
+}
+
+fn is_huggingface(url: &str) -> bool {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .is_some_and(|host| host == "huggingface.co" || host.ends_with(".huggingface.co"))
+}
+
+/// Add the configured HuggingFace token to a request for a huggingface.co URL
+/// Mirrors never see it, and reqwest drops it when the CDN redirect changes host
+pub fn with_hf_auth(request: reqwest::RequestBuilder, url: &str) -> reqwest::RequestBuilder {
+    if !is_huggingface(url) {
+        return request;
+    }
+    match HF_TOKEN.read().ok().and_then(|t| t.clone()) {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    }
+}
+
+/// Error for a failed model download response, explaining 401/403 from HuggingFace
+/// (gated models need an account that accepted their terms, plus a token)
+pub fn model_download_error(status: reqwest::StatusCode, url: &str) -> AppError {
+    let denied = status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN;
+    if !denied || !is_huggingface(url) {
+        return AppError::Network(format!("Download failed with status: {}", status));
+    }
+    let has_token = HF_TOKEN.read().ok().is_some_and(|t| t.is_some());
+    if has_token {
+        AppError::AuthRequired(format!(
+            "Authentication failed: HuggingFace rejected the configured token ({}). Check that it has read access \
+             and that you accepted the model's terms on its huggingface.co page",
+            status
+        ))
+    } else {
+        AppError::AuthRequired(format!(
+            "Authentication required: this model requires a HuggingFace token ({}). Accept the model's terms on \
+             its huggingface.co page, create a read token at https://huggingface.co/settings/tokens and set it as \
+             the HuggingFace token in Settings",
+            status
+        ))
+    }
+}
+
+/// URL for a yt-dlp binary download (GitHub releases)
+pub fn ytdlp_url(url: &str) -> String {
+    let mirrors = MIRRORS.read().ok();
+    rewrite_host(url, mirrors.as_ref().and_then(|m| m.ytdlp.as_deref()))
+}
+
+/// Refuse a network action with a clear message while offline mode is on
+pub fn ensure_online(action: &str) -> Result<(), AppError> {
+    if is_offline() {
+        return Err(AppError::Network(format!("Offline mode is on: {} needs a network connection", action)));
+    }
+    Ok(())
+}
+
+/// Plain-text IP echo service used by test_interface
+const IP_ECHO_URL: &str = "https://api.ipify.org";
+
+/// Result of test_interface
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterfaceTest {
+    pub local_address: String, // The address requests were bound to
+    pub egress_ip: String,     // Public address the echo service saw
+    pub latency_ms: u64,
+}
+
+/// Make one small request bound to `ipv4`, as yt-dlp's --source-address would,
+/// and report the public address it left from. Catches dead VPN adapters before a download
+pub async fn test_interface(ipv4: &str) -> Result<InterfaceTest, String> {
+    let address: std::net::Ipv4Addr = ipv4
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid IPv4 address: {}", ipv4))?;
+    ensure_online("testing a network interface")?;
+
+    let client = client_builder()
+        .local_address(std::net::IpAddr::V4(address))
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+
+    let started = std::time::Instant::now();
+    let unreachable = |e: reqwest::Error| {
+        format!(
+            "Failed to fetch the public address through {}: {}. The interface may be down or have no route to the internet",
+            address, e
+        )
+    };
+    let response = client
+        .get(IP_ECHO_URL)
+        .header("User-Agent", "Zinc-App")
+        .send()
+        .await
+        .map_err(unreachable)?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to fetch the public address: {} returned {}", IP_ECHO_URL, response.status()));
+    }
+    let body = response.text().await.map_err(unreachable)?;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    let egress_ip = body.trim();
+    if egress_ip.parse::<std::net::IpAddr>().is_err() {
+        return Err(format!("Failed to fetch the public address: unexpected reply from {}", IP_ECHO_URL));
+    }
+
+    Ok(InterfaceTest {
+        local_address: address.to_string(),
+        egress_ip: egress_ip.to_string(),
+        latency_ms,
+    })
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetworkInterface {
@@ -75,3 +368,44 @@ pub fn get_network_interfaces() -> Result<Vec<NetworkInterface>, String> {
 pub fn get_network_interfaces() -> Result<Vec<NetworkInterface>, String> {
     Ok(vec![])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn stalled_download_times_out() {
+        READ_TIMEOUT_SECS.store(1, Ordering::SeqCst);
+
+        // A server that sends its headers and a first chunk, then goes quiet
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = socket.read(&mut request).await;
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 1000\r\n\r\nfirst")
+                .await
+                .unwrap();
+            tokio::time::sleep(Duration::from_secs(30)).await;
+        });
+
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let response = send_with_retry(client.get(format!("http://{}/model.bin", address)))
+            .await
+            .unwrap();
+        let mut stream = response.bytes_stream();
+
+        let first = next_chunk(&mut stream).await.unwrap().unwrap().unwrap();
+        assert_eq!(&first[..], b"first");
+
+        let error = next_chunk(&mut stream).await.unwrap_err();
+        assert!(
+            matches!(&error, AppError::Network(m) if m.starts_with("Download timed out")),
+            "{}",
+            error
+        );
+    }
+}
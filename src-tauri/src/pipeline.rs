@@ -0,0 +1,1950 @@
+use crate::config::{prepare_output_dir, AppConfig};
+use crate::transcription_manager::TranscriptionManager;
+use crate::ytdlp::{DownloadOptions, DownloadProgress, YtDlp};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, watch, Mutex};
+use uuid::Uuid;
+
+use crate::transcription::{get_audio_duration, TranscriptionSettings};
+use crate::transcription::karaoke::generate_karaoke_ass;
+
+pub use crate::error::AppError;
+pub use crate::transcription::karaoke::KaraokeStyle;
+pub use crate::transcription::{SubtitleCue, TranscribeProgress};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubtitleSettings {
+    pub enabled: bool,
+    pub engine: String,  // "whisper_rs", "moonshine", "zipformer", "canary"
+    pub model: String,
+    pub style: String,   // "word" or "sentence"
+    #[serde(default)]
+    pub language: Option<String>, // Source language code; None or "auto" auto-detects
+    #[serde(default)]
+    pub output_format: Option<String>, // "srt" (default) or "lrc" lyrics for audio downloads
+}
+
+/// Where a task is in its lifecycle
+/// Serialized as the plain strings the frontend reads: "pending", "downloading", "postprocessing",
+/// "transcribing" or "transcribing:<stage>", "completed", "error" and "cancelled"
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum TaskStatus {
+    Pending,
+    Downloading,
+    Postprocessing,
+    Transcribing { stage: Option<String> }, // Engine stage such as "extracting" or "embedding"
+    Completed,
+    Error,
+    Cancelled,
+}
+
+impl TaskStatus {
+    pub fn transcribing(stage: &str) -> Self {
+        TaskStatus::Transcribing { stage: Some(stage.to_string()) }
+    }
+
+    /// Completed, failed or cancelled; nothing running will change it again
+    pub fn is_finished(&self) -> bool {
+        matches!(self, TaskStatus::Completed | TaskStatus::Error | TaskStatus::Cancelled)
+    }
+
+    pub fn is_transcribing(&self) -> bool {
+        matches!(self, TaskStatus::Transcribing { .. })
+    }
+
+    /// Status without the transcription stage, for grouping
+    pub fn name(&self) -> &'static str {
+        match self {
+            TaskStatus::Pending => "pending",
+            TaskStatus::Downloading => "downloading",
+            TaskStatus::Postprocessing => "postprocessing",
+            TaskStatus::Transcribing { .. } => "transcribing",
+            TaskStatus::Completed => "completed",
+            TaskStatus::Error => "error",
+            TaskStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl std::fmt::Display for TaskStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskStatus::Transcribing { stage: Some(stage) } => write!(f, "transcribing:{}", stage),
+            other => f.write_str(other.name()),
+        }
+    }
+}
+
+impl From<TaskStatus> for String {
+    fn from(status: TaskStatus) -> Self {
+        status.to_string()
+    }
+}
+
+impl TryFrom<String> for TaskStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, String> {
+        if let Some(stage) = value.strip_prefix("transcribing:") {
+            return Ok(TaskStatus::transcribing(stage));
+        }
+        Ok(match value.as_str() {
+            "pending" => TaskStatus::Pending,
+            "downloading" => TaskStatus::Downloading,
+            "postprocessing" => TaskStatus::Postprocessing,
+            "transcribing" => TaskStatus::Transcribing { stage: None },
+            "completed" => TaskStatus::Completed,
+            "error" => TaskStatus::Error,
+            "cancelled" => TaskStatus::Cancelled,
+            _ => return Err(format!("Unknown task status: {}", value)),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Download {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    pub thumbnail: Option<String>,
+    pub status: TaskStatus,
+    pub progress: f64,
+    pub speed: Option<String>,
+    pub eta: Option<String>,
+    pub output_path: Option<String>,
+    pub format: String,
+    pub error: Option<String>,
+    pub duration: Option<f64>,
+    pub whisper_model: Option<String>,
+    pub transcription_engine: Option<String>,
+    pub transcription_style: Option<String>, // "word" or "sentence"
+    pub transcription_language: Option<String>, // None auto-detects
+    #[serde(default)]
+    pub subtitle_format: Option<String>, // "lrc" writes lyrics next to audio; None means SRT
+    pub transcription_progress: Option<f64>,
+    pub transcription_message: Option<String>,
+    pub task_type: String,           // "download" (remote transcriptions too) | "local_transcribe" | "clip"
+    pub source_path: Option<String>, // Input file path for local transcriptions
+    pub in_place: bool,              // Replace the source with the subtitled file, or write <stem>_subtitled.<ext>
+    pub codec: Option<String>,       // "vcodec/acodec" yt-dlp selected, when a codec preference is set
+    #[serde(skip)]
+    pub log_tail: Vec<String>, // Last LOG_TAIL_LINES stderr and post-download hook lines; fetched with get_download_log
+    pub output_dir: Option<String>, // Per-task folder override; local results are copied here
+    #[serde(default)]
+    pub rtf: Option<f64>, // Measured transcription wall time per second of audio; below 1.0 beats realtime
+}
+
+/// Everything needed to queue a download
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadRequest {
+    pub url: String,
+    pub format: String,                // "quality", "quality:container" or a raw yt-dlp format
+    pub title: String,
+    pub thumbnail: Option<String>,
+    pub subtitle_settings: Option<SubtitleSettings>, // None uses the config defaults
+    pub duration: Option<f64>,
+    pub output_container: Option<String>,
+    pub output_dir: Option<PathBuf>, // Overrides config.output_dir for this task
+    #[serde(default)]
+    pub override_duration_guard: bool, // Transcribe even past max_transcription_minutes
+    #[serde(default)]
+    pub force_duplicate: bool, // Queue even when the same URL is already queued
+}
+
+/// Everything needed to add a local file for transcription
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalTranscriptionRequest {
+    pub file_path: String,
+    pub title: String,
+    pub engine: String,
+    pub model: String,
+    pub style: String,
+    pub language: Option<String>,   // None auto-detects
+    pub output_dir: Option<String>, // Copy the result here instead of leaving it next to the source
+    pub output_format: Option<String>, // "srt" (default) or "lrc" for audio files
+    #[serde(default)]
+    pub force_duplicate: bool, // Add even when the same file is already waiting or running
+}
+
+/// A direct http(s) link to an audio or video file, fetched without yt-dlp
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteTranscriptionRequest {
+    pub url: String,
+    pub engine: String,
+    pub model: String,
+    pub style: String,
+    pub language: Option<String>,      // None auto-detects
+    pub output_dir: Option<PathBuf>,   // Overrides config.output_dir for this task
+    pub output_format: Option<String>, // "srt" (default) or "lrc" for audio files
+    #[serde(default)]
+    pub override_duration_guard: bool, // Transcribe even past max_transcription_minutes
+}
+
+/// A named section to cut out of a finished download
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipRequest {
+    pub start: f64,   // Seconds
+    pub end: f64,
+    pub name: String, // Appended to the source file name: <stem>_<name>.<ext>
+}
+
+/// Containers a finished download can be remuxed into
+const REMUX_CONTAINERS: &[&str] = &["mp4", "mkv", "webm", "mov", "m4v"];
+
+/// Receives task updates from the pipeline
+/// The app forwards these as Tauri events; headless callers can log or collect them
+pub trait PipelineObserver: Send + Sync {
+    /// A task's state changed
+    fn task_updated(&self, task: &Download);
+
+    /// A task reached Completed, Error or Cancelled, reported once per task right after
+    /// its task_updated; later edits that restore Completed don't repeat it
+    fn task_finished(&self, _task: &Download) {}
+
+    /// Raw progress from the transcription engine; default ignores it
+    fn transcribe_progress(&self, _progress: &TranscribeProgress) {}
+}
+
+/// Task table and cancel handles shared by the pipeline and its callers
+#[derive(Default)]
+pub struct TaskStore {
+    pub downloads: Mutex<HashMap<String, Download>>,
+    pub cancel_senders: Mutex<HashMap<String, watch::Sender<bool>>>,
+    pub counters: TaskCounters,
+    workers: AtomicUsize, // Task futures still running, including ones already cancelled
+    edits: Mutex<HashSet<String>>, // Completed tasks with an edit (subtitles, remux, burn-in) running
+}
+
+impl TaskStore {
+    /// Append lines to a task's log_tail, keeping the last LOG_TAIL_LINES
+    pub async fn append_log(&self, task_id: &str, lines: impl IntoIterator<Item = String>) {
+        let mut downloads = self.downloads.lock().await;
+        if let Some(download) = downloads.get_mut(task_id) {
+            download.log_tail.extend(lines);
+            if download.log_tail.len() > LOG_TAIL_LINES {
+                let excess = download.log_tail.len() - LOG_TAIL_LINES;
+                download.log_tail.drain(..excess);
+            }
+        }
+    }
+}
+
+/// Counts a task worker as running until it is dropped, so shutdown can wait for it
+struct WorkerGuard(Arc<TaskStore>);
+
+impl WorkerGuard {
+    fn new(tasks: &Arc<TaskStore>) -> Self {
+        tasks.workers.fetch_add(1, Ordering::SeqCst);
+        Self(Arc::clone(tasks))
+    }
+}
+
+impl Drop for WorkerGuard {
+    fn drop(&mut self) {
+        self.0.workers.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Running totals for this session, bumped as tasks finish
+/// Survive clear_download, unlike the task table
+#[derive(Default)]
+pub struct TaskCounters {
+    completed: AtomicU64,
+    failed: AtomicU64,
+    downloaded_bytes: AtomicU64,
+    transcribed_ms: AtomicU64,
+}
+
+/// Point-in-time copy of the task counters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskTotals {
+    pub completed: u64,
+    pub failed: u64,
+    pub downloaded_bytes: u64,
+    pub transcribed_minutes: f64,
+}
+
+impl TaskCounters {
+    fn record_completion(&self, downloaded_bytes: u64, transcribed_secs: Option<f64>) {
+        self.completed.fetch_add(1, Ordering::Relaxed);
+        self.downloaded_bytes.fetch_add(downloaded_bytes, Ordering::Relaxed);
+        if let Some(secs) = transcribed_secs.filter(|s| s.is_finite() && *s > 0.0) {
+            self.transcribed_ms.fetch_add((secs * 1000.0) as u64, Ordering::Relaxed);
+        }
+    }
+
+    fn record_failure(&self) {
+        self.failed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn totals(&self) -> TaskTotals {
+        TaskTotals {
+            completed: self.completed.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            downloaded_bytes: self.downloaded_bytes.load(Ordering::Relaxed),
+            transcribed_minutes: self.transcribed_ms.load(Ordering::Relaxed) as f64 / 60_000.0,
+        }
+    }
+}
+
+/// Clamp a reported percentage to 0-100 and keep it from going backwards
+/// within the same status. A status/stage change may reset it
+fn normalize_progress(current_status: &TaskStatus, current: f64, new_status: &TaskStatus, reported: f64) -> f64 {
+    let reported = if reported.is_finite() {
+        reported.clamp(0.0, 100.0)
+    } else {
+        0.0
+    };
+
+    if current_status == new_status {
+        reported.max(current)
+    } else {
+        reported
+    }
+}
+
+/// Number of yt-dlp/ffmpeg stderr lines kept per task
+const LOG_TAIL_LINES: usize = 200;
+
+/// Build yt-dlp options from a format string ("quality" or "quality:container")
+/// Returns the options and whether the download is audio-only
+pub fn resolve_download_options(
+    config: &AppConfig,
+    format: &str,
+    output_container: Option<String>,
+    generate_subtitles: bool,
+    whisper_model: Option<String>,
+) -> Result<(DownloadOptions, bool), String> {
+    let (quality, container) = match format.split_once(':') {
+        Some((quality, container)) => (quality.to_string(), Some(container.to_string())),
+        None => (format.to_string(), None),
+    };
+
+    let is_audio_only = YtDlp::is_audio_preset(&quality);
+    let requested_container = output_container.or(container);
+
+    // On audio-only downloads the suffix names the audio preset ("audio:m4a" is "m4a")
+    let (quality, requested_container) = match requested_container {
+        Some(c) if is_audio_only && c != "original" && YtDlp::is_audio_preset(&c) => (c, None),
+        other => (quality, other),
+    };
+
+    // Determine container: explicit override, then format suffix, then the default format
+    let output_container = match requested_container {
+        Some(c) if c == "original" => None, // Original format doesn't need remux
+        Some(c) => Some(c),
+        None if is_audio_only => None, // Audio-only doesn't need container
+        None => match config.default_format.as_str() {
+            "original" => None,
+            other if YtDlp::is_audio_preset(other) => None, // Not merge containers
+            other => Some(other.to_string()),
+        },
+    };
+
+    if let Some(ref c) = output_container {
+        YtDlp::validate_output_container(c, is_audio_only)?;
+    }
+
+    if quality.trim().is_empty() {
+        return Err("No format selected".to_string());
+    }
+
+    // Named presets expand to a selector; anything else is a raw yt-dlp
+    // format (e.g. "137+140" from the format picker) and is passed through unchanged
+    let presets = YtDlp::get_format_presets(
+        config.preferred_vcodec.as_deref(),
+        config.preferred_acodec.as_deref(),
+        config.max_filesize.as_deref(),
+    );
+    let format_string = presets
+        .get(&quality)
+        .cloned()
+        .unwrap_or_else(|| quality.clone());
+    let (audio_format, audio_quality) = YtDlp::audio_extraction(&quality).unzip();
+
+    let options = DownloadOptions {
+        format: format_string,
+        output_dir: config.output_dir.clone(),
+        filename_template: None,
+        output_container,
+        generate_subtitles,
+        whisper_model,
+        source_address: config.network_interface.clone(),
+        cookies_browser: config.cookies_browser.clone(),
+        restrict_filenames: config.restrict_filenames,
+        embed_native_subs: config.embed_native_subs && !is_audio_only, // Nothing to embed into
+        preferred_vcodec: config.preferred_vcodec.clone(),
+        preferred_acodec: config.preferred_acodec.clone(),
+        format_sort: config.format_sort.clone(),
+        max_filesize: config.max_filesize.clone(),
+        audio_format,
+        audio_quality,
+        credentials: config.credentials(),
+    };
+
+    Ok((options, is_audio_only))
+}
+
+/// Copy a finished file into a folder, keeping its name
+async fn copy_into(file: &Path, dir: &Path) -> Result<PathBuf, String> {
+    let name = file
+        .file_name()
+        .ok_or_else(|| format!("Invalid output path: {}", file.display()))?;
+    let target = dir.join(name);
+    if target == file {
+        return Ok(target);
+    }
+    tokio::fs::copy(file, &target)
+        .await
+        .map_err(|e| format!("Failed to copy result to {}: {}", dir.display(), e))?;
+    Ok(target)
+}
+
+/// Whether a task is still running or queued to run
+/// Pending local tasks are waiting on the user, not the queue, so they are left alone
+fn is_active(download: &Download) -> bool {
+    // Pending local transcriptions wait for the user to start them; queued downloads and clips don't
+    match download.status {
+        TaskStatus::Pending => download.task_type != "local_transcribe",
+        TaskStatus::Downloading | TaskStatus::Postprocessing | TaskStatus::Transcribing { .. } => true,
+        TaskStatus::Completed | TaskStatus::Error | TaskStatus::Cancelled => false,
+    }
+}
+
+/// An unfinished task of the same kind working on the same URL or file
+fn find_queued<'a>(downloads: &'a HashMap<String, Download>, candidate: &Download) -> Option<&'a Download> {
+    downloads.values().find(|task| {
+        let same_source = if candidate.url.trim().is_empty() {
+            task.source_path.is_some() && task.source_path == candidate.source_path
+        } else {
+            task.url.trim() == candidate.url.trim()
+        };
+        same_source && task.task_type == candidate.task_type && !task.status.is_finished()
+    })
+}
+
+/// Insert a new task, refusing one that duplicates an unfinished task unless forced
+/// Checked under the same lock as the insert, so two quick pastes can't both get through
+fn insert_task(
+    downloads: &mut HashMap<String, Download>,
+    download: Download,
+    force_duplicate: bool,
+) -> Result<(), AppError> {
+    if !force_duplicate {
+        if let Some(existing) = find_queued(downloads, &download) {
+            return Err(AppError::AlreadyQueued(format!("Already queued: {} (task {})", existing.title, existing.id)));
+        }
+    }
+    downloads.insert(download.id.clone(), download);
+    Ok(())
+}
+
+/// Download and transcription orchestration, independent of Tauri
+/// Cheap to clone; clones share the same task store and observer
+#[derive(Clone)]
+pub struct Pipeline {
+    tasks: Arc<TaskStore>,
+    observer: Arc<dyn PipelineObserver>,
+}
+
+impl Pipeline {
+    pub fn new(tasks: Arc<TaskStore>, observer: Arc<dyn PipelineObserver>) -> Self {
+        Self { tasks, observer }
+    }
+
+    /// Pipeline with its own empty task store; network settings stay at their defaults
+    pub fn standalone(observer: Arc<dyn PipelineObserver>) -> Self {
+        Self::new(Arc::new(TaskStore::default()), observer)
+    }
+
+    /// Standalone pipeline for a headless caller, applying the config's proxy, offline
+    /// and timeout settings process-wide as the app does at startup
+    pub fn from_config(config: &AppConfig, observer: Arc<dyn PipelineObserver>) -> Self {
+        crate::network::apply_config(config);
+        Self::standalone(observer)
+    }
+
+    /// Snapshot of a single task
+    pub async fn get_task(&self, task_id: &str) -> Option<Download> {
+        self.tasks.downloads.lock().await.get(task_id).cloned()
+    }
+
+    /// Apply an update to a task unless it was cancelled, then notify the observer
+    /// Returns whether the update was applied
+    async fn update_task(&self, task_id: &str, update: impl FnOnce(&mut Download)) -> bool {
+        let mut downloads = self.tasks.downloads.lock().await;
+        if let Some(download) = downloads.get_mut(task_id).filter(|d| d.status != TaskStatus::Cancelled) {
+            update(download);
+            self.observer.task_updated(download);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Mark a task completed and count it in the session totals; a cancelled task keeps its status
+    async fn complete_task(
+        &self,
+        task_id: &str,
+        downloaded_bytes: u64,
+        transcribed_secs: Option<f64>,
+        update: impl FnOnce(&mut Download),
+    ) {
+        let mut downloads = self.tasks.downloads.lock().await;
+        let Some(download) = downloads.get_mut(task_id).filter(|d| d.status != TaskStatus::Cancelled) else {
+            return;
+        };
+        let first = !download.status.is_finished();
+        download.status = TaskStatus::Completed;
+        download.progress = 100.0;
+        update(download);
+        self.observer.task_updated(download);
+
+        if first {
+            self.tasks.counters.record_completion(downloaded_bytes, transcribed_secs);
+            self.observer.task_finished(download);
+        }
+    }
+
+    /// Mark a task failed; a cancelled task keeps its status
+    async fn fail_task(&self, task_id: &str, error: String) {
+        let mut downloads = self.tasks.downloads.lock().await;
+        if let Some(download) = downloads.get_mut(task_id) {
+            if download.status == TaskStatus::Cancelled {
+                return;
+            }
+            let first = !download.status.is_finished();
+            download.status = TaskStatus::Error;
+            download.error = Some(error);
+            self.observer.task_updated(download);
+            if first {
+                self.tasks.counters.record_failure();
+                self.observer.task_finished(download);
+            }
+        }
+    }
+
+    /// Spawn a task that appends log lines to a download's log_tail
+    fn spawn_log_collector(&self, task_id: String) -> mpsc::Sender<String> {
+        let (log_tx, mut log_rx) = mpsc::channel::<String>(256);
+        let tasks = Arc::clone(&self.tasks);
+        tokio::spawn(async move {
+            while let Some(line) = log_rx.recv().await {
+                tasks.append_log(&task_id, [line]).await;
+            }
+        });
+        log_tx
+    }
+
+    /// Forward transcription progress into a task's stage status
+    fn spawn_transcribe_forwarder(&self, task_id: String) -> mpsc::Sender<TranscribeProgress> {
+        let (transcribe_tx, mut transcribe_rx) = mpsc::channel::<TranscribeProgress>(100);
+        let pipeline = self.clone();
+
+        tokio::spawn(async move {
+            while let Some(progress) = transcribe_rx.recv().await {
+                // Skip the "complete" stage - we handle completion in the main flow
+                if progress.stage == "complete" {
+                    continue;
+                }
+                let mut downloads = pipeline.tasks.downloads.lock().await;
+                if let Some(download) = downloads.get_mut(&task_id) {
+                    // Don't overwrite if already completed or cancelled
+                    if matches!(download.status, TaskStatus::Completed | TaskStatus::Cancelled) {
+                        continue;
+                    }
+                    let stage_status = TaskStatus::transcribing(&progress.stage);
+                    download.transcription_progress = Some(normalize_progress(
+                        &download.status,
+                        download.transcription_progress.unwrap_or(0.0),
+                        &stage_status,
+                        progress.progress,
+                    ));
+                    download.status = stage_status;
+                    download.transcription_message = Some(progress.message.clone());
+                    pipeline.observer.transcribe_progress(&progress);
+                    pipeline.observer.task_updated(download);
+                }
+            }
+        });
+
+        transcribe_tx
+    }
+
+    /// Queue a download (and optional transcription) and return its task id
+    /// The work runs in the background; progress arrives through the observer
+    pub async fn start_download(
+        &self,
+        config: &AppConfig,
+        request: DownloadRequest,
+    ) -> Result<String, AppError> {
+        YtDlp::validate_url(&request.url)?;
+        let download_id = Uuid::new_v4().to_string();
+
+        // Use per-video subtitle settings if provided, otherwise fall back to global config
+        let (generate_subtitles, transcription_engine, transcription_model, transcription_style, transcription_language, subtitle_format) = match &request.subtitle_settings {
+            Some(settings) => (
+                settings.enabled,
+                settings.engine.clone(),
+                settings.model.clone(),
+                settings.style.clone(),
+                settings.language.clone(),
+                settings.output_format.clone().filter(|f| f != "srt"),
+            ),
+            None => (
+                config.generate_subtitles,
+                config.transcription_engine.clone(),
+                // A hand-edited config can name a model the engine doesn't have
+                TranscriptionManager::new()
+                    .resolve_model(
+                        &config.transcription_engine,
+                        &[
+                            Some(&config.transcription_model),
+                            config.engine_models.get(&config.transcription_engine).map(String::as_str),
+                        ],
+                    )
+                    .await
+                    .unwrap_or_else(|| config.transcription_model.clone()),
+                "sentence".to_string(), // Default style when using global config
+                None,
+                None,
+            ),
+        };
+
+        // Fail before downloading rather than after, when the engine can't take the language
+        let transcription_language = if generate_subtitles {
+            TranscriptionManager::new()
+                .validate_language(&transcription_engine, transcription_language.as_deref())?
+        } else {
+            None
+        };
+
+        // Reject impossible format/container combinations before the task is created
+        let (mut options, is_audio_only) = resolve_download_options(
+            config,
+            &request.format,
+            request.output_container,
+            generate_subtitles,
+            Some(transcription_model.clone()),
+        )?;
+
+        if let Some(dir) = request.output_dir {
+            options.output_dir = dir;
+        }
+        // The configured folder was checked at load, but its drive may have been unplugged since
+        prepare_output_dir(&options.output_dir)?;
+
+        let settings = TranscriptionSettings::from(config);
+        if generate_subtitles {
+            TranscriptionManager::validate_subtitle_format(subtitle_format.as_deref(), is_audio_only)?;
+            // Ask before downloading when the length is already known
+            if !request.override_duration_guard {
+                TranscriptionManager::check_duration_guard(request.duration, settings.max_transcription_minutes)?;
+            }
+        }
+        let override_duration_guard = request.override_duration_guard;
+
+        let download = Download {
+            id: download_id.clone(),
+            url: request.url.clone(),
+            title: request.title,
+            thumbnail: request.thumbnail,
+            status: TaskStatus::Pending,
+            progress: 0.0,
+            speed: None,
+            eta: None,
+            output_path: None,
+            format: request.format,
+            error: None,
+            duration: request.duration,
+            whisper_model: if generate_subtitles { Some(transcription_model.clone()) } else { None },
+            transcription_engine: if generate_subtitles { Some(transcription_engine.clone()) } else { None },
+            transcription_style: if generate_subtitles { Some(transcription_style.clone()) } else { None },
+            transcription_language: transcription_language.clone(),
+            subtitle_format: subtitle_format.clone(),
+            transcription_progress: None,
+            transcription_message: None,
+            task_type: "download".to_string(),
+            source_path: None,
+            in_place: true,
+            codec: None,
+            log_tail: Vec::new(),
+            output_dir: Some(options.output_dir.to_string_lossy().to_string()),
+            rtf: None,
+        };
+
+        insert_task(&mut *self.tasks.downloads.lock().await, download, request.force_duplicate)?;
+
+        let (progress_tx, mut progress_rx) = mpsc::channel::<DownloadProgress>(100);
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+
+        // Store the cancel sender so we can signal cancellation later
+        self.tasks.cancel_senders.lock().await.insert(download_id.clone(), cancel_tx);
+
+        let pipeline = self.clone();
+        tokio::spawn(async move {
+            while let Some(progress) = progress_rx.recv().await {
+                pipeline.update_task(&progress.download_id, |download| {
+                    // yt-dlp has exited once the task moves on; a late progress line mustn't undo that
+                    if matches!(download.status, TaskStatus::Completed | TaskStatus::Error)
+                        || download.status.is_transcribing()
+                    {
+                        return;
+                    }
+                    // Merged formats download their streams one after another, each to
+                    // its own destination; a new file starts again from 0
+                    let new_file = progress.filename.is_some() && progress.filename != download.output_path;
+                    download.progress = normalize_progress(
+                        &download.status,
+                        if new_file { 0.0 } else { download.progress },
+                        &progress.status,
+                        progress.progress,
+                    );
+                    download.speed = progress.speed.clone();
+                    download.eta = progress.eta.clone();
+                    download.status = progress.status.clone();
+                    if let Some(filename) = &progress.filename {
+                        download.output_path = Some(filename.clone());
+                    }
+                    if progress.codec.is_some() {
+                        download.codec = progress.codec.clone();
+                    }
+                }).await;
+            }
+        });
+
+        let pipeline = self.clone();
+        let url = request.url;
+        let duration = request.duration;
+        let subtitle_failure_policy = config.subtitle_failure_policy.clone();
+        let task_id = download_id.clone();
+
+        tokio::spawn(async move {
+            let _worker = WorkerGuard::new(&pipeline.tasks);
+            pipeline.update_task(&task_id, |download| {
+                download.status = TaskStatus::Downloading;
+            }).await;
+
+            let cancel_rx_for_transcription = cancel_rx.clone();
+            let log_tx = pipeline.spawn_log_collector(task_id.clone());
+            match YtDlp::start_download(
+                &url,
+                options.clone(),
+                progress_tx,
+                task_id.clone(),
+                cancel_rx,
+                log_tx.clone(),
+            )
+            .await
+            {
+                Ok(path) => {
+                    let path_str = path.to_string_lossy().to_string();
+                    let downloaded_bytes = tokio::fs::metadata(&path).await.map(|m| m.len()).unwrap_or(0);
+
+                    // The caller may not have known the length; the finished file does
+                    let probed = match duration {
+                        Some(_) => None,
+                        None => get_audio_duration(&path).await,
+                    };
+                    if probed.is_some() {
+                        pipeline.update_task(&task_id, |download| download.duration = probed).await;
+                    }
+                    let duration = duration.or(probed);
+
+                    // Check if subtitle generation is enabled
+                    log::info!("Download completed. generate_subtitles={}, is_audio_only={}", options.generate_subtitles, is_audio_only);
+                    // Audio-only downloads are only transcribed for LRC lyrics
+                    let wants_lrc = subtitle_format.as_deref() == Some("lrc");
+                    let wants_subtitles = options.generate_subtitles && (!is_audio_only || wants_lrc);
+
+                    // Video-only downloads have nothing to transcribe; skip rather than fail
+                    let has_audio = !wants_subtitles
+                        || TranscriptionManager::has_audio_stream(&path).await != Some(false);
+
+                    if wants_subtitles && has_audio {
+                        let transcribing_path = path_str.clone();
+                        pipeline.update_task(&task_id, |download| {
+                            download.status = TaskStatus::Transcribing { stage: None };
+                            download.output_path = Some(transcribing_path);
+                        }).await;
+
+                        let video_path = PathBuf::from(&path_str);
+                        let transcription_manager = TranscriptionManager::new()
+                            .with_settings(settings)
+                            .with_log(log_tx)
+                            .with_duration_guard_override(override_duration_guard);
+
+                        log::info!("Starting transcription for: {:?} with engine: {}, model: {}, style: {}",
+                            video_path, transcription_engine, transcription_model, transcription_style);
+
+                        let attempts = if subtitle_failure_policy == "retry_once" { 2 } else { 1 };
+                        let mut result = Err(String::new());
+                        for attempt in 1..=attempts {
+                            if let (true, Err(e)) = (attempt > 1, &result) {
+                                if *cancel_rx_for_transcription.borrow() {
+                                    break;
+                                }
+                                log::warn!("Retrying transcription after failure: {}", e);
+                            }
+                            result = transcription_manager.process_video(
+                                &video_path,
+                                &transcription_engine,
+                                &transcription_model,
+                                transcription_language.as_deref(),
+                                &transcription_style,
+                                true, // Downloaded files are ours to replace
+                                pipeline.spawn_transcribe_forwarder(task_id.clone()),
+                                cancel_rx_for_transcription.clone(),
+                            ).await;
+                            if result.is_ok() {
+                                break;
+                            }
+                        }
+                        if wants_lrc {
+                            if let Ok(srt) = result {
+                                result = TranscriptionManager::convert_to_lrc(&srt).await;
+                            }
+                        }
+
+                        match result {
+                            Ok(result) => {
+                                log::info!("Transcription successful: {:?}", result);
+                                let transcribed_secs = duration;
+                                let rtf = transcription_manager.last_rtf();
+                                pipeline.complete_task(&task_id, downloaded_bytes, transcribed_secs, |download| {
+                                    download.rtf = rtf;
+                                }).await;
+                            }
+                            Err(e) if subtitle_failure_policy == "fail_task" => {
+                                log::error!("Transcription failed: {}", e);
+                                pipeline.fail_task(&task_id, format!("Subtitle generation failed: {}", e)).await;
+                            }
+                            Err(e) => {
+                                // Transcription failed, but download succeeded
+                                // Mark as completed with a warning
+                                log::error!("Transcription failed: {}", e);
+                                pipeline.complete_task(&task_id, downloaded_bytes, None, |download| {
+                                    download.error = Some(format!("Subtitle generation failed: {}", e));
+                                }).await;
+                            }
+                        }
+                    } else {
+                        pipeline.complete_task(&task_id, downloaded_bytes, None, |download| {
+                            download.output_path = Some(path_str);
+                            if !has_audio {
+                                download.transcription_message =
+                                    Some("No audio track, subtitles skipped".to_string());
+                            }
+                        }).await;
+                    }
+                }
+                Err(e) => pipeline.fail_task(&task_id, e).await,
+            }
+
+            // Clean up cancel sender
+            pipeline.tasks.cancel_senders.lock().await.remove(&task_id);
+        });
+
+        Ok(download_id)
+    }
+
+    /// Add a local file for transcription (creates a pending task)
+    pub async fn add_local_transcription(&self, request: LocalTranscriptionRequest) -> Result<String, AppError> {
+        let LocalTranscriptionRequest { file_path, title, engine, model, style, language, output_dir, output_format, force_duplicate } = request;
+        let language = TranscriptionManager::new().validate_language(&engine, language.as_deref())?;
+
+        let path = PathBuf::from(&file_path);
+        if !path.exists() {
+            return Err(format!("File not found: {}", file_path).into());
+        }
+
+        // Reject files ffmpeg can't pull audio from before a task is created
+        TranscriptionManager::validate_input(&path).await?;
+        let output_format = output_format.filter(|f| f != "srt");
+        TranscriptionManager::validate_subtitle_format(output_format.as_deref(), TranscriptionManager::is_audio_file(&path))?;
+
+        if let Some(dir) = &output_dir {
+            prepare_output_dir(Path::new(dir))?;
+        }
+
+        let task_id = Uuid::new_v4().to_string();
+
+        // Audio files have no frame to show, so only the duration is probed for them
+        let duration = get_audio_duration(&path).await;
+        let thumbnail = if TranscriptionManager::is_audio_file(&path) {
+            None
+        } else {
+            crate::media::extract_poster_frame(&path, duration).await
+        };
+
+        let download = Download {
+            id: task_id.clone(),
+            url: String::new(),
+            title,
+            thumbnail,
+            status: TaskStatus::Pending,
+            progress: 0.0,
+            speed: None,
+            eta: None,
+            output_path: Some(file_path.clone()),
+            format: String::new(),
+            error: None,
+            duration,
+            whisper_model: Some(model),
+            transcription_engine: Some(engine),
+            transcription_style: Some(style),
+            transcription_language: language,
+            subtitle_format: output_format,
+            transcription_progress: None,
+            transcription_message: None,
+            task_type: "local_transcribe".to_string(),
+            source_path: Some(file_path),
+            in_place: false,
+            codec: None,
+            log_tail: Vec::new(),
+            output_dir,
+            rtf: None,
+        };
+
+        insert_task(&mut *self.tasks.downloads.lock().await, download.clone(), force_duplicate)?;
+        self.observer.task_updated(&download);
+
+        Ok(task_id)
+    }
+
+    /// Start transcription for a pending local transcription task with `config`'s settings
+    /// `override_duration_guard` is set once the user confirms a file over max_transcription_minutes
+    pub async fn start_local_transcription(
+        &self,
+        config: &AppConfig,
+        task_id: &str,
+        override_duration_guard: bool,
+    ) -> Result<(), AppError> {
+        // Get task info
+        let (file_path, engine_id, model_id, style, language, in_place, output_dir, subtitle_format) = {
+            let downloads = self.tasks.downloads.lock().await;
+            let task = downloads.get(task_id)
+                .ok_or_else(|| format!("Task not found: {}", task_id))?;
+
+            if task.task_type != "local_transcribe" {
+                return Err("Not a local transcription task".into());
+            }
+            if task.status != TaskStatus::Pending {
+                return Err(format!("Task is not pending, status: {}", task.status).into());
+            }
+
+            let source = task.source_path.clone()
+                .ok_or_else(|| "No source path".to_string())?;
+            let engine = task.transcription_engine.clone()
+                .ok_or_else(|| "No engine specified".to_string())?;
+            let model = task.whisper_model.clone()
+                .ok_or_else(|| "No model specified".to_string())?;
+
+            let style = task.transcription_style.clone()
+                .unwrap_or_else(|| "sentence".to_string());
+
+            (
+                source,
+                engine,
+                model,
+                style,
+                task.transcription_language.clone(),
+                task.in_place,
+                task.output_dir.clone(),
+                task.subtitle_format.clone(),
+            )
+        };
+
+        let video_path = PathBuf::from(&file_path);
+
+        // Checked here too, so the error reaches the caller instead of failing the task
+        let settings = TranscriptionSettings::from(config);
+        if !override_duration_guard {
+            TranscriptionManager::check_duration_guard(
+                get_audio_duration(&video_path).await,
+                settings.max_transcription_minutes,
+            )?;
+        }
+
+        // Claim the task under one lock; a second start or a cancel may have come in during the probe
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+        {
+            let mut downloads = self.tasks.downloads.lock().await;
+            let download = downloads
+                .get_mut(task_id)
+                .ok_or_else(|| format!("Task not found: {}", task_id))?;
+            if download.status != TaskStatus::Pending {
+                return Err(format!("Task is not pending, status: {}", download.status).into());
+            }
+            download.status = TaskStatus::transcribing("extracting");
+            self.observer.task_updated(download);
+            self.tasks.cancel_senders.lock().await.insert(task_id.to_string(), cancel_tx);
+        }
+
+        let pipeline = self.clone();
+        let task_id = task_id.to_string();
+
+        tokio::spawn(async move {
+            let _worker = WorkerGuard::new(&pipeline.tasks);
+            let transcribe_tx = pipeline.spawn_transcribe_forwarder(task_id.clone());
+            let log_tx = pipeline.spawn_log_collector(task_id.clone());
+            let transcription_manager = TranscriptionManager::new()
+                .with_settings(settings)
+                .with_log(log_tx)
+                .with_duration_guard_override(override_duration_guard);
+
+            log::info!(
+                "Starting local file transcription for: {:?} with engine: {}, model: {}",
+                video_path,
+                engine_id,
+                model_id
+            );
+
+            match transcription_manager
+                .process_video(
+                    &video_path,
+                    &engine_id,
+                    &model_id,
+                    language.as_deref(),
+                    &style,
+                    in_place,
+                    transcribe_tx,
+                    cancel_rx,
+                )
+                .await
+            {
+                Ok(result) => {
+                    log::info!("Local file transcription successful: {:?}", result);
+
+                    let result = if subtitle_format.as_deref() == Some("lrc") {
+                        match TranscriptionManager::convert_to_lrc(&result).await {
+                            Ok(lrc) => lrc,
+                            Err(e) => {
+                                pipeline.fail_task(&task_id, e).await;
+                                pipeline.tasks.cancel_senders.lock().await.remove(&task_id);
+                                return;
+                            }
+                        }
+                    } else {
+                        result
+                    };
+
+                    // The result is written next to the source; copy it to the chosen folder
+                    let result = match output_dir {
+                        Some(dir) => match copy_into(&result, Path::new(&dir)).await {
+                            Ok(copied) => copied,
+                            Err(e) => {
+                                pipeline.fail_task(&task_id, e).await;
+                                pipeline.tasks.cancel_senders.lock().await.remove(&task_id);
+                                return;
+                            }
+                        },
+                        None => result,
+                    };
+
+                    let transcribed_secs = get_audio_duration(&video_path).await;
+                    let rtf = transcription_manager.last_rtf();
+                    pipeline.complete_task(&task_id, 0, transcribed_secs, |download| {
+                        download.output_path = Some(result.to_string_lossy().to_string());
+                        download.rtf = rtf;
+                    }).await;
+                }
+                Err(e) => {
+                    log::error!("Local file transcription failed: {}", e);
+                    pipeline.fail_task(&task_id, e).await;
+                }
+            }
+
+            // Clean up cancel sender
+            pipeline.tasks.cancel_senders.lock().await.remove(&task_id);
+        });
+
+        Ok(())
+    }
+
+    /// Download a direct media link and transcribe it like a finished download
+    /// Video-site URLs are rejected here; they need start_download and yt-dlp
+    pub async fn start_remote_transcription(
+        &self,
+        config: &AppConfig,
+        request: RemoteTranscriptionRequest,
+    ) -> Result<String, AppError> {
+        let RemoteTranscriptionRequest {
+            url, engine, model, style, language, output_dir, output_format, override_duration_guard,
+        } = request;
+        crate::network::ensure_online("downloading media")?;
+
+        let language = TranscriptionManager::new().validate_language(&engine, language.as_deref())?;
+        let file_name = probe_media_url(&url).await?;
+        let settings = TranscriptionSettings::from(config);
+
+        let output_format = output_format.filter(|f| f != "srt");
+        TranscriptionManager::validate_subtitle_format(
+            output_format.as_deref(),
+            TranscriptionManager::is_audio_file(Path::new(&file_name)),
+        )?;
+
+        let dir = output_dir.unwrap_or_else(|| config.output_dir.clone());
+        prepare_output_dir(&dir)?;
+        let path = unique_path(&dir, &file_name);
+
+        let task_id = Uuid::new_v4().to_string();
+        let download = Download {
+            id: task_id.clone(),
+            url: url.clone(),
+            title: file_name,
+            thumbnail: None,
+            status: TaskStatus::Pending,
+            progress: 0.0,
+            speed: None,
+            eta: None,
+            output_path: Some(path.to_string_lossy().to_string()),
+            format: "direct".to_string(),
+            error: None,
+            duration: None,
+            whisper_model: Some(model.clone()),
+            transcription_engine: Some(engine.clone()),
+            transcription_style: Some(style.clone()),
+            transcription_language: language.clone(),
+            subtitle_format: output_format.clone(),
+            transcription_progress: None,
+            transcription_message: None,
+            task_type: "download".to_string(),
+            source_path: None,
+            in_place: true,
+            codec: None,
+            log_tail: Vec::new(),
+            output_dir: Some(dir.to_string_lossy().to_string()),
+            rtf: None,
+        };
+
+        self.tasks.downloads.lock().await.insert(task_id.clone(), download.clone());
+        self.observer.task_updated(&download);
+
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+        self.tasks.cancel_senders.lock().await.insert(task_id.clone(), cancel_tx);
+
+        let pipeline = self.clone();
+        let id = task_id.clone();
+
+        tokio::spawn(async move {
+            let task_id = id;
+            let _worker = WorkerGuard::new(&pipeline.tasks);
+            pipeline.update_task(&task_id, |download| {
+                download.status = TaskStatus::Downloading;
+            }).await;
+
+            let downloaded_bytes = match pipeline.fetch_media(&task_id, &url, &path, &cancel_rx).await {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    pipeline.fail_task(&task_id, e).await;
+                    pipeline.tasks.cancel_senders.lock().await.remove(&task_id);
+                    return;
+                }
+            };
+
+            let duration = get_audio_duration(&path).await;
+            if duration.is_some() {
+                pipeline.update_task(&task_id, |download| download.duration = duration).await;
+            }
+
+            // The length is only known now, so the guard can't be checked before the task starts
+            let guard = if override_duration_guard {
+                Ok(())
+            } else {
+                TranscriptionManager::check_duration_guard(duration, settings.max_transcription_minutes)
+            };
+
+            let log_tx = pipeline.spawn_log_collector(task_id.clone());
+            let transcription_manager = TranscriptionManager::new()
+                .with_settings(settings)
+                .with_log(log_tx)
+                .with_duration_guard_override(override_duration_guard);
+
+            let result = match guard {
+                Ok(()) => {
+                    pipeline.update_task(&task_id, |download| {
+                        download.status = TaskStatus::Transcribing { stage: None };
+                    }).await;
+                    log::info!("Starting transcription for: {:?} with engine: {}, model: {}, style: {}",
+                        path, engine, model, style);
+
+                    let mut result = transcription_manager.process_video(
+                        &path,
+                        &engine,
+                        &model,
+                        language.as_deref(),
+                        &style,
+                        true, // The downloaded file is ours to replace
+                        pipeline.spawn_transcribe_forwarder(task_id.clone()),
+                        cancel_rx,
+                    ).await;
+                    if output_format.as_deref() == Some("lrc") {
+                        if let Ok(srt) = result {
+                            result = TranscriptionManager::convert_to_lrc(&srt).await;
+                        }
+                    }
+                    result
+                }
+                Err(e) => Err(e.into()),
+            };
+
+            match result {
+                Ok(result) => {
+                    log::info!("Direct media transcription successful: {:?}", result);
+                    let rtf = transcription_manager.last_rtf();
+                    pipeline.complete_task(&task_id, downloaded_bytes, duration, |download| {
+                        download.rtf = rtf;
+                    }).await;
+                }
+                Err(e) => {
+                    log::error!("Direct media transcription failed: {}", e);
+                    pipeline.fail_task(&task_id, e).await;
+                }
+            }
+
+            pipeline.tasks.cancel_senders.lock().await.remove(&task_id);
+        });
+
+        Ok(task_id)
+    }
+
+    /// Stream `url` to `path` via a .part file, reporting progress on the task
+    /// Returns the number of bytes written
+    async fn fetch_media(
+        &self,
+        task_id: &str,
+        url: &str,
+        path: &Path,
+        cancel_rx: &watch::Receiver<bool>,
+    ) -> Result<u64, String> {
+        use tokio::io::AsyncWriteExt;
+
+        let client = crate::network::http_client();
+        let response = crate::network::send_with_retry(client.get(url).header("User-Agent", "Zinc-App"))
+            .await
+            .map_err(|e| format!("Failed to download media: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Download failed with status: {}", response.status()));
+        }
+
+        let total_size = response.content_length().filter(|&len| len > 0);
+        let part_path = PathBuf::from(format!("{}.part", path.to_string_lossy()));
+        let mut file = tokio::fs::File::create(&part_path)
+            .await
+            .map_err(|e| format!("Failed to create file: {}", e))?;
+
+        let started = Instant::now();
+        let mut downloaded: u64 = 0;
+        let mut last_percent = -1.0;
+        let mut stream = response.bytes_stream();
+
+        let result = async {
+            while let Some(chunk) = crate::network::next_chunk(&mut stream).await? {
+                if *cancel_rx.borrow() {
+                    return Err("Cancelled".to_string());
+                }
+
+                let chunk = chunk.map_err(|e| format!("Download error: {}", e))?;
+                file.write_all(&chunk)
+                    .await
+                    .map_err(|e| format!("Write error: {}", e))?;
+                downloaded += chunk.len() as u64;
+
+                // One update per whole percent keeps the event rate down on fast links
+                let percent = total_size
+                    .map(|t| (downloaded as f64 / t as f64 * 100.0).min(100.0).floor())
+                    .unwrap_or(0.0);
+                if percent > last_percent {
+                    last_percent = percent;
+                    let elapsed = started.elapsed().as_secs_f64().max(0.001);
+                    let rate = downloaded as f64 / elapsed;
+                    let eta = total_size
+                        .filter(|_| rate > 0.0)
+                        .map(|t| format!("{}s", (t.saturating_sub(downloaded) as f64 / rate).round() as u64));
+                    let speed = format!("{:.1}MiB/s", rate / (1024.0 * 1024.0));
+                    self.update_task(task_id, |download| {
+                        download.progress = percent;
+                        download.speed = Some(speed);
+                        download.eta = eta;
+                    }).await;
+                }
+            }
+            file.flush()
+                .await
+                .map_err(|e| format!("Failed to flush file: {}", e))
+        }
+        .await;
+        drop(file);
+
+        if let Err(e) = result {
+            let _ = tokio::fs::remove_file(&part_path).await;
+            return Err(e);
+        }
+
+        tokio::fs::rename(&part_path, path)
+            .await
+            .map_err(|e| format!("Failed to save downloaded file: {}", e))?;
+        Ok(downloaded)
+    }
+
+    /// Cancel a single task
+    pub async fn cancel(&self, task_id: &str) {
+        // Send cancellation signal to the task
+        if let Some(cancel_tx) = self.tasks.cancel_senders.lock().await.remove(task_id) {
+            let _ = cancel_tx.send(true);
+        }
+        // A cancelled edit puts its task back to Completed itself
+        if self.tasks.edits.lock().await.contains(task_id) {
+            return;
+        }
+
+        // Update the download status
+        let mut downloads = self.tasks.downloads.lock().await;
+        if let Some(download) = downloads.get_mut(task_id) {
+            let first = !download.status.is_finished();
+            download.status = TaskStatus::Cancelled;
+            self.observer.task_updated(download);
+            if first {
+                self.observer.task_finished(download);
+            }
+        }
+    }
+
+    /// Stop every running and pending task. Returns how many were stopped
+    pub async fn cancel_all(&self) -> usize {
+        // Signal every task and drop the senders in one go
+        let senders: Vec<_> = self.tasks.cancel_senders.lock().await.drain().collect();
+        for (_, cancel_tx) in &senders {
+            let _ = cancel_tx.send(true);
+        }
+
+        // Late forwarder updates skip cancelled tasks, so this status sticks
+        let edits = self.tasks.edits.lock().await.clone();
+        let mut downloads = self.tasks.downloads.lock().await;
+        let mut stopped = 0;
+        for download in downloads.values_mut() {
+            if is_active(download) && !edits.contains(&download.id) {
+                download.status = TaskStatus::Cancelled;
+                self.observer.task_updated(download);
+                self.observer.task_finished(download);
+                stopped += 1;
+            }
+        }
+
+        log::info!("cancel_all stopped {} task(s)", stopped);
+        stopped
+    }
+
+    /// Run an edit of a completed task as a cancellable job that shutdown waits for
+    /// `start` sets the task's status while it runs; it is completed again however the
+    /// edit ends, and `finish` updates it from the edit's result
+    async fn run_edit<T, Fut>(
+        &self,
+        task_id: &str,
+        start: impl FnOnce(&mut Download),
+        edit: impl FnOnce(watch::Receiver<bool>) -> Fut,
+        finish: impl FnOnce(&mut Download, &Result<T, AppError>),
+    ) -> Result<T, AppError>
+    where
+        Fut: Future<Output = Result<T, AppError>>,
+    {
+        {
+            let mut downloads = self.tasks.downloads.lock().await;
+            let task = downloads
+                .get_mut(task_id)
+                .filter(|d| d.status == TaskStatus::Completed)
+                .ok_or(AppError::from("The task is busy; wait for it to finish"))?;
+            start(task);
+            self.observer.task_updated(task);
+            self.tasks.edits.lock().await.insert(task_id.to_string());
+        }
+
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+        self.tasks.cancel_senders.lock().await.insert(task_id.to_string(), cancel_tx);
+        let _worker = WorkerGuard::new(&self.tasks);
+        let result = edit(cancel_rx).await;
+        self.tasks.cancel_senders.lock().await.remove(task_id);
+        self.tasks.edits.lock().await.remove(task_id);
+
+        self.update_task(task_id, |download| {
+            download.status = TaskStatus::Completed;
+            finish(download, &result);
+        })
+        .await;
+        result
+    }
+
+    /// Replace a finished task's subtitles with edited cues, re-embedding them
+    /// A failed or cancelled edit leaves the task completed with its previous subtitles
+    pub async fn update_subtitles(&self, task_id: &str, cues: Vec<SubtitleCue>) -> Result<(), AppError> {
+        let task = self
+            .get_task(task_id)
+            .await
+            .ok_or_else(|| AppError::from(format!("Download not found: {}", task_id)))?;
+        if task.status != TaskStatus::Completed {
+            return Err("Subtitles can only be edited once the task has completed".into());
+        }
+        let output_path = PathBuf::from(task.output_path.ok_or(AppError::from("Task has no output file"))?);
+        let language = task.transcription_language;
+
+        let manager = TranscriptionManager::new().with_log(self.spawn_log_collector(task_id.to_string()));
+        let progress_tx = self.spawn_transcribe_forwarder(task_id.to_string());
+        self.run_edit(
+            task_id,
+            |download| {
+                download.status = TaskStatus::transcribing("embedding");
+                download.transcription_progress = Some(0.0);
+            },
+            |cancel_rx| async move {
+                manager
+                    .replace_subtitles(&output_path, &cues, language.as_deref(), progress_tx, cancel_rx)
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| if e == "Cancelled" { AppError::Cancelled(e) } else { AppError::from(e) })
+            },
+            |download, result| {
+                download.transcription_progress = Some(100.0);
+                download.transcription_message = Some(match result {
+                    Ok(()) => "Subtitles updated".to_string(),
+                    Err(AppError::Cancelled(_)) => "Subtitle edit cancelled".to_string(),
+                    Err(e) => format!("Subtitle edit failed: {}", e),
+                });
+            },
+        )
+        .await
+    }
+
+    /// Cut named clips out of a completed task's file without re-downloading; the full file stays.
+    /// Each clip becomes its own task (task_type "clip") and the ids are returned in request order
+    pub async fn create_clips(&self, task_id: &str, clips: Vec<ClipRequest>) -> Result<Vec<String>, AppError> {
+        if clips.is_empty() {
+            return Err("No clips requested".into());
+        }
+
+        let source = self
+            .get_task(task_id)
+            .await
+            .ok_or_else(|| AppError::from(format!("Download not found: {}", task_id)))?;
+        if source.status != TaskStatus::Completed {
+            return Err("Clips can only be cut from a completed task".into());
+        }
+        let source_path = PathBuf::from(source.output_path.clone().ok_or(AppError::from("Task has no output file"))?);
+        if !source_path.exists() {
+            return Err(format!("File not found: {}", source_path.display()).into());
+        }
+        let duration = match source.duration {
+            Some(d) => Some(d),
+            None => get_audio_duration(&source_path).await,
+        };
+
+        let dir = source_path.parent().unwrap_or(Path::new("."));
+        let stem = source_path.file_stem().and_then(|s| s.to_str()).unwrap_or("video");
+        let ext = source_path.extension().and_then(|e| e.to_str()).unwrap_or("mp4");
+
+        // Validate everything before creating any task, so a bad clip doesn't leave half a batch
+        let mut jobs: Vec<(ClipRequest, String, PathBuf)> = Vec::new();
+        for (i, clip) in clips.into_iter().enumerate() {
+            let name = safe_file_name(&clip.name).unwrap_or_else(|| format!("clip{}", i + 1));
+            if !(clip.start >= 0.0 && clip.end > clip.start) {
+                return Err(format!("Clip '{}' must end after it starts", name).into());
+            }
+            if duration.is_some_and(|d| clip.start >= d) {
+                return Err(format!("Clip '{}' starts after the end of the video", name).into());
+            }
+            let output = dir.join(format!("{}_{}.{}", stem, name, ext));
+            if output.exists() || jobs.iter().any(|(_, _, o)| *o == output) {
+                return Err(format!("{} already exists", output.display()).into());
+            }
+            jobs.push((clip, name, output));
+        }
+
+        let mut queued = Vec::new();
+        {
+            let mut downloads = self.tasks.downloads.lock().await;
+            let mut cancel_senders = self.tasks.cancel_senders.lock().await;
+            for (clip, name, output) in jobs {
+                let end = duration.map_or(clip.end, |d| clip.end.min(d));
+                let download = Download {
+                    id: Uuid::new_v4().to_string(),
+                    url: source.url.clone(),
+                    title: format!("{} ({})", source.title, name),
+                    thumbnail: source.thumbnail.clone(),
+                    status: TaskStatus::Pending,
+                    progress: 0.0,
+                    speed: None,
+                    eta: None,
+                    output_path: Some(output.to_string_lossy().to_string()),
+                    format: source.format.clone(),
+                    error: None,
+                    duration: Some(end - clip.start),
+                    whisper_model: None,
+                    transcription_engine: None,
+                    transcription_style: None,
+                    transcription_language: None,
+                    subtitle_format: None,
+                    transcription_progress: None,
+                    transcription_message: None,
+                    task_type: "clip".to_string(),
+                    source_path: Some(source_path.to_string_lossy().to_string()),
+                    in_place: false,
+                    codec: source.codec.clone(),
+                    log_tail: Vec::new(),
+                    output_dir: source.output_dir.clone(),
+                    rtf: None,
+                };
+                let (cancel_tx, cancel_rx) = watch::channel(false);
+                cancel_senders.insert(download.id.clone(), cancel_tx);
+                downloads.insert(download.id.clone(), download.clone());
+                self.observer.task_updated(&download);
+                queued.push((download.id, clip.start, end - clip.start, output, cancel_rx));
+            }
+        }
+
+        let ids = queued.iter().map(|(id, ..)| id.clone()).collect();
+        let pipeline = self.clone();
+
+        tokio::spawn(async move {
+            let _worker = WorkerGuard::new(&pipeline.tasks);
+            for (id, start, length, output, cancel_rx) in queued {
+                // A cancelled clip is skipped
+                if pipeline.update_task(&id, |download| download.status = TaskStatus::Postprocessing).await {
+                    match crate::media::cut_clip(&source_path, &output, start, length, cancel_rx).await {
+                        Ok(()) => pipeline.complete_task(&id, 0, None, |_| {}).await,
+                        Err(e) => pipeline.fail_task(&id, e.into()).await,
+                    }
+                }
+                pipeline.tasks.cancel_senders.lock().await.remove(&id);
+            }
+        });
+
+        Ok(ids)
+    }
+
+    /// Rewrite a completed download into another container (e.g. webm to mp4 for a TV)
+    /// The original is kept; the task's output_path moves to the new file
+    pub async fn remux(&self, task_id: &str, target_container: &str) -> Result<String, AppError> {
+        let task = self
+            .get_task(task_id)
+            .await
+            .ok_or_else(|| AppError::from(format!("Download not found: {}", task_id)))?;
+        if task.status != TaskStatus::Completed {
+            return Err("Only a completed task can be remuxed".into());
+        }
+        let source = PathBuf::from(task.output_path.ok_or(AppError::from("Task has no output file"))?);
+        if !source.exists() {
+            return Err(format!("File not found: {}", source.display()).into());
+        }
+
+        let target = target_container.trim().trim_start_matches('.').to_lowercase();
+        if !REMUX_CONTAINERS.contains(&target.as_str()) {
+            return Err(AppError::Unsupported(format!(
+                "Unsupported container '{}'. Supported: {}",
+                target_container,
+                REMUX_CONTAINERS.join(", ")
+            )));
+        }
+        if TranscriptionManager::is_audio_file(&source) {
+            return Err("Audio files can't be remuxed into a video container".into());
+        }
+        let output = source.with_extension(&target);
+        if output == source {
+            return Err(format!("The file is already .{}", target).into());
+        }
+        if output.exists() {
+            return Err(format!("{} already exists", output.display()).into());
+        }
+        let subtitle_codec = TranscriptionManager::subtitle_codec_for(&target)?;
+
+        let output_str = output.to_string_lossy().to_string();
+        self.run_edit(
+            task_id,
+            |download| download.status = TaskStatus::Postprocessing,
+            |cancel_rx| crate::media::remux(&source, &output, subtitle_codec, cancel_rx),
+            |download, result| {
+                if result.is_ok() {
+                    download.output_path = Some(output_str.clone());
+                    // Streams may have been re-encoded
+                    download.codec = None;
+                }
+            },
+        )
+        .await?;
+        Ok(output_str)
+    }
+
+    /// Burn karaoke subtitles into a copy of a completed task's video: its word-level cues
+    /// become an ASS script whose \k tags highlight each word as it is spoken
+    /// The task keeps its output; the new `<name>.karaoke.mp4` path is returned
+    pub async fn burn_karaoke(&self, task_id: &str, style: &KaraokeStyle) -> Result<String, AppError> {
+        style.validate()?;
+        let task = self
+            .get_task(task_id)
+            .await
+            .ok_or_else(|| AppError::from(format!("Download not found: {}", task_id)))?;
+        if task.status != TaskStatus::Completed {
+            return Err("Only a completed task can be burned in".into());
+        }
+        if task.transcription_style.as_deref() != Some("word") {
+            return Err("Karaoke needs word timings; transcribe with the Word style first".into());
+        }
+        let output_path = PathBuf::from(task.output_path.ok_or(AppError::from("Task has no output file"))?);
+        // Local transcriptions that didn't write in place output only the .srt
+        let video = if output_path.extension().is_some_and(|e| e.eq_ignore_ascii_case("srt")) {
+            PathBuf::from(task.source_path.ok_or(AppError::from("Task has no video file"))?)
+        } else {
+            output_path.clone()
+        };
+        if !video.exists() {
+            return Err(format!("File not found: {}", video.display()).into());
+        }
+        if TranscriptionManager::is_audio_file(&video) {
+            return Err("Karaoke subtitles can only be burned into a video".into());
+        }
+        let output = video.with_extension("karaoke.mp4");
+        if output.exists() {
+            return Err(format!("{} already exists", output.display()).into());
+        }
+
+        let words = crate::transcription::parse_srt(&crate::media::read_subtitles(&output_path).await?);
+        if words.is_empty() {
+            return Err("No subtitles found to burn in".into());
+        }
+        let ass = generate_karaoke_ass(&words, style, crate::media::video_dimensions(&video).await);
+
+        self.run_edit(
+            task_id,
+            |download| download.status = TaskStatus::Postprocessing,
+            |cancel_rx| crate::media::burn_ass(&video, &ass, &output, cancel_rx),
+            |_, _| {},
+        )
+        .await?;
+        Ok(output.to_string_lossy().to_string())
+    }
+
+    /// Whether any task is still downloading or transcribing
+    pub async fn has_active_tasks(&self) -> bool {
+        self.tasks.downloads.lock().await.values().any(is_active)
+            || self.tasks.workers.load(Ordering::SeqCst) > 0
+    }
+
+    /// Stop everything before the app exits: cancel all tasks, give their child
+    /// processes up to `grace` to be killed, then remove what they left half-written
+    pub async fn shutdown(&self, grace: Duration) {
+        // An edit's completed file is not a partial download; the edit cleans up after itself
+        let edits = self.tasks.edits.lock().await.clone();
+        let stopped: Vec<Download> = self
+            .tasks
+            .downloads
+            .lock()
+            .await
+            .values()
+            .filter(|d| is_active(d) && !edits.contains(&d.id))
+            .cloned()
+            .collect();
+
+        self.cancel_all().await;
+
+        let deadline = Instant::now() + grace;
+        while self.tasks.workers.load(Ordering::SeqCst) > 0 && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        let remaining = self.tasks.workers.load(Ordering::SeqCst);
+        if remaining > 0 {
+            log::warn!("{} task(s) still running at shutdown", remaining);
+        }
+
+        for download in stopped {
+            let Some(output_path) = download.output_path.map(PathBuf::from) else {
+                continue;
+            };
+            // Local transcriptions point at the user's source file; only the temp folder is ours
+            match download.task_type.as_str() {
+                "download" => remove_partial_files(&output_path).await,
+                // Clips never overwrite, so whatever is at the path is a half-cut clip
+                "clip" => {
+                    let _ = tokio::fs::remove_file(&output_path).await;
+                }
+                _ => {}
+            }
+            if let Some(dir) = output_path.parent() {
+                let _ = tokio::fs::remove_dir_all(dir.join(".zinc_temp")).await;
+            }
+        }
+    }
+}
+
+/// Shared name prefix of yt-dlp's intermediate files for an output,
+/// e.g. "Title_id." for "Title_id.mp4", "Title_id.f137.mp4" or "Title_id.mp4.part"
+fn partial_file_prefix(output_path: &Path) -> Option<String> {
+    let name = output_path.file_name()?.to_str()?;
+    let name = name.strip_suffix(".part").unwrap_or(name);
+    let (base, _ext) = name.rsplit_once('.')?;
+    let base = match base.rsplit_once('.') {
+        Some((stem, format)) if is_format_suffix(format) => stem,
+        _ => base,
+    };
+    Some(format!("{}.", base))
+}
+
+/// "f137" style suffix yt-dlp gives each stream before merging
+fn is_format_suffix(part: &str) -> bool {
+    part.strip_prefix('f')
+        .is_some_and(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// A name made safe for a file name; None when nothing usable is left
+fn safe_file_name(name: &str) -> Option<String> {
+    let cleaned: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_control() || r#"\/:*?"<>|"#.contains(c) { '_' } else { c })
+        .collect();
+    let cleaned = cleaned.trim_matches(['.', ' ']);
+    (!cleaned.is_empty()).then(|| cleaned.to_string())
+}
+
+/// Extension for a media Content-Type, for links whose path doesn't have one
+fn media_extension_for(content_type: &str) -> Option<&'static str> {
+    let ext = match content_type {
+        "audio/mpeg" | "audio/mp3" => "mp3",
+        "audio/mp4" | "audio/x-m4a" => "m4a",
+        "audio/aac" => "aac",
+        "audio/ogg" => "ogg",
+        "audio/opus" => "opus",
+        "audio/wav" | "audio/x-wav" | "audio/wave" => "wav",
+        "audio/flac" | "audio/x-flac" => "flac",
+        "video/mp4" => "mp4",
+        "video/webm" => "webm",
+        "video/quicktime" => "mov",
+        "video/x-matroska" => "mkv",
+        "video/x-msvideo" => "avi",
+        _ => return None,
+    };
+    Some(ext)
+}
+
+/// Check that `url` points at a media file rather than a web page, returning a
+/// file name for it. The Content-Type decides when the server sends one; the
+/// extension in the URL path is the fallback (and the only check if HEAD fails)
+async fn probe_media_url(url: &str) -> Result<String, String> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid URL '{}': {}", url, e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!("Unsupported URL scheme '{}': only http and https links can be fetched", parsed.scheme()));
+    }
+
+    let segment = parsed
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.replace("%20", " "))
+        .unwrap_or_default();
+    let path_ext = Path::new(&segment)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .filter(|e| TranscriptionManager::is_media_extension(e));
+
+    let content_type = match crate::network::http_client()
+        .head(parsed.clone())
+        .header("User-Agent", "Zinc-App")
+        .send()
+        .await
+    {
+        Ok(response) if response.status().is_success() => response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.split(';').next().unwrap_or(v).trim().to_lowercase()),
+        Ok(response) => {
+            log::warn!("HEAD {} returned {}; judging by extension", url, response.status());
+            None
+        }
+        Err(e) => {
+            log::warn!("HEAD {} failed: {}; judging by extension", url, e);
+            None
+        }
+    };
+
+    let not_media = || {
+        format!(
+            "Unsupported link: {} is not a direct audio or video file. Use a normal download for video-site URLs",
+            url
+        )
+    };
+    let ext = match content_type.as_deref() {
+        Some(ct) if ct.starts_with("audio/") || ct.starts_with("video/") => path_ext
+            .or_else(|| media_extension_for(ct).map(str::to_string))
+            .ok_or_else(|| format!("Unsupported media type '{}' at {}", ct, url))?,
+        Some("text/html") | Some("application/xhtml+xml") => return Err(not_media()),
+        // Generic types like application/octet-stream: trust the extension
+        _ => path_ext.ok_or_else(not_media)?,
+    };
+
+    let stem = Path::new(&segment)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .and_then(safe_file_name)
+        .unwrap_or_else(|| "media".to_string());
+    Ok(format!("{}.{}", stem, ext))
+}
+
+/// `dir/name`, or `dir/<stem> (n).<ext>` when that file already exists
+fn unique_path(dir: &Path, name: &str) -> PathBuf {
+    let candidate = dir.join(name);
+    if !candidate.exists() {
+        return candidate;
+    }
+    let (stem, ext) = name.rsplit_once('.').unwrap_or((name, ""));
+    (1..)
+        .map(|n| dir.join(format!("{} ({}).{}", stem, n, ext)))
+        .find(|p| !p.exists() && !PathBuf::from(format!("{}.part", p.to_string_lossy())).exists())
+        .unwrap_or(candidate)
+}
+
+/// Delete the .part/.ytdl/unmerged stream files an interrupted yt-dlp run leaves
+/// next to its output. The finished output itself is never touched
+async fn remove_partial_files(output_path: &Path) {
+    let (Some(dir), Some(prefix)) = (output_path.parent(), partial_file_prefix(output_path)) else {
+        return;
+    };
+    let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+        return;
+    };
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(rest) = name.strip_prefix(&prefix) else {
+            continue;
+        };
+        let is_partial = rest.ends_with(".part")
+            || rest.ends_with(".ytdl")
+            || rest.contains(".part-Frag")
+            || rest.starts_with("temp.")
+            || rest.split('.').next().is_some_and(is_format_suffix);
+        if is_partial {
+            log::info!("Removing partial file {}", name);
+            let _ = tokio::fs::remove_file(entry.path()).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_never_decreases_within_a_stage() {
+        let status = TaskStatus::Downloading;
+        assert_eq!(normalize_progress(&status, 60.0, &status, 40.0), 60.0);
+        assert_eq!(normalize_progress(&status, 60.0, &status, 75.0), 75.0);
+
+        let stage = TaskStatus::transcribing("transcribing");
+        assert_eq!(normalize_progress(&stage, 50.0, &stage, 10.0), 50.0);
+    }
+
+    #[test]
+    fn progress_resets_on_a_stage_change() {
+        assert_eq!(
+            normalize_progress(&TaskStatus::Downloading, 100.0, &TaskStatus::Postprocessing, 0.0),
+            0.0
+        );
+        assert_eq!(
+            normalize_progress(
+                &TaskStatus::transcribing("extracting"),
+                100.0,
+                &TaskStatus::transcribing("transcribing"),
+                5.0
+            ),
+            5.0
+        );
+    }
+
+    #[test]
+    fn progress_is_clamped_to_0_100() {
+        let status = TaskStatus::Downloading;
+        assert_eq!(normalize_progress(&status, 10.0, &status, 250.0), 100.0);
+        assert_eq!(normalize_progress(&TaskStatus::Pending, 0.0, &status, -5.0), 0.0);
+    }
+
+    #[test]
+    fn non_finite_progress_is_treated_as_zero() {
+        let status = TaskStatus::Downloading;
+        assert_eq!(normalize_progress(&TaskStatus::Pending, 0.0, &status, f64::NAN), 0.0);
+        assert_eq!(normalize_progress(&TaskStatus::Pending, 0.0, &status, f64::INFINITY), 0.0);
+        // NaN can't drag an existing value down either
+        assert_eq!(normalize_progress(&status, 30.0, &status, f64::NAN), 30.0);
+    }
+
+    struct NoObserver;
+
+    impl PipelineObserver for NoObserver {
+        fn task_updated(&self, _task: &Download) {}
+    }
+
+    fn completed_task(id: &str) -> Download {
+        Download {
+            id: id.to_string(),
+            url: String::new(),
+            title: "Video".to_string(),
+            thumbnail: None,
+            status: TaskStatus::Completed,
+            progress: 100.0,
+            speed: None,
+            eta: None,
+            output_path: Some("video.mp4".to_string()),
+            format: "best".to_string(),
+            error: None,
+            duration: None,
+            whisper_model: None,
+            transcription_engine: None,
+            transcription_style: None,
+            transcription_language: None,
+            subtitle_format: None,
+            transcription_progress: None,
+            transcription_message: None,
+            task_type: "download".to_string(),
+            source_path: None,
+            in_place: false,
+            codec: None,
+            log_tail: Vec::new(),
+            output_dir: None,
+            rtf: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn cancelled_edit_leaves_the_task_completed() {
+        let pipeline = Pipeline::standalone(Arc::new(NoObserver));
+        pipeline.tasks.downloads.lock().await.insert("a".to_string(), completed_task("a"));
+
+        let edit = pipeline.run_edit(
+            "a",
+            |download| download.status = TaskStatus::Postprocessing,
+            |mut cancel_rx| async move {
+                let _ = cancel_rx.wait_for(|cancelled| *cancelled).await;
+                Err::<(), _>(AppError::Cancelled("Cancelled".to_string()))
+            },
+            |_, _| {},
+        );
+        let cancel = async {
+            while !pipeline.tasks.cancel_senders.lock().await.contains_key("a") {
+                tokio::task::yield_now().await;
+            }
+            assert_eq!(pipeline.cancel_all().await, 0);
+        };
+        let (result, ()) = tokio::join!(edit, cancel);
+
+        assert!(matches!(result, Err(AppError::Cancelled(_))));
+        assert_eq!(pipeline.get_task("a").await.unwrap().status, TaskStatus::Completed);
+        assert!(!pipeline.has_active_tasks().await);
+    }
+}
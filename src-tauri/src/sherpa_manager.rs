@@ -1,17 +1,58 @@
-use futures_util::StreamExt;
 use std::path::PathBuf;
 use std::process::Stdio;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 use tokio::process::Command;
+use tokio::sync::watch;
 
-use crate::transcription::InstallProgress;
+use crate::config::AppConfig;
+use crate::error::AppError;
+use crate::transcription::{fetch_content_length, InstallProgress};
 
 const APP_IDENTIFIER: &str = "com.zinc.app";
 
 /// Sherpa-onnx version to download (from k2-fsa releases)
 const SHERPA_VERSION: &str = "v1.12.23";
 
+/// Values accepted for sherpa_decoding_method
+pub const SHERPA_DECODING_METHODS: &[&str] = &["greedy_search", "modified_beam_search"];
+
+/// Records which release is unpacked in the bin directory
+const VERSION_FILE: &str = "sherpa-version.txt";
+
+/// Reader wrapper that reports extraction progress from archive bytes consumed
+struct ProgressReader<R> {
+    inner: R,
+    read: u64,
+    total: u64,
+    last_percent: i32,
+    stage: String,
+    callback: Box<dyn Fn(InstallProgress) + Send>,
+}
+
+impl<R: std::io::Read> std::io::Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read += n as u64;
+
+        if self.total > 0 {
+            let percentage = (self.read as f64 / self.total as f64 * 100.0).min(100.0);
+            // Only report whole-percent changes to avoid flooding the UI
+            if percentage as i32 > self.last_percent {
+                self.last_percent = percentage as i32;
+                (self.callback)(InstallProgress {
+                    downloaded: self.read,
+                    total: Some(self.total),
+                    percentage,
+                    stage: self.stage.clone(),
+                });
+            }
+        }
+
+        Ok(n)
+    }
+}
+
 pub struct SherpaManager;
 
 impl SherpaManager {
@@ -56,6 +97,17 @@ impl SherpaManager {
         Ok(bin_dir.join(binary_name))
     }
 
+    /// Returns the full path to the speaker diarization binary from the same release
+    pub fn get_diarization_binary_path() -> Result<PathBuf, String> {
+        let bin_dir = Self::get_bin_dir()?;
+        let binary_name = if cfg!(target_os = "windows") {
+            "sherpa-onnx-offline-speaker-diarization.exe"
+        } else {
+            "sherpa-onnx-offline-speaker-diarization"
+        };
+        Ok(bin_dir.join(binary_name))
+    }
+
     /// Check if sherpa-onnx is installed
     pub async fn is_installed() -> bool {
         let binary_path = match Self::get_binary_path() {
@@ -65,6 +117,46 @@ impl SherpaManager {
         binary_path.exists()
     }
 
+    /// sherpa-onnx-offline flags for the configured sherpa_* settings; unset ones keep sherpa's defaults
+    /// Transducer (Zipformer) only: Moonshine and Canary have fixed front ends and no beam search
+    pub fn decoding_args(config: &AppConfig) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(method) = &config.sherpa_decoding_method {
+            args.push(format!("--decoding-method={}", method));
+        }
+        if let Some(paths) = config.sherpa_max_active_paths {
+            args.push(format!("--max-active-paths={}", paths));
+        }
+        if let Some(dim) = config.sherpa_feature_dim {
+            args.push(format!("--feat-dim={}", dim));
+        }
+        args
+    }
+
+    /// Check if the installed sherpa-onnx build ships the ONNX Runtime CUDA provider
+    pub fn has_cuda_provider() -> bool {
+        let provider_lib = if cfg!(target_os = "windows") {
+            "onnxruntime_providers_cuda.dll"
+        } else {
+            "libonnxruntime_providers_cuda.so"
+        };
+        Self::get_bin_dir()
+            .map(|dir| dir.join(provider_lib).exists())
+            .unwrap_or(false)
+    }
+
+    /// Release the app pins and installs
+    pub fn pinned_version() -> &'static str {
+        SHERPA_VERSION
+    }
+
+    /// Release tag written at install time; None for installs made before it was recorded
+    pub async fn installed_tag() -> Option<String> {
+        let path = Self::get_bin_dir().ok()?.join(VERSION_FILE);
+        let tag = fs::read_to_string(path).await.ok()?;
+        Some(tag.trim().to_string())
+    }
+
     /// Get the installed version by running -h and checking it runs
     #[allow(dead_code)]
     pub async fn get_installed_version() -> Result<String, String> {
@@ -103,14 +195,17 @@ impl SherpaManager {
             format!("sherpa-onnx-{}-linux-x64-shared.tar.bz2", SHERPA_VERSION)
         };
 
-        format!(
+        crate::network::model_url(&format!(
             "https://github.com/k2-fsa/sherpa-onnx/releases/download/{}/{}",
             SHERPA_VERSION, asset_name
-        )
+        ))
     }
 
     /// Install sherpa-onnx by downloading from GitHub
-    pub async fn install(progress_callback: Box<dyn Fn(InstallProgress) + Send>) -> Result<String, String> {
+    pub async fn install(
+        progress_callback: Box<dyn Fn(InstallProgress) + Send>,
+        cancel_rx: watch::Receiver<bool>,
+    ) -> Result<String, AppError> {
         let bin_dir = Self::get_bin_dir()?;
 
         // Create bin directory if it doesn't exist
@@ -118,6 +213,8 @@ impl SherpaManager {
             .await
             .map_err(|e| format!("Failed to create bin directory: {}", e))?;
 
+        crate::network::ensure_online("installing sherpa-onnx")?;
+
         let download_url = Self::get_download_url();
 
         progress_callback(InstallProgress {
@@ -127,19 +224,18 @@ impl SherpaManager {
             stage: "Downloading sherpa-onnx...".to_string(),
         });
 
-        let client = reqwest::Client::new();
-        let response = client
-            .get(&download_url)
-            .header("User-Agent", "Zinc-App")
-            .send()
-            .await
-            .map_err(|e| format!("Failed to download sherpa-onnx: {}", e))?;
+        let client = crate::network::http_client();
+        let response = crate::network::send_with_retry(
+            client.get(&download_url).header("User-Agent", "Zinc-App"),
+        )
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to download sherpa-onnx: {}", e)))?;
 
         if !response.status().is_success() {
-            return Err(format!(
+            return Err(AppError::Network(format!(
                 "Download failed with status: {}",
                 response.status()
-            ));
+            )));
         }
 
         let total_size = response.content_length();
@@ -154,8 +250,14 @@ impl SherpaManager {
 
         let mut stream = response.bytes_stream();
 
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk.map_err(|e| format!("Download error: {}", e))?;
+        while let Some(chunk) = crate::network::next_chunk(&mut stream).await? {
+            if *cancel_rx.borrow() {
+                drop(file);
+                let _ = fs::remove_file(&temp_archive).await;
+                return Err(AppError::Cancelled("Cancelled".to_string()));
+            }
+
+            let chunk = chunk.map_err(|e| AppError::Network(format!("Download error: {}", e)))?;
             file.write_all(&chunk)
                 .await
                 .map_err(|e| format!("Failed to write file: {}", e))?;
@@ -187,7 +289,7 @@ impl SherpaManager {
         });
 
         // Extract the archive (all platforms use tar.bz2)
-        Self::extract_tar_bz2(&temp_archive, &bin_dir).await?;
+        Self::extract_tar_bz2(&temp_archive, &bin_dir, progress_callback).await?;
 
         // Clean up archive file
         let _ = fs::remove_file(&temp_archive).await;
@@ -209,6 +311,8 @@ impl SherpaManager {
             }
         }
 
+        let _ = fs::write(bin_dir.join(VERSION_FILE), SHERPA_VERSION).await;
+
         Ok(SHERPA_VERSION.to_string())
     }
 
@@ -277,7 +381,11 @@ impl SherpaManager {
     }
 
     /// Extract tar.bz2 file using Rust libraries (works on all platforms)
-    async fn extract_tar_bz2(archive_path: &PathBuf, dest_dir: &PathBuf) -> Result<(), String> {
+    async fn extract_tar_bz2(
+        archive_path: &PathBuf,
+        dest_dir: &PathBuf,
+        progress_callback: Box<dyn Fn(InstallProgress) + Send>,
+    ) -> Result<(), String> {
         log::info!("Extracting {:?} to {:?}", archive_path, dest_dir);
 
         let archive_path = archive_path.clone();
@@ -291,7 +399,15 @@ impl SherpaManager {
 
             let file = File::open(&archive_path)
                 .map_err(|e| format!("Failed to open archive: {}", e))?;
-            let reader = BufReader::new(file);
+            let total = file.metadata().map(|m| m.len()).unwrap_or(0);
+            let reader = BufReader::new(ProgressReader {
+                inner: file,
+                read: 0,
+                total,
+                last_percent: -1,
+                stage: "Extracting...".to_string(),
+                callback: progress_callback,
+            });
             let decompressor = BzDecoder::new(reader);
             let mut archive = Archive::new(decompressor);
 
@@ -338,25 +454,51 @@ impl SherpaManager {
         .map_err(|e| format!("Extraction task failed: {}", e))?
     }
 
+    /// Where a model package lives: its directory and the archive download_model
+    /// leaves behind if extraction is interrupted
+    pub fn model_storage(engine: &str, model_dir_name: &str) -> Vec<PathBuf> {
+        match Self::get_models_dir(engine) {
+            Ok(models_dir) => vec![
+                models_dir.join(model_dir_name),
+                models_dir.join(format!("{}.tar.bz2", model_dir_name)),
+            ],
+            Err(_) => Vec::new(),
+        }
+    }
+
     /// Download a model package (tar.bz2) for a specific engine
+    /// URLs to a bare .onnx file are saved into model_dir_name as-is
     pub async fn download_model(
         engine: &str,
         model_url: &str,
         model_dir_name: &str,
         progress_callback: Box<dyn Fn(InstallProgress) + Send>,
-    ) -> Result<PathBuf, String> {
+        cancel_rx: watch::Receiver<bool>,
+    ) -> Result<PathBuf, AppError> {
+        let model_url = &crate::network::model_url(model_url);
         let models_dir = Self::get_models_dir(engine)?;
         fs::create_dir_all(&models_dir)
             .await
             .map_err(|e| format!("Failed to create models directory: {}", e))?;
 
         let model_dir = models_dir.join(model_dir_name);
-
-        // Check if already installed
-        if model_dir.exists() && model_dir.join("tokens.txt").exists() {
+        let bare_file = model_url
+            .rsplit('/')
+            .next()
+            .filter(|name| name.ends_with(".onnx"))
+            .map(str::to_string);
+
+        // Check if already installed; packages without tokens.txt ship a model.onnx
+        let installed = match &bare_file {
+            Some(name) => model_dir.join(name).exists(),
+            None => model_dir.join("tokens.txt").exists() || model_dir.join("model.onnx").exists(),
+        };
+        if installed {
             return Ok(model_dir);
         }
 
+        crate::network::ensure_online("downloading a model")?;
+
         progress_callback(InstallProgress {
             downloaded: 0,
             total: None,
@@ -364,32 +506,48 @@ impl SherpaManager {
             stage: format!("Downloading {} model...", engine),
         });
 
-        let client = reqwest::Client::new();
-        let response = client
-            .get(model_url)
-            .header("User-Agent", "Zinc-App")
-            .send()
-            .await
-            .map_err(|e| format!("Failed to start download: {}", e))?;
+        let client = crate::network::http_client();
+        let response = crate::network::send_with_retry(
+            client.get(model_url).header("User-Agent", "Zinc-App"),
+        )
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to start download: {}", e)))?;
 
         if !response.status().is_success() {
-            return Err(format!(
+            return Err(AppError::Network(format!(
                 "Download failed with status: {}",
                 response.status()
-            ));
+            )));
         }
 
-        let total_size = response.content_length();
+        let total_size = match response.content_length() {
+            Some(len) => Some(len),
+            None => fetch_content_length(&client, model_url).await,
+        };
         let mut downloaded: u64 = 0;
 
-        let archive_path = models_dir.join(format!("{}.tar.bz2", model_dir_name));
+        let archive_path = match &bare_file {
+            Some(name) => {
+                fs::create_dir_all(&model_dir)
+                    .await
+                    .map_err(|e| format!("Failed to create model directory: {}", e))?;
+                model_dir.join(format!("{}.part", name))
+            }
+            None => models_dir.join(format!("{}.tar.bz2", model_dir_name)),
+        };
         let mut file = fs::File::create(&archive_path)
             .await
             .map_err(|e| format!("Failed to create file: {}", e))?;
 
         let mut stream = response.bytes_stream();
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk.map_err(|e| format!("Download error: {}", e))?;
+        while let Some(chunk) = crate::network::next_chunk(&mut stream).await? {
+            if *cancel_rx.borrow() {
+                drop(file);
+                let _ = fs::remove_file(&archive_path).await;
+                return Err(AppError::Cancelled("Cancelled".to_string()));
+            }
+
+            let chunk = chunk.map_err(|e| AppError::Network(format!("Download error: {}", e)))?;
             file.write_all(&chunk)
                 .await
                 .map_err(|e| format!("Write error: {}", e))?;
@@ -412,6 +570,13 @@ impl SherpaManager {
             .map_err(|e| format!("Failed to flush file: {}", e))?;
         drop(file);
 
+        if let Some(name) = &bare_file {
+            fs::rename(&archive_path, model_dir.join(name))
+                .await
+                .map_err(|e| format!("Failed to save model file: {}", e))?;
+            return Ok(model_dir);
+        }
+
         progress_callback(InstallProgress {
             downloaded,
             total: total_size,
@@ -420,7 +585,7 @@ impl SherpaManager {
         });
 
         // Extract the archive
-        Self::extract_model_tar_bz2(&archive_path, &models_dir).await?;
+        Self::extract_model_tar_bz2(&archive_path, &models_dir, progress_callback).await?;
 
         // Clean up the archive
         let _ = fs::remove_file(&archive_path).await;
@@ -429,7 +594,11 @@ impl SherpaManager {
     }
 
     /// Extract model tar.bz2 archive using Rust libraries
-    async fn extract_model_tar_bz2(archive_path: &PathBuf, dest_dir: &PathBuf) -> Result<(), String> {
+    async fn extract_model_tar_bz2(
+        archive_path: &PathBuf,
+        dest_dir: &PathBuf,
+        progress_callback: Box<dyn Fn(InstallProgress) + Send>,
+    ) -> Result<(), String> {
         log::info!("Extracting model {:?} to {:?}", archive_path, dest_dir);
 
         let archive_path = archive_path.clone();
@@ -443,7 +612,15 @@ impl SherpaManager {
 
             let file = File::open(&archive_path)
                 .map_err(|e| format!("Failed to open archive: {}", e))?;
-            let reader = BufReader::new(file);
+            let total = file.metadata().map(|m| m.len()).unwrap_or(0);
+            let reader = BufReader::new(ProgressReader {
+                inner: file,
+                read: 0,
+                total,
+                last_percent: -1,
+                stage: "Extracting model files...".to_string(),
+                callback: progress_callback,
+            });
             let decompressor = BzDecoder::new(reader);
             let mut archive = Archive::new(decompressor);
 
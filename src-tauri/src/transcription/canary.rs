@@ -0,0 +1,372 @@
+use super::{
+    check_nvidia_gpu, generate_srt_from_segments, get_audio_duration, parse_json_text_field,
+    postprocess_segments, segments_from_text, split_audio_segments, ChunkDir, validate_model_files, InstallProgress,
+    TranscribeProgress, TranscriptionEngine, TranscriptionModel,
+    TranscriptionSettings,
+};
+use super::diarization::label_speakers;
+use crate::error::AppError;
+use crate::sherpa_manager::SherpaManager;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::fs;
+use tokio::process::Command;
+use tokio::sync::{mpsc, watch};
+
+/// NeMo Canary 180M Flash, exported to ONNX by sherpa-onnx
+const CANARY_180M_URL: &str = "https://github.com/k2-fsa/sherpa-onnx/releases/download/asr-models/sherpa-onnx-nemo-canary-180m-flash-en-es-de-fr-int8.tar.bz2";
+const CANARY_180M_DIR: &str = "sherpa-onnx-nemo-canary-180m-flash-en-es-de-fr-int8";
+
+/// Canary has no token timestamps in sherpa-onnx, so cues are timed per window;
+/// shorter windows give tighter cues at the cost of more context breaks
+const CHUNK_DURATION: f64 = 20.0;
+
+/// Canary transcription engine using the sherpa-onnx offline CLI
+/// Most accurate engine for English, Spanish, German and French, but large and slow
+pub struct CanaryEngine {
+    settings: TranscriptionSettings,
+}
+
+impl CanaryEngine {
+    pub fn new(settings: TranscriptionSettings) -> Self {
+        Self { settings }
+    }
+
+    /// Get the models directory for Canary
+    fn get_models_dir() -> Result<PathBuf, String> {
+        SherpaManager::get_models_dir("canary")
+    }
+
+    /// Check if a model is installed
+    fn is_model_installed(_model: &str) -> bool {
+        if let Ok(models_dir) = Self::get_models_dir() {
+            // Check for the tokens file as indicator that model is complete
+            models_dir.join(CANARY_180M_DIR).join("tokens.txt").exists()
+        } else {
+            false
+        }
+    }
+
+    /// Get the model configuration paths (encoder, decoder, tokens)
+    fn get_model_paths(model: &str) -> Result<(PathBuf, PathBuf, PathBuf), String> {
+        let model_dir = Self::get_models_dir()?.join(CANARY_180M_DIR);
+
+        if !model_dir.exists() {
+            return Err(format!("Model '{}' is not installed", model));
+        }
+
+        Ok((
+            model_dir.join("encoder.int8.onnx"),
+            model_dir.join("decoder.int8.onnx"),
+            model_dir.join("tokens.txt"),
+        ))
+    }
+
+    /// Pick the sherpa-onnx execution provider: CUDA when the installed build
+    /// ships it and an NVIDIA GPU is present, CPU otherwise
+    async fn get_provider() -> &'static str {
+        if SherpaManager::has_cuda_provider() && check_nvidia_gpu().await {
+            "cuda"
+        } else {
+            "cpu"
+        }
+    }
+
+    /// Transcribe a single audio file, returning its text; a cancel kills sherpa-onnx
+    /// `model_args` are the model file, language and provider flags built by `transcribe`
+    async fn transcribe_single(
+        audio_path: &Path,
+        sherpa_binary: &Path,
+        model_args: &[String],
+        provider: &str,
+        mut cancel_rx: watch::Receiver<bool>,
+    ) -> Result<String, String> {
+        let mut cmd = Command::new(sherpa_binary);
+        cmd.args(model_args);
+        cmd.arg(audio_path);
+
+        cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).kill_on_drop(true);
+
+        #[cfg(target_os = "windows")]
+        cmd.creation_flags(0x08000000);
+
+        log::info!("Running sherpa-onnx-offline for Canary transcription (provider: {})", provider);
+
+        let child = cmd
+            .spawn()
+            .map_err(|e| format!("Failed to run sherpa-onnx: {}", e))?;
+        let output = child.wait_with_output();
+        tokio::pin!(output);
+
+        let mut cancel_open = true;
+        let output = loop {
+            tokio::select! {
+                output = &mut output => {
+                    break output.map_err(|e| format!("Failed to run sherpa-onnx: {}", e))?;
+                }
+                changed = cancel_rx.changed(), if cancel_open => {
+                    // A dropped sender can't cancel anymore; keep waiting on the process
+                    if changed.is_err() {
+                        cancel_open = false;
+                    } else if *cancel_rx.borrow() {
+                        // Returning drops the child, which kills it
+                        return Err("Cancelled".to_string());
+                    }
+                }
+            }
+        };
+
+        let stdout_str = String::from_utf8_lossy(&output.stdout);
+        let stderr_str = String::from_utf8_lossy(&output.stderr);
+
+        if !output.status.success() {
+            return Err(format!(
+                "sherpa-onnx transcription failed: {}",
+                stderr_str.lines().next().unwrap_or("unknown error")
+            ));
+        }
+
+        // sherpa-onnx prints the JSON result to stderr on some platforms and stdout on others
+        let combined_output = format!("{}\n{}", stdout_str, stderr_str);
+        Ok(parse_json_text_field(&combined_output))
+    }
+}
+
+impl Default for CanaryEngine {
+    fn default() -> Self {
+        Self::new(TranscriptionSettings::default())
+    }
+}
+
+#[async_trait::async_trait]
+impl TranscriptionEngine for CanaryEngine {
+    fn id(&self) -> &'static str {
+        "canary"
+    }
+
+    fn name(&self) -> &'static str {
+        "Canary"
+    }
+
+    fn description(&self) -> &'static str {
+        "Highest-accuracy multilingual engine, slower (2-6x realtime)"
+    }
+
+    fn gpu_required(&self) -> bool {
+        false
+    }
+
+    async fn check_gpu_available(&self) -> bool {
+        Self::get_provider().await == "cuda"
+    }
+
+    async fn is_available(&self) -> Result<bool, String> {
+        let sherpa_installed = SherpaManager::is_installed().await;
+        Ok(sherpa_installed && Self::is_model_installed("180m-flash"))
+    }
+
+    async fn available_models(&self) -> Vec<TranscriptionModel> {
+        let (speed_gpu, speed_cpu) = self.speed_multiplier("180m-flash");
+        vec![TranscriptionModel {
+            id: "180m-flash".to_string(),
+            name: "180M Flash (int8)".to_string(),
+            size: "210 MB".to_string(),
+            installed: Self::is_model_installed("180m-flash"),
+            speed_gpu,
+            speed_cpu,
+        }]
+    }
+
+    fn speed_multiplier(&self, _model: &str) -> (f64, f64) {
+        // Autoregressive decoder: several times slower than the transducer engines
+        let (gpu, cpu) = (6.0, 2.0);
+        // Without the CUDA provider sherpa runs on CPU, so the GPU figure would be misleading
+        if SherpaManager::has_cuda_provider() {
+            (gpu, cpu)
+        } else {
+            (cpu, cpu)
+        }
+    }
+
+    fn supported_languages(&self) -> Vec<&'static str> {
+        vec!["en", "es", "de", "fr"]
+    }
+
+    async fn install(
+        &self,
+        progress_callback: Box<dyn Fn(InstallProgress) + Send + 'static>,
+        cancel_rx: watch::Receiver<bool>,
+    ) -> Result<(), AppError> {
+        if !SherpaManager::is_installed().await {
+            SherpaManager::install(progress_callback, cancel_rx).await?;
+        }
+        Ok(())
+    }
+
+    async fn download_model(
+        &self,
+        _model: &str,
+        progress_callback: Box<dyn Fn(InstallProgress) + Send + 'static>,
+        cancel_rx: watch::Receiver<bool>,
+    ) -> Result<(), AppError> {
+        // Auto-install sherpa-onnx if not installed
+        if !SherpaManager::is_installed().await {
+            log::info!("sherpa-onnx not installed, installing automatically...");
+            SherpaManager::install(Box::new(move |progress| {
+                log::info!("Installing sherpa-onnx: {}% - {}", progress.percentage as i32, progress.stage);
+            }), cancel_rx.clone()).await?;
+        }
+
+        SherpaManager::download_model("canary", CANARY_180M_URL, CANARY_180M_DIR, progress_callback, cancel_rx).await?;
+
+        Ok(())
+    }
+
+    fn model_storage(&self, _model: &str) -> Vec<PathBuf> {
+        SherpaManager::model_storage("canary", CANARY_180M_DIR)
+    }
+
+    async fn validate_model(&self, model: &str) -> Result<(), String> {
+        let (encoder, decoder, tokens) = Self::get_model_paths(model)?;
+        validate_model_files(model, &[encoder, decoder, tokens]).await
+    }
+
+    async fn transcribe(
+        &self,
+        audio_path: &Path,
+        model: &str,
+        language: Option<&str>,
+        _style: &str, // No token timestamps, so word mode isn't possible
+        progress_tx: mpsc::Sender<TranscribeProgress>,
+        cancel_rx: watch::Receiver<bool>,
+    ) -> Result<PathBuf, String> {
+        if *cancel_rx.borrow() {
+            return Err("Cancelled".to_string());
+        }
+
+        let _ = progress_tx
+            .send(TranscribeProgress {
+                stage: "preparing".to_string(),
+                progress: 0.0,
+                message: "Loading Canary model...".to_string(),
+            })
+            .await;
+
+        let (encoder, decoder, tokens) = Self::get_model_paths(model)?;
+
+        for (name, path) in [("encoder", &encoder), ("decoder", &decoder), ("tokens", &tokens)] {
+            if !path.exists() {
+                return Err(format!(
+                    "Model file '{}' not found at {:?}. Please download the model first.",
+                    name, path
+                ));
+            }
+        }
+
+        let sherpa_binary = SherpaManager::get_binary_path()?;
+        if !sherpa_binary.exists() {
+            return Err("sherpa-onnx is not installed. Please install it first.".to_string());
+        }
+
+        // Canary can't detect the language itself
+        let language = language.unwrap_or("en");
+        let provider = Self::get_provider().await;
+        let duration = get_audio_duration(audio_path).await.unwrap_or(60.0);
+
+        // Source and target language are the same, so Canary transcribes rather than translates
+        let model_args = [
+            format!("--canary-encoder={}", encoder.to_string_lossy()),
+            format!("--canary-decoder={}", decoder.to_string_lossy()),
+            format!("--canary-src-lang={}", language),
+            format!("--canary-tgt-lang={}", language),
+            "--canary-use-pnc=1".to_string(), // Punctuation and capitalization
+            format!("--tokens={}", tokens.to_string_lossy()),
+            format!("--provider={}", provider),
+            "--num-threads=4".to_string(),
+        ];
+
+        let temp_dir = ChunkDir::create(audio_path.parent().unwrap_or(Path::new(".")), ".zinc_canary_chunks").await?;
+        let chunk_paths = split_audio_segments(audio_path, temp_dir.path(), CHUNK_DURATION).await?;
+        let num_chunks = chunk_paths.len();
+
+        log::info!(
+            "Canary transcription: {:.1}s audio in {} chunk(s) of {:.0}s, language {}",
+            duration,
+            num_chunks,
+            CHUNK_DURATION,
+            language
+        );
+
+        let mut segments: Vec<(i64, i64, String)> = Vec::new();
+
+        for (i, chunk_path) in chunk_paths.iter().enumerate() {
+            if *cancel_rx.borrow() {
+                return Err("Cancelled".to_string());
+            }
+
+            let chunk_start = i as f64 * CHUNK_DURATION;
+            let progress = 5.0 + (85.0 * i as f64 / num_chunks as f64);
+            let _ = progress_tx
+                .send(TranscribeProgress {
+                    stage: "transcribing".to_string(),
+                    progress,
+                    message: format!("Processing chunk {}/{}...", i + 1, num_chunks),
+                })
+                .await;
+
+            let chunk_text =
+                Self::transcribe_single(chunk_path, &sherpa_binary, &model_args, provider, cancel_rx.clone()).await;
+
+            let _ = fs::remove_file(chunk_path).await;
+
+            match chunk_text {
+                Ok(text) => {
+                    // Spread the window's text over the window, then shift it into place
+                    let chunk_len = (duration - chunk_start).clamp(0.1, CHUNK_DURATION);
+                    let offset_ms = (chunk_start * 1000.0) as i64;
+                    segments.extend(
+                        segments_from_text(&text, chunk_len)
+                            .into_iter()
+                            .map(|(start, end, text)| (start + offset_ms, end + offset_ms, text)),
+                    );
+                }
+                Err(e) if e == "Cancelled" => return Err(e),
+                Err(e) => {
+                    log::warn!("Chunk {} failed: {}", i + 1, e);
+                    // Continue with other chunks
+                }
+            }
+        }
+
+
+        if segments.is_empty() {
+            return Err("Transcription produced no text. The audio may be silent, corrupted, or in an unsupported format.".to_string());
+        }
+
+        let _ = progress_tx
+            .send(TranscribeProgress {
+                stage: "transcribing".to_string(),
+                progress: 90.0,
+                message: "Generating subtitles...".to_string(),
+            })
+            .await;
+
+        let segments = postprocess_segments(segments, "sentence", &self.settings);
+        let segments = label_speakers(audio_path, segments, &self.settings, cancel_rx.clone()).await?;
+        let srt_path = audio_path.with_extension("srt");
+
+        fs::write(&srt_path, generate_srt_from_segments(segments))
+            .await
+            .map_err(|e| format!("Failed to write SRT file: {}", e))?;
+
+        let _ = progress_tx
+            .send(TranscribeProgress {
+                stage: "transcribing".to_string(),
+                progress: 95.0,
+                message: "Transcription complete, preparing to embed...".to_string(),
+            })
+            .await;
+
+        Ok(srt_path)
+    }
+}
@@ -0,0 +1,224 @@
+use super::{InstallProgress, TranscriptionSettings};
+use crate::error::AppError;
+use crate::sherpa_manager::SherpaManager;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::process::Command;
+use tokio::sync::watch;
+
+/// Models directory name under the app's models folder
+const MODELS_ENGINE: &str = "diarization";
+
+/// pyannote segmentation model, packaged as tar.bz2 with a model.onnx inside
+const SEGMENTATION_URL: &str = "https://github.com/k2-fsa/sherpa-onnx/releases/download/speaker-segmentation-models/sherpa-onnx-pyannote-segmentation-3-0.tar.bz2";
+const SEGMENTATION_DIR: &str = "sherpa-onnx-pyannote-segmentation-3-0";
+
+/// Speaker embedding model, published as a bare .onnx file
+const EMBEDDING_URL: &str = "https://github.com/k2-fsa/sherpa-onnx/releases/download/speaker-recongition-models/nemo_en_titanet_small.onnx";
+const EMBEDDING_DIR: &str = "speaker-embedding";
+const EMBEDDING_FILE: &str = "nemo_en_titanet_small.onnx";
+
+/// A stretch of audio attributed to one speaker (ms, ms, speaker index)
+pub type SpeakerTurn = (i64, i64, usize);
+
+fn segmentation_model() -> Result<PathBuf, String> {
+    Ok(SherpaManager::get_models_dir(MODELS_ENGINE)?
+        .join(SEGMENTATION_DIR)
+        .join("model.onnx"))
+}
+
+fn embedding_model() -> Result<PathBuf, String> {
+    Ok(SherpaManager::get_models_dir(MODELS_ENGINE)?
+        .join(EMBEDDING_DIR)
+        .join(EMBEDDING_FILE))
+}
+
+/// Whether the diarization binary and both models are in place
+pub async fn is_available() -> bool {
+    let paths = [
+        SherpaManager::get_diarization_binary_path(),
+        segmentation_model(),
+        embedding_model(),
+    ];
+    paths.iter().all(|p| p.as_ref().is_ok_and(|p| p.exists()))
+}
+
+/// Download the segmentation and embedding models (and sherpa-onnx if missing)
+pub async fn install<F>(progress_callback: F, cancel_rx: watch::Receiver<bool>) -> Result<(), AppError>
+where
+    F: Fn(InstallProgress) + Send + Sync + Clone + 'static,
+{
+    if !SherpaManager::is_installed().await {
+        SherpaManager::install(Box::new(progress_callback.clone()), cancel_rx.clone()).await?;
+    }
+
+    SherpaManager::download_model(
+        MODELS_ENGINE,
+        SEGMENTATION_URL,
+        SEGMENTATION_DIR,
+        Box::new(progress_callback.clone()),
+        cancel_rx.clone(),
+    )
+    .await?;
+    SherpaManager::download_model(
+        MODELS_ENGINE,
+        EMBEDDING_URL,
+        EMBEDDING_DIR,
+        Box::new(progress_callback),
+        cancel_rx,
+    )
+    .await?;
+    Ok(())
+}
+
+/// Run sherpa-onnx speaker diarization on a 16kHz wav, stopping it if cancelled
+pub async fn speaker_turns(
+    audio_path: &Path,
+    mut cancel_rx: watch::Receiver<bool>,
+) -> Result<Vec<SpeakerTurn>, String> {
+    if *cancel_rx.borrow() {
+        return Err("Cancelled".to_string());
+    }
+
+    let binary = SherpaManager::get_diarization_binary_path()?;
+    if !binary.exists() {
+        return Err("Speaker diarization is not installed".to_string());
+    }
+    let (segmentation, embedding) = (segmentation_model()?, embedding_model()?);
+    if !segmentation.exists() || !embedding.exists() {
+        return Err("Speaker diarization models are not installed".to_string());
+    }
+
+    let mut cmd = Command::new(&binary);
+    cmd.args([
+        &format!("--segmentation.pyannote-model={}", segmentation.to_string_lossy()),
+        &format!("--embedding.model={}", embedding.to_string_lossy()),
+        "--clustering.cluster-threshold=0.5", // Speaker count is unknown up front
+        audio_path.to_str().unwrap_or(""),
+    ]);
+    cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).kill_on_drop(true);
+
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000);
+
+    log::info!("Running speaker diarization on {:?}", audio_path);
+
+    let child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to run speaker diarization: {}", e))?;
+    let output = child.wait_with_output();
+    tokio::pin!(output);
+
+    let mut cancel_open = true;
+    let output = loop {
+        tokio::select! {
+            output = &mut output => {
+                break output.map_err(|e| format!("Failed to run speaker diarization: {}", e))?;
+            }
+            changed = cancel_rx.changed(), if cancel_open => {
+                // A dropped sender can't cancel anymore; keep waiting on the process
+                if changed.is_err() {
+                    cancel_open = false;
+                } else if *cancel_rx.borrow() {
+                    // Returning drops the child, which kills it
+                    return Err("Cancelled".to_string());
+                }
+            }
+        }
+    };
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "Speaker diarization failed: {}",
+            stderr.lines().next().unwrap_or("unknown error")
+        ));
+    }
+
+    Ok(parse_turns(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse lines like "0.318 -- 6.865 speaker_00"
+fn parse_turns(output: &str) -> Vec<SpeakerTurn> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let start: f64 = parts.next()?.parse().ok()?;
+            if parts.next()? != "--" {
+                return None;
+            }
+            let end: f64 = parts.next()?.parse().ok()?;
+            let speaker: usize = parts.next()?.strip_prefix("speaker_")?.parse().ok()?;
+            Some(((start * 1000.0) as i64, (end * 1000.0) as i64, speaker))
+        })
+        .collect()
+}
+
+/// Prefix cues with "Speaker N:" where the speaker changes
+/// Each cue goes to the speaker it overlaps most; one speaker means no labels
+pub fn assign_speakers(segments: Vec<(i64, i64, String)>, turns: &[SpeakerTurn]) -> Vec<(i64, i64, String)> {
+    let speakers: Vec<Option<usize>> = segments
+        .iter()
+        .map(|(start, end, _)| {
+            turns
+                .iter()
+                .map(|&(t_start, t_end, speaker)| ((*end).min(t_end) - (*start).max(t_start), speaker))
+                .filter(|(overlap, _)| *overlap > 0)
+                .max_by_key(|(overlap, _)| *overlap)
+                .map(|(_, speaker)| speaker)
+        })
+        .collect();
+
+    // Number speakers in order of appearance so the first voice is always "Speaker 1"
+    let mut order: Vec<usize> = Vec::new();
+    for speaker in speakers.iter().flatten() {
+        if !order.contains(speaker) {
+            order.push(*speaker);
+        }
+    }
+    if order.len() < 2 {
+        return segments;
+    }
+
+    let mut previous = None;
+    segments
+        .into_iter()
+        .zip(speakers)
+        .map(|((start, end, text), speaker)| {
+            let text = match speaker {
+                Some(s) if previous != Some(s) => {
+                    let label = order.iter().position(|o| *o == s).unwrap_or(0) + 1;
+                    format!("Speaker {}: {}", label, text)
+                }
+                _ => text,
+            };
+            if speaker.is_some() {
+                previous = speaker;
+            }
+            (start, end, text)
+        })
+        .collect()
+}
+
+/// Label segments by speaker when diarization is enabled in the settings
+/// Best-effort: a missing install or failed run leaves the cues unlabeled; only cancelling fails
+pub async fn label_speakers(
+    audio_path: &Path,
+    segments: Vec<(i64, i64, String)>,
+    settings: &TranscriptionSettings,
+    cancel_rx: watch::Receiver<bool>,
+) -> Result<Vec<(i64, i64, String)>, String> {
+    if !settings.diarize {
+        return Ok(segments);
+    }
+
+    match speaker_turns(audio_path, cancel_rx).await {
+        Ok(turns) => Ok(assign_speakers(segments, &turns)),
+        Err(e) if e == "Cancelled" => Err(e),
+        Err(e) => {
+            log::warn!("{}; writing subtitles without speaker labels", e);
+            Ok(segments)
+        }
+    }
+}
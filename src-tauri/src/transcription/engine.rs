@@ -1,47 +1,69 @@
+use crate::config::AppConfig;
+use crate::error::AppError;
+use crate::media::{ffmpeg_command, ffprobe_command};
+use crate::sherpa_manager::SherpaManager;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
-use tokio::process::Command;
+use std::time::Duration;
 use tokio::sync::{mpsc, watch};
 
-/// Extract a segment of audio using ffmpeg
-/// Returns the path to the extracted segment (16kHz mono WAV)
-pub async fn extract_audio_segment(
+/// A job's own folder for audio chunks, `<base>/<prefix>_<uuid>`, so concurrent jobs
+/// never read each other's chunk files. Removed on drop, whichever way the job ends
+pub struct ChunkDir(PathBuf);
+
+impl ChunkDir {
+    pub async fn create(base: &Path, prefix: &str) -> Result<Self, String> {
+        let dir = base.join(format!("{}_{}", prefix, uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .map_err(|e| format!("Failed to create temp directory: {}", e))?;
+        Ok(Self(dir))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for ChunkDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Split audio into consecutive 16kHz mono WAV chunks in a single ffmpeg pass
+/// Returns the chunk paths in playback order
+pub async fn split_audio_segments(
     input_path: &Path,
-    output_path: &Path,
-    start_secs: f64,
-    duration_secs: f64,
-) -> Result<(), String> {
+    output_dir: &Path,
+    segment_secs: f64,
+) -> Result<Vec<PathBuf>, String> {
     let input_str = input_path
         .to_str()
         .ok_or("Invalid input path encoding")?;
-    let output_str = output_path
+    let pattern = output_dir.join("chunk_%05d.wav");
+    let pattern_str = pattern
         .to_str()
         .ok_or("Invalid output path encoding")?;
 
-    let mut cmd = Command::new(if cfg!(target_os = "windows") {
-        "ffmpeg.exe"
-    } else {
-        "ffmpeg"
-    });
+    let mut cmd = ffmpeg_command();
 
     cmd.args([
-        "-y",                           // Overwrite output
-        "-ss", &format!("{:.3}", start_secs), // Seek to start (before input for faster seeking)
-        "-i", input_str,                // Input file
-        "-t", &format!("{:.3}", duration_secs), // Duration
-        "-vn",                          // No video
-        "-acodec", "pcm_s16le",         // 16-bit PCM
-        "-ar", "16000",                 // 16kHz sample rate
-        "-ac", "1",                     // Mono
-        output_str,
+        "-y",
+        "-i", input_str,
+        "-vn",
+        "-acodec", "pcm_s16le",
+        "-ar", "16000",
+        "-ac", "1",
+        "-f", "segment",                // Write consecutive chunk files in one decode
+        "-segment_time", &format!("{:.3}", segment_secs),
+        "-reset_timestamps", "1",
+        pattern_str,
     ]);
 
     cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
 
-    #[cfg(target_os = "windows")]
-    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-
     let output = cmd
         .output()
         .await
@@ -49,12 +71,125 @@ pub async fn extract_audio_segment(
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("ffmpeg segment extraction failed: {}", stderr));
+        return Err(format!("ffmpeg segment split failed: {}", stderr));
     }
 
+    let mut entries = tokio::fs::read_dir(output_dir)
+        .await
+        .map_err(|e| format!("Failed to read chunk directory: {}", e))?;
+    let mut chunks = Vec::new();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with("chunk_") && name.ends_with(".wav") {
+            chunks.push(path);
+        }
+    }
+    // Zero-padded names sort in playback order
+    chunks.sort();
+
+    if chunks.is_empty() {
+        return Err("ffmpeg produced no audio chunks".to_string());
+    }
+
+    Ok(chunks)
+}
+
+/// Ask the server for a file's size with a HEAD request (follows redirects)
+/// Returns None when the size is not advertised
+pub async fn fetch_content_length(client: &reqwest::Client, url: &str) -> Option<u64> {
+    let response = crate::network::with_hf_auth(client.head(url), url)
+        .header("User-Agent", "Zinc-App")
+        .send()
+        .await
+        .ok()?;
+
+    if !response.status().is_success() {
+        return None;
+    }
+
+    // Read the header directly: HEAD responses have no body to size
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_LENGTH)?
+        .to_str()
+        .ok()?
+        .parse::<u64>()
+        .ok()
+        .filter(|len| *len > 0)
+}
+
+/// Check that every model file exists and is non-empty
+pub async fn validate_model_files(model: &str, files: &[PathBuf]) -> Result<(), String> {
+    for path in files {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+
+        match tokio::fs::metadata(path).await {
+            Ok(meta) if meta.len() > 0 => {}
+            Ok(_) => {
+                return Err(format!(
+                    "Model '{}' is corrupt ({} is empty). Please re-download the model.",
+                    model, name
+                ));
+            }
+            Err(_) => {
+                return Err(format!(
+                    "Model '{}' is corrupt or incomplete ({} is missing). Please re-download the model.",
+                    model, name
+                ));
+            }
+        }
+    }
     Ok(())
 }
 
+/// nvidia-smi result for the session; None until the first probe
+/// Held across the probe, so concurrent callers wait for one nvidia-smi instead of each starting their own
+static NVIDIA_GPU: tokio::sync::Mutex<Option<bool>> = tokio::sync::Mutex::const_new(None);
+
+/// A driver in a bad state can leave nvidia-smi hanging; it gets this long to answer
+const NVIDIA_SMI_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Check for an NVIDIA GPU by querying nvidia-smi
+/// Probed once per session and cached, since engine info asks on every fetch
+pub async fn check_nvidia_gpu() -> bool {
+    let mut cached = NVIDIA_GPU.lock().await;
+    match *cached {
+        Some(found) => found,
+        None => *cached.insert(probe_nvidia_smi().await),
+    }
+}
+
+/// Probe nvidia-smi again, replacing the cached result (e.g. after a driver install)
+/// A probe that times out counts as no GPU
+pub async fn refresh_nvidia_gpu() -> bool {
+    let mut cached = NVIDIA_GPU.lock().await;
+    *cached.insert(probe_nvidia_smi().await)
+}
+
+async fn probe_nvidia_smi() -> bool {
+    let mut cmd = tokio::process::Command::new("nvidia-smi");
+    cmd.args(["--query-gpu=name", "--format=csv,noheader"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true); // A timed-out probe is killed when its future is dropped
+
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000);
+
+    match tokio::time::timeout(NVIDIA_SMI_TIMEOUT, cmd.status()).await {
+        Ok(Ok(status)) => status.success(),
+        Ok(Err(_)) => false,
+        Err(_) => {
+            log::warn!("nvidia-smi did not answer within {:?}; assuming no NVIDIA GPU", NVIDIA_SMI_TIMEOUT);
+            false
+        }
+    }
+}
+
 /// Format seconds as SRT timestamp (HH:MM:SS,mmm)
 pub fn format_srt_time(seconds: f64) -> String {
     let hours = (seconds / 3600.0) as u32;
@@ -64,53 +199,331 @@ pub fn format_srt_time(seconds: f64) -> String {
     format!("{:02}:{:02}:{:02},{:03}", hours, minutes, secs, millis)
 }
 
-/// Generate SRT content from transcription text by splitting on sentence boundaries
-pub fn generate_srt_from_text(text: &str, duration_secs: f64) -> String {
+/// Generate SRT content from timestamped segments (start_ms, end_ms, text)
+pub fn generate_srt_from_segments(segments: Vec<(i64, i64, String)>) -> String {
+    let mut srt = String::new();
+
+    for (i, (start_ms, end_ms, text)) in segments.iter().enumerate() {
+        let start_secs = *start_ms as f64 / 1000.0;
+        let end_secs = *end_ms as f64 / 1000.0;
+
+        srt.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            format_srt_time(start_secs),
+            format_srt_time(end_secs),
+            text.trim()
+        ));
+    }
+
+    srt
+}
+
+/// LRC timestamp "[mm:ss.xx]" for a time in ms, rounded to the nearest hundredth
+/// Minutes keep counting past 59, as LRC has no hours field
+pub fn format_lrc_time(ms: i64) -> String {
+    let centis = (ms.max(0) + 5) / 10;
+    format!("[{:02}:{:02}.{:02}]", centis / 6000, (centis / 100) % 60, centis % 100)
+}
+
+/// Generate synchronized LRC lyrics from timestamped segments (start_ms, end_ms, text)
+/// LRC lines have no end time, so each line shows until the next one starts
+pub fn generate_lrc_from_segments(segments: Vec<(i64, i64, String)>) -> String {
+    let mut lrc = String::new();
+    for (start_ms, _, text) in segments {
+        let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !line.is_empty() {
+            lrc.push_str(&format!("{}{}\n", format_lrc_time(start_ms), line));
+        }
+    }
+    lrc
+}
+
+/// A cue read back from an SRT file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubtitleCue {
+    pub index: usize,
+    pub start: i64, // ms
+    pub end: i64,   // ms
+    pub text: String,
+}
+
+/// Parse "HH:MM:SS,mmm" (or "." before the millis) into ms
+fn parse_srt_time(time: &str) -> Option<i64> {
+    let (hms, millis) = time.trim().split_once([',', '.'])?;
+    let mut parts = hms.split(':').map(|p| p.parse::<i64>().ok());
+    let (h, m, s) = (parts.next()??, parts.next()??, parts.next()??);
+    Some(((h * 60 + m) * 60 + s) * 1000 + millis.parse::<i64>().ok()?)
+}
+
+/// Parse SRT content into cues; malformed blocks are skipped
+/// Inverse of generate_srt_from_segments
+pub fn parse_srt(content: &str) -> Vec<SubtitleCue> {
+    let content = content.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+    content
+        .split("\n\n")
+        .filter_map(|block| {
+            let mut lines = block.trim().lines();
+            let index = lines.next()?.trim().parse().ok()?;
+            let (start, end) = lines.next()?.split_once("-->")?;
+            // Position settings may follow the end time
+            let end = end.split_whitespace().next()?;
+            let text = lines.collect::<Vec<_>>().join("\n");
+            Some(SubtitleCue {
+                index,
+                start: parse_srt_time(start)?,
+                end: parse_srt_time(end)?,
+                text,
+            })
+        })
+        .collect()
+}
+
+/// Split transcription text into evenly timed sentence segments (start_ms, end_ms, text)
+/// Used by engines that only return plain text
+pub fn segments_from_text(text: &str, duration_secs: f64) -> Vec<(i64, i64, String)> {
     let text = text.trim();
     if text.is_empty() {
-        return String::new();
+        return Vec::new();
     }
 
+    let duration_ms = (duration_secs * 1000.0) as i64;
+
     // Split into sentences
     let sentences: Vec<&str> = text
-        .split(|c| c == '.' || c == '!' || c == '?')
+        .split(['.', '!', '?'])
         .map(|s| s.trim())
         .filter(|s| !s.is_empty())
         .collect();
 
     if sentences.is_empty() {
-        let end_time = format_srt_time(duration_secs);
-        return format!("1\n00:00:00,000 --> {}\n{}\n\n", end_time, text);
+        return vec![(0, duration_ms, text.to_string())];
     }
 
-    let time_per_sentence = duration_secs / sentences.len() as f64;
-    let mut srt = String::new();
+    let ms_per_sentence = duration_ms as f64 / sentences.len() as f64;
+
+    sentences
+        .iter()
+        .enumerate()
+        .map(|(i, sentence)| {
+            let start_ms = (i as f64 * ms_per_sentence) as i64;
+            let end_ms = ((i + 1) as f64 * ms_per_sentence) as i64;
+            (start_ms, end_ms, format!("{}.", sentence))
+        })
+        .collect()
+}
 
-    for (i, sentence) in sentences.iter().enumerate() {
-        let start_time = i as f64 * time_per_sentence;
-        let end_time = (i + 1) as f64 * time_per_sentence;
+/// Cues shorter than this are merged into a neighbour when limits are configured
+const MIN_CUE_MS: i64 = 1000;
+/// Merge ceilings used when only one of the limits is configured
+const DEFAULT_MERGE_MAX_CHARS: usize = 42;
+const DEFAULT_MERGE_MAX_MS: i64 = 7000;
+
+/// The config values a transcription job reads, taken from the caller's AppConfig when the
+/// job is queued; nothing below the manager goes back to config.json
+#[derive(Debug, Clone)]
+pub struct TranscriptionSettings {
+    pub cue_limits: CueLimits,
+    pub subtitle_offset_ms: i64,
+    pub min_confidence: Option<f32>,
+    pub tighten_cues: bool,
+    pub diarize: bool,
+    pub normalize_audio: bool,
+    pub fallback_models: Vec<String>,
+    pub max_transcription_minutes: Option<u32>,
+    pub sherpa_args: Vec<String>, // Zipformer decoding flags
+}
 
-        srt.push_str(&format!(
-            "{}\n{} --> {}\n{}.\n\n",
-            i + 1,
-            format_srt_time(start_time),
-            format_srt_time(end_time),
-            sentence
-        ));
+impl From<&AppConfig> for TranscriptionSettings {
+    fn from(config: &AppConfig) -> Self {
+        Self {
+            cue_limits: CueLimits {
+                max_chars: config.max_cue_chars,
+                max_duration_ms: config.max_cue_duration_secs.map(|s| (s * 1000.0) as i64),
+            },
+            subtitle_offset_ms: config.subtitle_offset_ms,
+            min_confidence: config.min_confidence,
+            tighten_cues: config.tighten_cues,
+            diarize: config.diarize,
+            normalize_audio: config.normalize_audio,
+            fallback_models: config.fallback_models.clone(),
+            max_transcription_minutes: config.max_transcription_minutes,
+            sherpa_args: SherpaManager::decoding_args(config),
+        }
     }
+}
 
-    srt
+/// The app's default settings, for callers without a config
+impl Default for TranscriptionSettings {
+    fn default() -> Self {
+        Self::from(&AppConfig::default())
+    }
+}
+
+/// Reading-speed limits for generated cues, from the app config
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CueLimits {
+    pub max_chars: Option<usize>,
+    pub max_duration_ms: Option<i64>,
+}
+
+impl CueLimits {
+    fn is_unlimited(&self) -> bool {
+        self.max_chars.is_none() && self.max_duration_ms.is_none()
+    }
+}
+
+/// Clean up an engine's segments before SRT writing: drop stutters, then apply
+/// the configured cue limits. "word" style skips the limits so karaoke timing survives.
+/// The configured subtitle_offset_ms is applied last, in both styles
+pub fn postprocess_segments(
+    segments: Vec<(i64, i64, String)>,
+    style: &str,
+    settings: &TranscriptionSettings,
+) -> Vec<(i64, i64, String)> {
+    let segments = dedupe_and_merge_cues(segments);
+    let segments = if style == "word" {
+        segments
+    } else {
+        reflow_segments(segments, settings.cue_limits)
+    };
+    shift_segments(segments, settings.subtitle_offset_ms)
+}
+
+/// Move every cue by `offset_ms` (negative is earlier), clamping at zero
+/// Cues pushed entirely before zero are dropped
+pub fn shift_segments(segments: Vec<(i64, i64, String)>, offset_ms: i64) -> Vec<(i64, i64, String)> {
+    if offset_ms == 0 {
+        return segments;
+    }
+    segments
+        .into_iter()
+        .map(|(start, end, text)| ((start + offset_ms).max(0), (end + offset_ms).max(0), text))
+        .filter(|(start, end, _)| end > start)
+        .collect()
+}
+
+/// Gap under which two cues with the same text count as one repeated cue
+const DUPLICATE_CUE_GAP_MS: i64 = 500;
+
+/// Merge adjacent cues with identical text (chunk-boundary repeats) and clamp
+/// cues that run past the start of the next one
+pub fn dedupe_and_merge_cues(mut segments: Vec<(i64, i64, String)>) -> Vec<(i64, i64, String)> {
+    segments.sort_by_key(|(start, _, _)| *start);
+
+    let mut cues: Vec<(i64, i64, String)> = Vec::with_capacity(segments.len());
+    for (start_ms, end_ms, text) in segments {
+        if let Some((last_start, last_end, last_text)) = cues.last_mut() {
+            let same_text = last_text.trim().eq_ignore_ascii_case(text.trim());
+            if same_text && start_ms - *last_end <= DUPLICATE_CUE_GAP_MS {
+                *last_end = (*last_end).max(end_ms);
+                continue;
+            }
+
+            // Overlapping timestamps make players show both cues at once
+            if *last_end > start_ms {
+                *last_end = start_ms.max(*last_start);
+            }
+        }
+        cues.push((start_ms, end_ms, text));
+    }
+
+    cues
+}
+
+/// Split cues that exceed the limits on word boundaries, then merge sub-second
+/// fragments into their neighbours where the result still fits
+pub fn reflow_segments(segments: Vec<(i64, i64, String)>, limits: CueLimits) -> Vec<(i64, i64, String)> {
+    if limits.is_unlimited() {
+        return segments;
+    }
+
+    let split: Vec<(i64, i64, String)> = segments
+        .into_iter()
+        .flat_map(|segment| split_segment(segment, limits))
+        .collect();
+
+    let merge_max_chars = limits.max_chars.unwrap_or(DEFAULT_MERGE_MAX_CHARS);
+    let merge_max_ms = limits.max_duration_ms.unwrap_or(DEFAULT_MERGE_MAX_MS);
+
+    let mut merged: Vec<(i64, i64, String)> = Vec::with_capacity(split.len());
+    for (start_ms, end_ms, text) in split {
+        if let Some((last_start, last_end, last_text)) = merged.last_mut() {
+            let is_short = end_ms - start_ms < MIN_CUE_MS || *last_end - *last_start < MIN_CUE_MS;
+            let combined_chars = last_text.chars().count() + 1 + text.chars().count();
+            let fits = combined_chars <= merge_max_chars && end_ms - *last_start <= merge_max_ms;
+            // Don't bridge a real pause between lines
+            let is_adjacent = start_ms - *last_end < MIN_CUE_MS;
+
+            if is_short && fits && is_adjacent {
+                last_text.push(' ');
+                last_text.push_str(&text);
+                *last_end = end_ms;
+                continue;
+            }
+        }
+        merged.push((start_ms, end_ms, text));
+    }
+
+    merged
+}
+
+/// Break one cue into word-wrapped pieces, sharing its time span by character count
+fn split_segment((start_ms, end_ms, text): (i64, i64, String), limits: CueLimits) -> Vec<(i64, i64, String)> {
+    let text = text.trim().to_string();
+    let total_chars = text.chars().count();
+    let duration_ms = (end_ms - start_ms).max(0);
+
+    // Enough pieces to satisfy the duration limit, each no wider than the char limit
+    let pieces_for_duration = match limits.max_duration_ms {
+        Some(max) if max > 0 => ((duration_ms + max - 1) / max).max(1) as usize,
+        _ => 1,
+    };
+    let target_chars = total_chars.div_ceil(pieces_for_duration);
+    let target_chars = limits.max_chars.map_or(target_chars, |max| target_chars.min(max)).max(1);
+
+    if total_chars <= target_chars {
+        return vec![(start_ms, end_ms, text)];
+    }
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > target_chars {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+
+    let line_chars: usize = lines.iter().map(|l| l.chars().count()).sum::<usize>().max(1);
+    let mut pieces = Vec::with_capacity(lines.len());
+    let mut consumed = 0;
+    for (i, line) in lines.iter().enumerate() {
+        let piece_start = start_ms + duration_ms * consumed as i64 / line_chars as i64;
+        consumed += line.chars().count();
+        // The last piece ends exactly where the original cue did
+        let piece_end = if i == lines.len() - 1 {
+            end_ms
+        } else {
+            start_ms + duration_ms * consumed as i64 / line_chars as i64
+        };
+        pieces.push((piece_start, piece_end, line.clone()));
+    }
+
+    pieces
 }
 
 /// Get audio duration using ffprobe
 pub async fn get_audio_duration(audio_path: &Path) -> Option<f64> {
     let audio_str = audio_path.to_str()?;
 
-    let mut cmd = Command::new(if cfg!(target_os = "windows") {
-        "ffprobe.exe"
-    } else {
-        "ffprobe"
-    });
+    let mut cmd = ffprobe_command();
 
     cmd.args([
         "-v", "error",
@@ -121,9 +534,6 @@ pub async fn get_audio_duration(audio_path: &Path) -> Option<f64> {
 
     cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
 
-    #[cfg(target_os = "windows")]
-    cmd.creation_flags(0x08000000);
-
     let output = cmd.output().await.ok()?;
     let duration_str = String::from_utf8_lossy(&output.stdout);
     duration_str.trim().parse().ok()
@@ -197,6 +607,9 @@ pub struct EngineInfo {
     pub gpu_available: bool,
     pub languages: Vec<String>,
     pub models: Vec<TranscriptionModel>,
+    pub supports_word_timestamps: bool, // "word" style gives real per-word timing
+    pub supports_translation: bool,     // Models can translate speech to English
+    pub multilingual: bool,             // More than one supported language
 }
 
 /// Download progress for engines/models
@@ -239,6 +652,17 @@ pub trait TranscriptionEngine: Send + Sync {
     /// Get supported language codes
     fn supported_languages(&self) -> Vec<&'static str>;
 
+    /// Whether the engine produces per-word timing for the "word" style
+    /// Engines without it fall back to sentence cues
+    fn supports_word_timestamps(&self) -> bool {
+        false
+    }
+
+    /// Whether the engine's models can translate speech to English
+    fn supports_translation(&self) -> bool {
+        false
+    }
+
     /// Get full engine info
     async fn get_info(&self) -> EngineInfo {
         let is_available = self.is_available().await.unwrap_or(false);
@@ -261,14 +685,28 @@ pub trait TranscriptionEngine: Send + Sync {
             gpu_available,
             languages: self.supported_languages().iter().map(|s| s.to_string()).collect(),
             models: self.available_models().await,
+            supports_word_timestamps: self.supports_word_timestamps(),
+            supports_translation: self.supports_translation(),
+            multilingual: self.supported_languages().len() > 1,
         }
     }
 
     /// Install the engine (download binary/runtime)
-    async fn install(&self, progress_callback: Box<dyn Fn(InstallProgress) + Send + 'static>) -> Result<(), String>;
+    /// Stops and cleans up partial files when cancel_rx becomes true
+    async fn install(
+        &self,
+        progress_callback: Box<dyn Fn(InstallProgress) + Send + 'static>,
+        cancel_rx: watch::Receiver<bool>,
+    ) -> Result<(), AppError>;
 
     /// Download a model for this engine
-    async fn download_model(&self, model: &str, progress_callback: Box<dyn Fn(InstallProgress) + Send + 'static>) -> Result<(), String>;
+    /// Stops and cleans up partial files when cancel_rx becomes true
+    async fn download_model(
+        &self,
+        model: &str,
+        progress_callback: Box<dyn Fn(InstallProgress) + Send + 'static>,
+        cancel_rx: watch::Receiver<bool>,
+    ) -> Result<(), AppError>;
 
     /// Transcribe audio file to SRT
     /// style: "word" for one word per subtitle (karaoke-style), "sentence" for natural phrase groupings
@@ -281,4 +719,166 @@ pub trait TranscriptionEngine: Send + Sync {
         progress_tx: mpsc::Sender<TranscribeProgress>,
         cancel_rx: watch::Receiver<bool>,
     ) -> Result<PathBuf, String>;
+
+    /// Check that all files for a model are present and intact
+    async fn validate_model(&self, model: &str) -> Result<(), String>;
+
+    /// Files and directories a model occupies on disk, whether or not they exist
+    /// repair_model deletes these before downloading again
+    fn model_storage(&self, model: &str) -> Vec<PathBuf>;
+
+    /// Release any cached models or GPU memory held by the engine
+    /// Called when no transcriptions are running; default is a no-op
+    async fn release(&self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lrc_time_rounds_to_the_nearest_hundredth() {
+        assert_eq!(format_lrc_time(0), "[00:00.00]");
+        assert_eq!(format_lrc_time(1234), "[00:01.23]");
+        assert_eq!(format_lrc_time(1235), "[00:01.24]");
+        assert_eq!(format_lrc_time(999), "[00:01.00]");
+    }
+
+    #[test]
+    fn lrc_time_rounding_carries_into_minutes() {
+        assert_eq!(format_lrc_time(59_995), "[01:00.00]");
+        assert_eq!(format_lrc_time(59_994), "[00:59.99]");
+    }
+
+    #[test]
+    fn lrc_time_keeps_counting_minutes_past_an_hour() {
+        assert_eq!(format_lrc_time(3_725_500), "[62:05.50]");
+    }
+
+    #[test]
+    fn lrc_time_clamps_negative_times() {
+        assert_eq!(format_lrc_time(-40), "[00:00.00]");
+    }
+
+    #[test]
+    fn long_line_is_split_on_word_boundaries() {
+        let text = (0..29).map(|i| format!("word{:02}", i)).collect::<Vec<_>>().join(" ");
+        assert_eq!(text.chars().count(), 202);
+        let limits = CueLimits { max_chars: Some(42), max_duration_ms: None };
+
+        let cues = reflow_segments(vec![(0, 10_000, text.clone())], limits);
+
+        assert!(cues.len() > 1);
+        assert!(cues.iter().all(|(_, _, line)| line.chars().count() <= 42));
+        let rejoined = cues.iter().map(|(_, _, line)| line.as_str()).collect::<Vec<_>>().join(" ");
+        assert_eq!(rejoined, text);
+        // The pieces share the original span without gaps or overlap
+        assert_eq!(cues.first().unwrap().0, 0);
+        assert_eq!(cues.last().unwrap().1, 10_000);
+        assert!(cues.windows(2).all(|pair| pair[0].1 == pair[1].0));
+    }
+
+    #[test]
+    fn chunk_boundary_repeat_is_merged() {
+        // whisper_rs chunks share CHUNK_OVERLAP_SECS, so the sentence in the overlap
+        // comes back from both chunks with slightly different timing and case
+        let segments = vec![
+            (295_000, 297_400, "so let's get started.".to_string()),
+            (297_500, 299_800, "and that's the plan.".to_string()),
+            (298_100, 299_900, "And that's the plan.".to_string()),
+            (299_700, 302_000, "First, the setup.".to_string()),
+        ];
+
+        let cues = dedupe_and_merge_cues(segments);
+
+        assert_eq!(
+            cues,
+            vec![
+                (295_000, 297_400, "so let's get started.".to_string()),
+                (297_500, 299_700, "and that's the plan.".to_string()),
+                (299_700, 302_000, "First, the setup.".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn repeated_text_after_a_pause_is_kept() {
+        let segments = vec![
+            (0, 1000, "Yes.".to_string()),
+            (5000, 6000, "Yes.".to_string()),
+        ];
+        assert_eq!(dedupe_and_merge_cues(segments.clone()), segments);
+    }
+
+    #[test]
+    fn negative_offset_clamps_the_first_cue_at_zero() {
+        let segments = vec![
+            (200, 1500, "first".to_string()),
+            (2000, 3000, "second".to_string()),
+        ];
+        assert_eq!(
+            shift_segments(segments, -500),
+            vec![(0, 1000, "first".to_string()), (1500, 2500, "second".to_string())]
+        );
+    }
+
+    #[test]
+    fn negative_offset_drops_cues_pushed_before_zero() {
+        let segments = vec![
+            (0, 400, "gone".to_string()),
+            (300, 800, "clipped".to_string()),
+            (1000, 2000, "kept".to_string()),
+        ];
+        assert_eq!(
+            shift_segments(segments, -500),
+            vec![(0, 300, "clipped".to_string()), (500, 1500, "kept".to_string())]
+        );
+    }
+
+    #[test]
+    fn positive_offset_moves_everything_later() {
+        let segments = vec![(0, 1000, "a".to_string())];
+        assert_eq!(shift_segments(segments, 250), vec![(250, 1250, "a".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn chunk_dirs_are_per_job_and_removed_on_drop() {
+        let base = std::env::temp_dir();
+        let first = ChunkDir::create(&base, "zinc_test_chunks").await.unwrap();
+        let second = ChunkDir::create(&base, "zinc_test_chunks").await.unwrap();
+        assert_ne!(first.path(), second.path());
+
+        std::fs::write(first.path().join("chunk_00000.wav"), b"").unwrap();
+        let path = first.path().to_path_buf();
+        drop(first);
+        assert!(!path.exists());
+        assert!(second.path().is_dir());
+    }
+
+    #[test]
+    fn postprocess_uses_the_settings_it_is_given() {
+        let settings = TranscriptionSettings {
+            cue_limits: CueLimits { max_chars: Some(10), max_duration_ms: None },
+            subtitle_offset_ms: 1000,
+            ..TranscriptionSettings::default()
+        };
+        let segments = vec![(0, 4000, "one two three four".to_string())];
+
+        let cues = postprocess_segments(segments.clone(), "sentence", &settings);
+        assert!(cues.len() > 1, "{:?}", cues);
+        assert_eq!(cues[0].0, 1000);
+
+        // Word style keeps its timing apart from the offset
+        assert_eq!(
+            postprocess_segments(segments, "word", &settings),
+            vec![(1000, 5000, "one two three four".to_string())]
+        );
+    }
+
+    #[test]
+    fn unlimited_reflow_leaves_cues_alone() {
+        let text = "x ".repeat(100);
+        let cues = reflow_segments(vec![(0, 1000, text.clone())], CueLimits::default());
+        assert_eq!(cues, vec![(0, 1000, text)]);
+    }
 }
@@ -0,0 +1,149 @@
+use super::SubtitleCue;
+use serde::{Deserialize, Serialize};
+
+/// Lines break once they would exceed this many characters
+const MAX_LINE_CHARS: usize = 32;
+/// A pause longer than this between words starts a new line
+const MAX_LINE_GAP_MS: i64 = 1000;
+/// Script height; font sizes are given at this resolution and libass scales them
+const PLAY_RES_Y: u32 = 1080;
+
+/// Look of burned-in karaoke subtitles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KaraokeStyle {
+    pub font: String,
+    pub font_size: u32,          // At 1080 lines; scaled with the video
+    pub primary_color: String,   // "#RRGGBB" for words not yet spoken
+    pub highlight_color: String, // "#RRGGBB" for the current and already spoken words
+}
+
+impl Default for KaraokeStyle {
+    fn default() -> Self {
+        Self {
+            font: "Arial".to_string(),
+            font_size: 64,
+            primary_color: "#FFFFFF".to_string(),
+            highlight_color: "#FFD700".to_string(),
+        }
+    }
+}
+
+impl KaraokeStyle {
+    pub fn validate(&self) -> Result<(), String> {
+        let font = self.font.trim();
+        if font.is_empty() || font.contains(',') {
+            return Err("Karaoke font must be a font name without commas".to_string());
+        }
+        if !(8..=300).contains(&self.font_size) {
+            return Err("Karaoke font size must be between 8 and 300".to_string());
+        }
+        for color in [&self.primary_color, &self.highlight_color] {
+            if ass_color(color).is_none() {
+                return Err(format!("Invalid karaoke color '{}'. Use #RRGGBB", color));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// "#RRGGBB" as ASS's "&H00BBGGRR"
+fn ass_color(hex: &str) -> Option<String> {
+    let hex = hex.trim().strip_prefix('#')?;
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(format!("&H00{}{}{}", &hex[4..6], &hex[2..4], &hex[0..2]).to_uppercase())
+}
+
+/// ms as ASS's "H:MM:SS.cc"
+fn ass_time(ms: i64) -> String {
+    let cs = ms.max(0) / 10;
+    format!("{}:{:02}:{:02}.{:02}", cs / 360_000, cs / 6000 % 60, cs / 100 % 60, cs % 100)
+}
+
+/// Word cues grouped into display lines, broken at pauses, sentence ends and MAX_LINE_CHARS
+fn group_lines(words: &[SubtitleCue]) -> Vec<&[SubtitleCue]> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    let mut chars = 0;
+    for (i, word) in words.iter().enumerate() {
+        let len = word.text.trim().chars().count();
+        if i > start {
+            let previous = &words[i - 1];
+            if word.start - previous.end > MAX_LINE_GAP_MS
+                || previous.text.trim_end().ends_with(['.', '!', '?'])
+                || chars + len > MAX_LINE_CHARS
+            {
+                lines.push(&words[start..i]);
+                start = i;
+                chars = 0;
+            }
+        }
+        chars += len + 1;
+    }
+    if start < words.len() {
+        lines.push(&words[start..]);
+    }
+    lines
+}
+
+/// ASS script for word-level cues: a dialogue line per group of words, each word
+/// carrying a \k tag so it switches to the highlight color as it is spoken
+/// `video_size` sets the script's aspect ratio; 16:9 when unknown
+pub fn generate_karaoke_ass(words: &[SubtitleCue], style: &KaraokeStyle, video_size: Option<(u32, u32)>) -> String {
+    let (width, height) = video_size.filter(|(w, h)| *w > 0 && *h > 0).unwrap_or((1920, 1080));
+    let play_res_x = (PLAY_RES_Y as u64 * width as u64 / height as u64).max(1);
+    // \k fills from SecondaryColour to PrimaryColour, so the highlight is ASS's primary
+    let highlight = ass_color(&style.highlight_color).unwrap_or_else(|| "&H0000D7FF".to_string());
+    let base = ass_color(&style.primary_color).unwrap_or_else(|| "&H00FFFFFF".to_string());
+
+    let mut ass = format!(
+        "[Script Info]\n\
+         ScriptType: v4.00+\n\
+         PlayResX: {}\n\
+         PlayResY: {}\n\
+         WrapStyle: 2\n\
+         ScaledBorderAndShadow: yes\n\
+         \n\
+         [V4+ Styles]\n\
+         Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, \
+         Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, \
+         Shadow, Alignment, MarginL, MarginR, MarginV, Encoding\n\
+         Style: Karaoke,{},{},{},{},&H00000000,&H80000000,-1,0,0,0,100,100,0,0,1,3,1,2,60,60,80,1\n\
+         \n\
+         [Events]\n\
+         Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n",
+        play_res_x,
+        PLAY_RES_Y,
+        style.font.trim(),
+        style.font_size,
+        highlight,
+        base
+    );
+
+    for line in group_lines(words) {
+        let (Some(first), Some(last)) = (line.first(), line.last()) else {
+            continue;
+        };
+        let mut text = String::new();
+        for (i, word) in line.iter().enumerate() {
+            // A word stays current until the next one starts, so the sweep has no gaps
+            let until = line.get(i + 1).map_or(word.end, |next| next.start);
+            let centis = ((until - word.start) / 10).max(1);
+            // Braces and backslashes would be read as override tags
+            let clean: String = word.text.trim().chars().filter(|c| !matches!(c, '{' | '}' | '\\')).collect();
+            if i > 0 {
+                text.push(' ');
+            }
+            text.push_str(&format!("{{\\k{}}}{}", centis, clean));
+        }
+        ass.push_str(&format!(
+            "Dialogue: 0,{},{},Karaoke,,0,0,0,,{}\n",
+            ass_time(first.start),
+            ass_time(last.end.max(first.start)),
+            text
+        ));
+    }
+    ass
+}
@@ -1,10 +1,16 @@
+mod canary;
+pub mod diarization;
 mod engine;
+pub mod karaoke;
 mod moonshine;
 mod whisper_rs_engine;
+mod zipformer;
 
+pub use canary::CanaryEngine;
 pub use engine::*;
 pub use moonshine::MoonshineEngine;
 pub use whisper_rs_engine::WhisperRsEngine;
+pub use zipformer::ZipformerEngine;
 
 use std::path::Path;
 use std::sync::Arc;
@@ -16,11 +22,14 @@ pub struct TranscriptionDispatcher {
 }
 
 impl TranscriptionDispatcher {
-    pub fn new() -> Self {
+    /// Engines that transcribe with `settings`
+    pub fn new(settings: &TranscriptionSettings) -> Self {
         Self {
             engines: vec![
-                Arc::new(WhisperRsEngine::new()),  // Primary GPU engine
-                Arc::new(MoonshineEngine::new()),  // CPU fallback
+                Arc::new(WhisperRsEngine::new(settings.clone())),  // Primary GPU engine
+                Arc::new(MoonshineEngine::new(settings.clone())),  // CPU fallback
+                Arc::new(ZipformerEngine::new(settings.clone())),  // Low-memory CPU engine for long files
+                Arc::new(CanaryEngine::new(settings.clone())),     // Slow but most accurate multilingual engine
             ],
         }
     }
@@ -36,13 +45,9 @@ impl TranscriptionDispatcher {
         self.engines.iter().find(|e| e.id() == id).cloned()
     }
 
-    /// Get engine info for all engines
+    /// Get engine info for all engines, fetched concurrently but returned in registration order
     pub async fn get_engine_infos(&self) -> Vec<EngineInfo> {
-        let mut infos = Vec::new();
-        for engine in &self.engines {
-            infos.push(engine.get_info().await);
-        }
-        infos
+        futures_util::future::join_all(self.engines.iter().map(|engine| engine.get_info())).await
     }
 
     /// Transcribe using the specified engine
@@ -66,6 +71,6 @@ impl TranscriptionDispatcher {
 
 impl Default for TranscriptionDispatcher {
     fn default() -> Self {
-        Self::new()
+        Self::new(&TranscriptionSettings::default())
     }
 }
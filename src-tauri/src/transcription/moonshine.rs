@@ -1,7 +1,10 @@
 use super::{
-    generate_srt_from_text, get_audio_duration, parse_json_text_field,
-    InstallProgress, TranscribeProgress, TranscriptionEngine, TranscriptionModel,
+    check_nvidia_gpu, generate_srt_from_segments, get_audio_duration, parse_json_text_field,
+    postprocess_segments, segments_from_text, split_audio_segments, ChunkDir, validate_model_files, InstallProgress, TranscribeProgress, TranscriptionEngine, TranscriptionModel,
+    TranscriptionSettings,
 };
+use super::diarization::label_speakers;
+use crate::error::AppError;
 use crate::sherpa_manager::SherpaManager;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
@@ -15,11 +18,13 @@ const MOONSHINE_BASE_URL: &str = "https://github.com/k2-fsa/sherpa-onnx/releases
 
 /// Moonshine transcription engine using sherpa-onnx CLI
 /// Fast, edge-optimized engine using ONNX Runtime
-pub struct MoonshineEngine;
+pub struct MoonshineEngine {
+    settings: TranscriptionSettings,
+}
 
 impl MoonshineEngine {
-    pub fn new() -> Self {
-        Self
+    pub fn new(settings: TranscriptionSettings) -> Self {
+        Self { settings }
     }
 
     /// Get the model directory name for a model ID
@@ -73,11 +78,21 @@ impl MoonshineEngine {
             model_dir.join("tokens.txt"),
         ))
     }
+
+    /// Pick the sherpa-onnx execution provider: CUDA when the installed build
+    /// ships it and an NVIDIA GPU is present, CPU otherwise
+    async fn get_provider() -> &'static str {
+        if SherpaManager::has_cuda_provider() && check_nvidia_gpu().await {
+            "cuda"
+        } else {
+            "cpu"
+        }
+    }
 }
 
 impl Default for MoonshineEngine {
     fn default() -> Self {
-        Self::new()
+        Self::new(TranscriptionSettings::default())
     }
 }
 
@@ -100,7 +115,7 @@ impl TranscriptionEngine for MoonshineEngine {
     }
 
     async fn check_gpu_available(&self) -> bool {
-        true // Moonshine works on CPU and GPU
+        Self::get_provider().await == "cuda"
     }
 
     async fn is_available(&self) -> Result<bool, String> {
@@ -111,31 +126,39 @@ impl TranscriptionEngine for MoonshineEngine {
     }
 
     async fn available_models(&self) -> Vec<TranscriptionModel> {
+        let (tiny_gpu, tiny_cpu) = self.speed_multiplier("tiny");
+        let (base_gpu, base_cpu) = self.speed_multiplier("base");
         vec![
             TranscriptionModel {
                 id: "tiny".to_string(),
                 name: "Tiny (int8)".to_string(),
                 size: "35 MB".to_string(),
                 installed: Self::is_model_installed("tiny"),
-                speed_gpu: 50.0,
-                speed_cpu: 15.0,
+                speed_gpu: tiny_gpu,
+                speed_cpu: tiny_cpu,
             },
             TranscriptionModel {
                 id: "base".to_string(),
                 name: "Base (int8)".to_string(),
                 size: "70 MB".to_string(),
                 installed: Self::is_model_installed("base"),
-                speed_gpu: 30.0,
-                speed_cpu: 10.0,
+                speed_gpu: base_gpu,
+                speed_cpu: base_cpu,
             },
         ]
     }
 
     fn speed_multiplier(&self, model: &str) -> (f64, f64) {
-        match model {
+        let (gpu, cpu) = match model {
             "tiny" => (50.0, 15.0),
             "base" => (30.0, 10.0),
             _ => (30.0, 10.0),
+        };
+        // Without the CUDA provider sherpa runs on CPU, so the GPU figure would be misleading
+        if SherpaManager::has_cuda_provider() {
+            (gpu, cpu)
+        } else {
+            (cpu, cpu)
         }
     }
 
@@ -147,10 +170,11 @@ impl TranscriptionEngine for MoonshineEngine {
     async fn install(
         &self,
         progress_callback: Box<dyn Fn(InstallProgress) + Send + 'static>,
-    ) -> Result<(), String> {
+        cancel_rx: watch::Receiver<bool>,
+    ) -> Result<(), AppError> {
         // Install sherpa-onnx runtime
         if !SherpaManager::is_installed().await {
-            SherpaManager::install(progress_callback).await?;
+            SherpaManager::install(progress_callback, cancel_rx).await?;
         }
         Ok(())
     }
@@ -159,7 +183,8 @@ impl TranscriptionEngine for MoonshineEngine {
         &self,
         model: &str,
         progress_callback: Box<dyn Fn(InstallProgress) + Send + 'static>,
-    ) -> Result<(), String> {
+        cancel_rx: watch::Receiver<bool>,
+    ) -> Result<(), AppError> {
         // Auto-install sherpa-onnx if not installed
         if !SherpaManager::is_installed().await {
             log::info!("sherpa-onnx not installed, installing automatically...");
@@ -167,17 +192,31 @@ impl TranscriptionEngine for MoonshineEngine {
             // The main download will still show progress
             SherpaManager::install(Box::new(move |progress| {
                 log::info!("Installing sherpa-onnx: {}% - {}", progress.percentage as i32, progress.stage);
-            })).await?;
+            }), cancel_rx.clone()).await?;
         }
 
         let url = Self::get_model_url(model);
         let model_dir_name = Self::get_model_dir_name(model);
 
-        SherpaManager::download_model("moonshine", url, model_dir_name, progress_callback).await?;
+        SherpaManager::download_model("moonshine", url, model_dir_name, progress_callback, cancel_rx).await?;
 
         Ok(())
     }
 
+    fn model_storage(&self, model: &str) -> Vec<PathBuf> {
+        SherpaManager::model_storage("moonshine", Self::get_model_dir_name(model))
+    }
+
+    async fn validate_model(&self, model: &str) -> Result<(), String> {
+        let (preprocessor, encoder, uncached_decoder, cached_decoder, tokens) =
+            Self::get_model_paths(model)?;
+        validate_model_files(
+            model,
+            &[preprocessor, encoder, uncached_decoder, cached_decoder, tokens],
+        )
+        .await
+    }
+
     async fn transcribe(
         &self,
         audio_path: &Path,
@@ -228,6 +267,20 @@ impl TranscriptionEngine for MoonshineEngine {
         // Generate output SRT path
         let srt_path = audio_path.with_extension("srt");
 
+        let provider = Self::get_provider().await;
+        log::info!("Moonshine using sherpa-onnx provider: {}", provider);
+
+        // The same model files and provider go to every chunk
+        let model_args = [
+            format!("--moonshine-preprocessor={}", preprocessor.to_string_lossy()),
+            format!("--moonshine-encoder={}", encoder.to_string_lossy()),
+            format!("--moonshine-uncached-decoder={}", uncached_decoder.to_string_lossy()),
+            format!("--moonshine-cached-decoder={}", cached_decoder.to_string_lossy()),
+            format!("--tokens={}", tokens.to_string_lossy()),
+            format!("--provider={}", provider),
+            "--num-threads=4".to_string(),
+        ];
+
         // Get audio duration to determine if we need chunking
         let duration = get_audio_duration(audio_path).await.unwrap_or(60.0);
 
@@ -239,11 +292,8 @@ impl TranscriptionEngine for MoonshineEngine {
             Self::transcribe_chunked(
                 audio_path,
                 &sherpa_binary,
-                &preprocessor,
-                &encoder,
-                &uncached_decoder,
-                &cached_decoder,
-                &tokens,
+                &model_args,
+                provider,
                 duration,
                 CHUNK_DURATION,
                 &progress_tx,
@@ -258,15 +308,7 @@ impl TranscriptionEngine for MoonshineEngine {
                 })
                 .await;
 
-            Self::transcribe_single(
-                audio_path,
-                &sherpa_binary,
-                &preprocessor,
-                &encoder,
-                &uncached_decoder,
-                &cached_decoder,
-                &tokens,
-            ).await?
+            Self::transcribe_single(audio_path, &sherpa_binary, &model_args, provider).await?
         };
 
         // If no transcript produced, return error
@@ -286,7 +328,9 @@ impl TranscriptionEngine for MoonshineEngine {
             .await;
 
         // Generate SRT file
-        let srt_content = generate_srt_from_text(transcript, duration);
+        let segments = postprocess_segments(segments_from_text(transcript, duration), "sentence", &self.settings);
+        let segments = label_speakers(audio_path, segments, &self.settings, cancel_rx.clone()).await?;
+        let srt_content = generate_srt_from_segments(segments);
         fs::write(&srt_path, srt_content)
             .await
             .map_err(|e| format!("Failed to write SRT file: {}", e))?;
@@ -305,32 +349,23 @@ impl TranscriptionEngine for MoonshineEngine {
 
 impl MoonshineEngine {
     /// Transcribe a single audio file (for short audio under chunk duration)
+    /// `model_args` are the model file and provider flags built by `transcribe`
     async fn transcribe_single(
         audio_path: &Path,
         sherpa_binary: &Path,
-        preprocessor: &Path,
-        encoder: &Path,
-        uncached_decoder: &Path,
-        cached_decoder: &Path,
-        tokens: &Path,
+        model_args: &[String],
+        provider: &str,
     ) -> Result<String, String> {
         let mut cmd = Command::new(sherpa_binary);
-        cmd.args([
-            &format!("--moonshine-preprocessor={}", preprocessor.to_str().unwrap()),
-            &format!("--moonshine-encoder={}", encoder.to_str().unwrap()),
-            &format!("--moonshine-uncached-decoder={}", uncached_decoder.to_str().unwrap()),
-            &format!("--moonshine-cached-decoder={}", cached_decoder.to_str().unwrap()),
-            &format!("--tokens={}", tokens.to_str().unwrap()),
-            "--num-threads=4",
-            audio_path.to_str().unwrap(),
-        ]);
+        cmd.args(model_args);
+        cmd.arg(audio_path);
 
         cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
 
         #[cfg(target_os = "windows")]
         cmd.creation_flags(0x08000000);
 
-        log::info!("Running sherpa-onnx-offline for Moonshine transcription");
+        log::info!("Running sherpa-onnx-offline for Moonshine transcription (provider: {})", provider);
 
         let output = cmd
             .output()
@@ -355,42 +390,31 @@ impl MoonshineEngine {
         Ok(parse_json_text_field(&combined_output))
     }
 
-    /// Transcribe long audio by splitting into chunks with ffmpeg
+    /// Transcribe long audio by splitting it into chunks with a single ffmpeg pass
     async fn transcribe_chunked(
         audio_path: &Path,
         sherpa_binary: &Path,
-        preprocessor: &Path,
-        encoder: &Path,
-        uncached_decoder: &Path,
-        cached_decoder: &Path,
-        tokens: &Path,
+        model_args: &[String],
+        provider: &str,
         total_duration: f64,
         chunk_duration: f64,
         progress_tx: &mpsc::Sender<TranscribeProgress>,
     ) -> Result<String, String> {
-        let num_chunks = (total_duration / chunk_duration).ceil() as usize;
+        // Create temp directory for chunks
+        let temp_dir = ChunkDir::create(audio_path.parent().unwrap_or(Path::new(".")), ".zinc_moonshine_chunks").await?;
+        let chunk_paths = split_audio_segments(audio_path, temp_dir.path(), chunk_duration).await?;
+        let num_chunks = chunk_paths.len();
+
         log::info!(
-            "Splitting {:.1}s audio into {} chunks of {:.0}s each",
+            "Split {:.1}s audio into {} chunks of {:.0}s each",
             total_duration,
             num_chunks,
             chunk_duration
         );
 
-        // Create temp directory for chunks
-        let temp_dir = audio_path
-            .parent()
-            .unwrap_or(Path::new("."))
-            .join(".zinc_moonshine_chunks");
-        fs::create_dir_all(&temp_dir)
-            .await
-            .map_err(|e| format!("Failed to create temp directory: {}", e))?;
-
         let mut all_transcripts = Vec::new();
 
-        for i in 0..num_chunks {
-            let start_time = i as f64 * chunk_duration;
-            let chunk_path = temp_dir.join(format!("chunk_{:03}.wav", i));
-
+        for (i, chunk_path) in chunk_paths.iter().enumerate() {
             let progress = 10.0 + (70.0 * i as f64 / num_chunks as f64);
             let _ = progress_tx
                 .send(TranscribeProgress {
@@ -400,55 +424,11 @@ impl MoonshineEngine {
                 })
                 .await;
 
-            // Extract chunk using ffmpeg
-            let mut ffmpeg_cmd = Command::new(if cfg!(target_os = "windows") {
-                "ffmpeg.exe"
-            } else {
-                "ffmpeg"
-            });
-
-            ffmpeg_cmd.args([
-                "-y",
-                "-i", audio_path.to_str().unwrap(),
-                "-ss", &format!("{:.3}", start_time),
-                "-t", &format!("{:.3}", chunk_duration),
-                "-acodec", "pcm_s16le",
-                "-ar", "16000",
-                "-ac", "1",
-                chunk_path.to_str().unwrap(),
-            ]);
-
-            ffmpeg_cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
-
-            #[cfg(target_os = "windows")]
-            ffmpeg_cmd.creation_flags(0x08000000);
-
-            let ffmpeg_output = ffmpeg_cmd
-                .output()
-                .await
-                .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
-
-            if !ffmpeg_output.status.success() {
-                let _ = fs::remove_dir_all(&temp_dir).await;
-                return Err(format!(
-                    "ffmpeg chunk extraction failed: {}",
-                    String::from_utf8_lossy(&ffmpeg_output.stderr)
-                ));
-            }
-
             // Transcribe this chunk
-            let chunk_transcript = Self::transcribe_single(
-                &chunk_path,
-                sherpa_binary,
-                preprocessor,
-                encoder,
-                uncached_decoder,
-                cached_decoder,
-                tokens,
-            ).await;
+            let chunk_transcript = Self::transcribe_single(chunk_path, sherpa_binary, model_args, provider).await;
 
             // Clean up chunk file immediately
-            let _ = fs::remove_file(&chunk_path).await;
+            let _ = fs::remove_file(chunk_path).await;
 
             match chunk_transcript {
                 Ok(text) => {
@@ -465,8 +445,6 @@ impl MoonshineEngine {
             }
         }
 
-        // Clean up temp directory
-        let _ = fs::remove_dir_all(&temp_dir).await;
 
         Ok(all_transcripts.join(" "))
     }
@@ -1,14 +1,16 @@
 use super::{
-    extract_audio_segment, format_srt_time, get_audio_duration, InstallProgress,
-    TranscribeProgress, TranscriptionEngine, TranscriptionModel,
+    check_nvidia_gpu, fetch_content_length, generate_srt_from_segments, get_audio_duration,
+    postprocess_segments, split_audio_segments, ChunkDir, InstallProgress, TranscribeProgress, TranscriptionEngine, TranscriptionModel,
+    TranscriptionSettings,
 };
+use super::diarization::label_speakers;
+use crate::error::AppError;
 use crate::sherpa_manager::SherpaManager;
-use futures_util::StreamExt;
 use std::path::{Path, PathBuf};
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 use tokio::sync::{mpsc, watch};
-use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperSegment, WhisperToken};
 
 /// Duration threshold for chunked transcription (5 minutes)
 const CHUNK_DURATION_SECS: f64 = 300.0;
@@ -44,13 +46,133 @@ const MODEL_URLS: &[(&str, &str, &str)] = &[
     ),
 ];
 
+/// Expected GGML model file sizes in bytes, used to catch truncated downloads
+/// before whisper.cpp tries to load them
+const MODEL_SIZES: &[(&str, u64)] = &[
+    ("tiny", 77_691_713),
+    ("base", 147_951_465),
+    ("small", 487_601_967),
+    ("medium", 1_533_763_059),
+    ("large-v3", 3_095_033_483),
+];
+
+/// Allowed deviation from the expected model size (upstream re-uploads vary slightly)
+const MODEL_SIZE_TOLERANCE: f64 = 0.1;
+
+/// Special tokens like [_BEG_] and <|endoftext|> carry no text
+fn is_special_token(token: &WhisperToken) -> bool {
+    token
+        .to_str_lossy()
+        .map(|t| t.starts_with("[_") || t.starts_with("<|"))
+        .unwrap_or(true)
+}
+
+/// Silence inside a segment longer than this splits it into separate cues
+const CUE_SPEECH_GAP_CS: i64 = 100;
+
+/// Cues for one segment in ms, shifted by offset_ms
+/// With tighten, cue edges follow the first and last token timestamps and a
+/// pause between tokens ends the cue, so no cue spans silence
+fn segment_cues(segment: &WhisperSegment, text: String, offset_ms: i64, tighten: bool) -> Vec<(i64, i64, String)> {
+    let (seg_start, seg_end) = (segment.start_timestamp(), segment.end_timestamp());
+    let whole = vec![(seg_start * 10 + offset_ms, seg_end * 10 + offset_ms, text)];
+    if !tighten {
+        return whole;
+    }
+
+    // clamp panics on an inverted range
+    let seg_end = seg_end.max(seg_start);
+    let mut cues: Vec<(i64, i64, String)> = Vec::new();
+    let mut current: Option<(i64, i64, String)> = None;
+    for i in 0..segment.n_tokens() {
+        let Some(token) = segment.get_token(i) else { continue };
+        if is_special_token(&token) {
+            continue;
+        }
+        let data = token.token_data();
+        let piece = token.to_str_lossy().map(|t| t.to_string()).unwrap_or_default();
+        // Keep token times inside the segment; whisper occasionally reports them past its edges
+        let (t0, t1) = (data.t0.clamp(seg_start, seg_end), data.t1.clamp(seg_start, seg_end));
+
+        match current.as_mut() {
+            Some(cue) if t0 - cue.1 < CUE_SPEECH_GAP_CS => {
+                cue.1 = cue.1.max(t1);
+                cue.2.push_str(&piece);
+            }
+            _ => {
+                cues.extend(current.take());
+                current = Some((t0, t1, piece));
+            }
+        }
+    }
+    cues.extend(current);
+
+    let cues: Vec<_> = cues
+        .into_iter()
+        .filter(|(start, end, text)| end > start && !text.trim().is_empty())
+        .map(|(start, end, text)| (start * 10 + offset_ms, end * 10 + offset_ms, text))
+        .collect();
+
+    // No usable token timing; fall back to the segment's own bounds
+    if cues.is_empty() {
+        whole
+    } else {
+        cues
+    }
+}
+
+/// Confidence of a segment in 0-1: the mean probability of its text tokens,
+/// scaled down by whisper's estimate that the segment is not speech at all
+fn segment_confidence(segment: &WhisperSegment) -> f32 {
+    let mut total = 0.0;
+    let mut count = 0;
+    for i in 0..segment.n_tokens() {
+        if let Some(token) = segment.get_token(i) {
+            if !is_special_token(&token) {
+                total += token.token_probability();
+                count += 1;
+            }
+        }
+    }
+
+    if count == 0 {
+        return 0.0;
+    }
+    (total / count as f32) * (1.0 - segment.no_speech_probability())
+}
+
+/// Whether a segment clears the configured confidence floor
+fn passes_confidence(segment: &WhisperSegment, text: &str, min_confidence: Option<f32>) -> bool {
+    let Some(min) = min_confidence else {
+        return true;
+    };
+    meets_confidence(segment_confidence(segment), min, text)
+}
+
+/// The threshold check behind passes_confidence
+/// Dropped segments are logged so hallucination filtering can be tuned
+fn meets_confidence(confidence: f32, min: f32, text: &str) -> bool {
+    if confidence < min {
+        log::info!(
+            "Dropping low-confidence segment ({:.2} < {:.2}): {}",
+            confidence,
+            min,
+            text.trim()
+        );
+        return false;
+    }
+    true
+}
+
 /// Whisper-rs transcription engine using native Rust bindings with CUDA support
 /// Provides fast GPU-accelerated transcription via whisper.cpp
-pub struct WhisperRsEngine;
+pub struct WhisperRsEngine {
+    settings: TranscriptionSettings,
+}
 
 impl WhisperRsEngine {
-    pub fn new() -> Self {
-        Self
+    pub fn new(settings: TranscriptionSettings) -> Self {
+        Self { settings }
     }
 
     /// Get the models directory for whisper-rs
@@ -82,6 +204,33 @@ impl WhisperRsEngine {
         Ok(models_dir.join(Self::get_model_filename(model)))
     }
 
+    /// Check that a model file exists and its size is within the expected bounds
+    fn check_model_file(model: &str, path: &Path) -> Result<(), String> {
+        let size = std::fs::metadata(path)
+            .map(|m| m.len())
+            .map_err(|_| format!("Model '{}' is not installed. Please download it first.", model))?;
+
+        let Some(expected) = MODEL_SIZES
+            .iter()
+            .find(|(id, _)| *id == model)
+            .map(|(_, bytes)| *bytes)
+        else {
+            return Ok(());
+        };
+
+        let min = (expected as f64 * (1.0 - MODEL_SIZE_TOLERANCE)) as u64;
+        let max = (expected as f64 * (1.0 + MODEL_SIZE_TOLERANCE)) as u64;
+        if size < min || size > max {
+            return Err(format!(
+                "Model '{}' is corrupt ({} MB on disk, expected about {} MB). Please re-download the model.",
+                model,
+                size / (1024 * 1024),
+                expected / (1024 * 1024)
+            ));
+        }
+        Ok(())
+    }
+
     /// Check if a model is installed
     fn is_model_installed(model: &str) -> bool {
         if let Ok(path) = Self::get_model_path(model) {
@@ -91,107 +240,100 @@ impl WhisperRsEngine {
         }
     }
 
-    /// Check if CUDA is available by checking for nvidia-smi
-    fn check_cuda_available() -> bool {
-        // Check for NVIDIA GPU via nvidia-smi
-        std::process::Command::new("nvidia-smi")
-            .arg("--query-gpu=name")
-            .arg("--format=csv,noheader")
-            .output()
-            .map(|o| o.status.success())
-            .unwrap_or(false)
-    }
-
     /// Load audio file as f32 samples at 16kHz mono
     async fn load_audio(audio_path: &Path) -> Result<Vec<f32>, String> {
         let audio_path = audio_path.to_path_buf();
+        tokio::task::spawn_blocking(move || Self::read_audio(&audio_path))
+            .await
+            .map_err(|e| format!("Audio loading task failed: {}", e))?
+    }
 
-        tokio::task::spawn_blocking(move || {
-            let reader = hound::WavReader::open(&audio_path)
-                .map_err(|e| format!("Failed to open audio file: {}", e))?;
-
-            let spec = reader.spec();
-            let sample_rate = spec.sample_rate;
-            let channels = spec.channels as usize;
-
-            // Read samples based on format
-            let samples: Vec<f32> = match spec.sample_format {
-                hound::SampleFormat::Int => {
-                    let bits = spec.bits_per_sample;
-                    let max_val = (1i32 << (bits - 1)) as f32;
+    /// Blocking body of load_audio
+    pub fn read_audio(audio_path: &Path) -> Result<Vec<f32>, String> {
+        let reader = hound::WavReader::open(audio_path)
+            .map_err(|e| format!("Failed to open audio file: {}", e))?;
+
+        let spec = reader.spec();
+        let sample_rate = spec.sample_rate;
+        let channels = spec.channels as usize;
+        let frames = reader.duration() as usize;
+
+        // Decode and downmix to mono in one pass, so long files never hold a
+        // second interleaved copy of the samples in memory
+        let samples: Box<dyn Iterator<Item = f32>> = match spec.sample_format {
+            hound::SampleFormat::Int => {
+                // hound already recenters unsigned 8-bit PCM and reports the valid bits for
+                // 24-bit samples in 32-bit containers, so every width is signed here. The scale
+                // is computed in i64 because 1i32 << 31 wraps negative and would invert 32-bit audio.
+                let bits = spec.bits_per_sample.clamp(1, 32);
+                let max_val = (1i64 << (bits - 1)) as f32;
+                Box::new(
                     reader
                         .into_samples::<i32>()
                         .filter_map(|s| s.ok())
-                        .map(|s| s as f32 / max_val)
-                        .collect()
-                }
-                hound::SampleFormat::Float => reader
-                    .into_samples::<f32>()
-                    .filter_map(|s| s.ok())
-                    .collect(),
-            };
-
-            // Convert to mono if stereo
-            let mono_samples: Vec<f32> = if channels > 1 {
-                samples
-                    .chunks(channels)
-                    .map(|chunk| chunk.iter().sum::<f32>() / channels as f32)
-                    .collect()
-            } else {
-                samples
-            };
-
-            // Resample to 16kHz if needed (whisper requires 16kHz)
-            let final_samples = if sample_rate != 16000 {
-                // Simple linear interpolation resampling
-                let ratio = sample_rate as f64 / 16000.0;
-                let new_len = (mono_samples.len() as f64 / ratio) as usize;
-                let mut resampled = Vec::with_capacity(new_len);
-
-                for i in 0..new_len {
-                    let src_idx = i as f64 * ratio;
-                    let idx_floor = src_idx.floor() as usize;
-                    let idx_ceil = (idx_floor + 1).min(mono_samples.len() - 1);
-                    let frac = src_idx - idx_floor as f64;
-
-                    let sample = mono_samples[idx_floor] * (1.0 - frac as f32)
-                        + mono_samples[idx_ceil] * frac as f32;
-                    resampled.push(sample);
+                        .map(move |s| (s as f32 / max_val).clamp(-1.0, 1.0)),
+                )
+            }
+            hound::SampleFormat::Float => {
+                // Float WAVs may overshoot full scale or carry NaNs from a bad encoder
+                Box::new(
+                    reader
+                        .into_samples::<f32>()
+                        .filter_map(|s| s.ok())
+                        .map(|s| if s.is_nan() { 0.0 } else { s.clamp(-1.0, 1.0) }),
+                )
+            }
+        };
+
+        let mut mono_samples: Vec<f32> = Vec::with_capacity(frames);
+        if channels > 1 {
+            let mut frame_sum = 0.0f32;
+            let mut frame_len = 0usize;
+            for sample in samples {
+                frame_sum += sample;
+                frame_len += 1;
+                if frame_len == channels {
+                    mono_samples.push(frame_sum / channels as f32);
+                    frame_sum = 0.0;
+                    frame_len = 0;
                 }
+            }
+            if frame_len > 0 {
+                mono_samples.push(frame_sum / frame_len as f32);
+            }
+        } else {
+            mono_samples.extend(samples);
+        }
 
-                resampled
-            } else {
-                mono_samples
-            };
-
-            Ok(final_samples)
-        })
-        .await
-        .map_err(|e| format!("Audio loading task failed: {}", e))?
-    }
-
-    /// Generate SRT content from whisper segments with timestamps
-    fn generate_srt_from_segments(segments: Vec<(i64, i64, String)>) -> String {
-        let mut srt = String::new();
-
-        for (i, (start_ms, end_ms, text)) in segments.iter().enumerate() {
-            let start_secs = *start_ms as f64 / 1000.0;
-            let end_secs = *end_ms as f64 / 1000.0;
+        // Resample to 16kHz if needed (whisper requires 16kHz)
+        let final_samples = if sample_rate != 16000 {
+            // Simple linear interpolation resampling
+            let ratio = sample_rate as f64 / 16000.0;
+            let new_len = (mono_samples.len() as f64 / ratio) as usize;
+            let mut resampled = Vec::with_capacity(new_len);
+
+            for i in 0..new_len {
+                let src_idx = i as f64 * ratio;
+                let idx_floor = src_idx.floor() as usize;
+                let idx_ceil = (idx_floor + 1).min(mono_samples.len() - 1);
+                let frac = src_idx - idx_floor as f64;
+
+                let sample = mono_samples[idx_floor] * (1.0 - frac as f32)
+                    + mono_samples[idx_ceil] * frac as f32;
+                resampled.push(sample);
+            }
 
-            srt.push_str(&format!(
-                "{}\n{} --> {}\n{}\n\n",
-                i + 1,
-                format_srt_time(start_secs),
-                format_srt_time(end_secs),
-                text.trim()
-            ));
-        }
+            resampled
+        } else {
+            mono_samples
+        };
 
-        srt
+        Ok(final_samples)
     }
 
     /// Transcribe audio in chunks for long files
-    /// This prevents memory issues and maintains accurate timestamps
+    /// Each chunk is extracted to its own WAV and loaded only while it is transcribed,
+    /// so the full file is never held in memory and timestamps stay accurate
     async fn transcribe_chunked(
         &self,
         audio_path: &Path,
@@ -210,20 +352,20 @@ impl WhisperRsEngine {
             })
             .await;
 
-        // Get model path
+        // Get model path and make sure the file is intact before whisper.cpp loads it
         let model_path = Self::get_model_path(model)?;
-        if !model_path.exists() {
-            return Err(format!(
-                "Model '{}' is not installed. Please download it first.",
-                model
-            ));
-        }
+        Self::check_model_file(model, &model_path)?;
 
-        // Calculate number of chunks
-        // Each chunk is CHUNK_DURATION_SECS with CHUNK_OVERLAP_SECS overlap
+        // Split the audio once into back-to-back segments of (chunk - overlap) seconds.
+        // Each chunk is one segment plus the first CHUNK_OVERLAP_SECS of the next,
+        // which keeps the overlap without re-decoding the source per chunk
         let effective_chunk_duration = CHUNK_DURATION_SECS - CHUNK_OVERLAP_SECS;
-        let num_chunks = ((duration - CHUNK_OVERLAP_SECS) / effective_chunk_duration).ceil() as usize;
-        let num_chunks = num_chunks.max(1);
+        let overlap_samples = (CHUNK_OVERLAP_SECS * 16000.0) as usize;
+
+        // Create temp directory for chunk files
+        let temp_dir = ChunkDir::create(&std::env::temp_dir(), "zinc_whisper_chunks").await?;
+        let chunk_paths = split_audio_segments(audio_path, temp_dir.path(), effective_chunk_duration).await?;
+        let num_chunks = chunk_paths.len();
 
         log::info!(
             "Chunked transcription: {:.1}s audio -> {} chunks of {:.0}s (with {:.0}s overlap)",
@@ -233,30 +375,33 @@ impl WhisperRsEngine {
             CHUNK_OVERLAP_SECS
         );
 
-        // Create temp directory for chunk files
-        let temp_dir = std::env::temp_dir().join(format!("zinc_whisper_chunks_{}", std::process::id()));
-        fs::create_dir_all(&temp_dir)
-            .await
-            .map_err(|e| format!("Failed to create temp directory: {}", e))?;
-
         let mut all_segments: Vec<(i64, i64, String)> = Vec::new();
+        let (min_confidence, tighten_cues) = (self.settings.min_confidence, self.settings.tighten_cues);
+        let mut next_samples: Option<Vec<f32>> = None;
 
         // Process each chunk
         for chunk_idx in 0..num_chunks {
             // Check for cancellation at the start of each chunk
             if *cancel_rx.borrow() {
-                // Clean up temp directory
-                let _ = fs::remove_dir_all(&temp_dir).await;
                 return Err("Cancelled".to_string());
             }
 
-            let chunk_start = chunk_idx as f64 * effective_chunk_duration;
-            let chunk_duration = if chunk_idx == num_chunks - 1 {
-                // Last chunk: extend to end of audio
-                duration - chunk_start
-            } else {
-                CHUNK_DURATION_SECS
+            // Load this segment (already read if it was the previous chunk's overlap),
+            // then append the head of the next segment as overlap
+            let mut audio_samples = match next_samples.take() {
+                Some(samples) => samples,
+                None => Self::load_audio(&chunk_paths[chunk_idx]).await?,
             };
+            let _ = fs::remove_file(&chunk_paths[chunk_idx]).await;
+
+            if let Some(next_path) = chunk_paths.get(chunk_idx + 1) {
+                let next = Self::load_audio(next_path).await?;
+                audio_samples.extend_from_slice(&next[..overlap_samples.min(next.len())]);
+                next_samples = Some(next);
+            }
+
+            let chunk_start = chunk_idx as f64 * effective_chunk_duration;
+            let chunk_duration = audio_samples.len() as f64 / 16000.0;
 
             // Skip if chunk would be too short
             if chunk_duration < 0.5 {
@@ -279,13 +424,6 @@ impl WhisperRsEngine {
                 })
                 .await;
 
-            // Extract chunk audio
-            let chunk_path = temp_dir.join(format!("chunk_{}.wav", chunk_idx));
-            extract_audio_segment(audio_path, &chunk_path, chunk_start, chunk_duration).await?;
-
-            // Load chunk audio
-            let audio_samples = Self::load_audio(&chunk_path).await?;
-
             log::info!(
                 "Chunk {}/{}: {} samples ({:.1}s) from offset {:.1}s",
                 chunk_idx + 1,
@@ -365,15 +503,9 @@ impl WhisperRsEngine {
                             .to_str_lossy()
                             .map(|s| s.to_string())
                             .unwrap_or_default();
-                        let start = segment.start_timestamp();
-                        let end = segment.end_timestamp();
-
-                        // Convert centiseconds to milliseconds and add chunk offset
-                        let start_ms = start * 10 + chunk_offset_ms;
-                        let end_ms = end * 10 + chunk_offset_ms;
-
-                        if !text.trim().is_empty() {
-                            segments.push((start_ms, end_ms, text));
+                        // Timestamps are centiseconds; segment_cues converts and adds the chunk offset
+                        if !text.trim().is_empty() && passes_confidence(&segment, &text, min_confidence) {
+                            segments.extend(segment_cues(&segment, text, chunk_offset_ms, tighten_cues));
                         }
                     }
                 }
@@ -383,9 +515,6 @@ impl WhisperRsEngine {
             .await
             .map_err(|e| format!("Chunk transcription task failed: {}", e))??;
 
-            // Clean up chunk file immediately
-            let _ = fs::remove_file(&chunk_path).await;
-
             // Merge segments, handling overlap deduplication
             if !all_segments.is_empty() && !chunk_segments.is_empty() {
                 // Find the overlap boundary (where previous chunk ends in overlap region)
@@ -411,8 +540,6 @@ impl WhisperRsEngine {
             }
         }
 
-        // Clean up temp directory
-        let _ = fs::remove_dir_all(&temp_dir).await;
 
         let _ = progress_tx
             .send(TranscribeProgress {
@@ -433,7 +560,9 @@ impl WhisperRsEngine {
         all_segments.sort_by_key(|(start, _, _)| *start);
 
         // Generate SRT file
-        let srt_content = Self::generate_srt_from_segments(all_segments);
+        let segments = postprocess_segments(all_segments, style, &self.settings);
+        let segments = label_speakers(audio_path, segments, &self.settings, cancel_rx.clone()).await?;
+        let srt_content = generate_srt_from_segments(segments);
         let srt_path = audio_path.with_extension("srt");
 
         fs::write(&srt_path, srt_content)
@@ -454,7 +583,7 @@ impl WhisperRsEngine {
 
 impl Default for WhisperRsEngine {
     fn default() -> Self {
-        Self::new()
+        Self::new(TranscriptionSettings::default())
     }
 }
 
@@ -477,7 +606,7 @@ impl TranscriptionEngine for WhisperRsEngine {
     }
 
     async fn check_gpu_available(&self) -> bool {
-        Self::check_cuda_available()
+        check_nvidia_gpu().await
     }
 
     async fn is_available(&self) -> Result<bool, String> {
@@ -541,6 +670,14 @@ impl TranscriptionEngine for WhisperRsEngine {
         }
     }
 
+    fn supports_word_timestamps(&self) -> bool {
+        true
+    }
+
+    fn supports_translation(&self) -> bool {
+        true
+    }
+
     fn supported_languages(&self) -> Vec<&'static str> {
         vec![
             "en", "zh", "de", "es", "ru", "ko", "fr", "ja", "pt", "tr", "pl", "ca", "nl", "ar",
@@ -557,7 +694,8 @@ impl TranscriptionEngine for WhisperRsEngine {
     async fn install(
         &self,
         _progress_callback: Box<dyn Fn(InstallProgress) + Send + 'static>,
-    ) -> Result<(), String> {
+        _cancel_rx: watch::Receiver<bool>,
+    ) -> Result<(), AppError> {
         // whisper-rs is built into the binary, no separate installation needed
         // Just ensure the models directory exists
         let models_dir = Self::get_models_dir()?;
@@ -571,21 +709,24 @@ impl TranscriptionEngine for WhisperRsEngine {
         &self,
         model: &str,
         progress_callback: Box<dyn Fn(InstallProgress) + Send + 'static>,
-    ) -> Result<(), String> {
+        cancel_rx: watch::Receiver<bool>,
+    ) -> Result<(), AppError> {
         let models_dir = Self::get_models_dir()?;
         fs::create_dir_all(&models_dir)
             .await
             .map_err(|e| format!("Failed to create models directory: {}", e))?;
 
-        let model_url = Self::get_model_url(model);
+        let model_url = &crate::network::model_url(Self::get_model_url(model));
         let model_path = Self::get_model_path(model)?;
 
-        // Check if already downloaded
-        if model_path.exists() {
+        // Check if already downloaded (a truncated file is re-downloaded)
+        if Self::check_model_file(model, &model_path).is_ok() {
             log::info!("Model {} already exists at {:?}", model, model_path);
             return Ok(());
         }
 
+        crate::network::ensure_online("downloading a model")?;
+
         progress_callback(InstallProgress {
             downloaded: 0,
             total: None,
@@ -593,22 +734,23 @@ impl TranscriptionEngine for WhisperRsEngine {
             stage: format!("Downloading {} model...", model),
         });
 
-        let client = reqwest::Client::new();
-        let response = client
-            .get(model_url)
-            .header("User-Agent", "Zinc-App")
-            .send()
-            .await
-            .map_err(|e| format!("Failed to start download: {}", e))?;
+        let client = crate::network::http_client();
+
+        // Hugging Face serves models through a CDN redirect that often omits
+        // Content-Length on the GET, so learn the size up front
+        let head_size = fetch_content_length(&client, model_url).await;
+
+        let response = crate::network::send_with_retry(
+            crate::network::with_hf_auth(client.get(model_url), model_url).header("User-Agent", "Zinc-App"),
+        )
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to start download: {}", e)))?;
 
         if !response.status().is_success() {
-            return Err(format!(
-                "Download failed with status: {}",
-                response.status()
-            ));
+            return Err(crate::network::model_download_error(response.status(), model_url));
         }
 
-        let total_size = response.content_length();
+        let total_size = response.content_length().or(head_size);
         let mut downloaded: u64 = 0;
 
         // Download to a temp file first
@@ -618,8 +760,14 @@ impl TranscriptionEngine for WhisperRsEngine {
             .map_err(|e| format!("Failed to create file: {}", e))?;
 
         let mut stream = response.bytes_stream();
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk.map_err(|e| format!("Download error: {}", e))?;
+        while let Some(chunk) = crate::network::next_chunk(&mut stream).await? {
+            if *cancel_rx.borrow() {
+                drop(file);
+                let _ = fs::remove_file(&temp_path).await;
+                return Err(AppError::Cancelled("Cancelled".to_string()));
+            }
+
+            let chunk = chunk.map_err(|e| AppError::Network(format!("Download error: {}", e)))?;
             file.write_all(&chunk)
                 .await
                 .map_err(|e| format!("Write error: {}", e))?;
@@ -651,6 +799,15 @@ impl TranscriptionEngine for WhisperRsEngine {
         Ok(())
     }
 
+    fn model_storage(&self, model: &str) -> Vec<PathBuf> {
+        Self::get_model_path(model).into_iter().collect()
+    }
+
+    async fn validate_model(&self, model: &str) -> Result<(), String> {
+        let model_path = Self::get_model_path(model)?;
+        Self::check_model_file(model, &model_path)
+    }
+
     async fn transcribe(
         &self,
         audio_path: &Path,
@@ -688,14 +845,9 @@ impl TranscriptionEngine for WhisperRsEngine {
             })
             .await;
 
-        // Get model path
+        // Get model path and make sure the file is intact before whisper.cpp loads it
         let model_path = Self::get_model_path(model)?;
-        if !model_path.exists() {
-            return Err(format!(
-                "Model '{}' is not installed. Please download it first.",
-                model
-            ));
-        }
+        Self::check_model_file(model, &model_path)?;
 
         let _ = progress_tx
             .send(TranscribeProgress {
@@ -726,8 +878,9 @@ impl TranscriptionEngine for WhisperRsEngine {
         // Run transcription in a blocking task since whisper-rs is synchronous
         let model_path_clone = model_path.clone();
         let language = language.map(|s| s.to_string());
-        let style = style.to_string();
+        let style_clone = style.to_string();
         let progress_tx_clone = progress_tx.clone();
+        let (min_confidence, tighten_cues) = (self.settings.min_confidence, self.settings.tighten_cues);
 
         let segments = tokio::task::spawn_blocking(move || {
             // Create whisper context with GPU enabled
@@ -735,7 +888,7 @@ impl TranscriptionEngine for WhisperRsEngine {
             ctx_params.use_gpu(true);
             ctx_params.gpu_device(0); // Use first GPU
 
-            println!("=== WHISPER-RS: Loading model with GPU enabled ===");
+            log::debug!("Loading whisper model with GPU enabled");
 
             let ctx = WhisperContext::new_with_params(
                 model_path_clone.to_str().unwrap(),
@@ -743,7 +896,7 @@ impl TranscriptionEngine for WhisperRsEngine {
             )
             .map_err(|e| format!("Failed to load Whisper model: {}", e))?;
 
-            println!("=== WHISPER-RS: Model loaded successfully ===");
+            log::debug!("Whisper model loaded");
 
             // Create full params for transcription
             let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
@@ -761,7 +914,7 @@ impl TranscriptionEngine for WhisperRsEngine {
             // Set segment length based on style:
             // "word" = one word per subtitle (karaoke-style timing)
             // "sentence" = natural phrase groupings (like movie subtitles)
-            if style == "word" {
+            if style_clone == "word" {
                 params.set_max_len(1); // One word per segment
             }
             // For "sentence" mode, don't set max_len - whisper naturally segments by phrases
@@ -804,15 +957,9 @@ impl TranscriptionEngine for WhisperRsEngine {
                     let text = segment.to_str_lossy()
                         .map(|s| s.to_string())
                         .unwrap_or_default();
-                    let start = segment.start_timestamp();
-                    let end = segment.end_timestamp();
-
-                    // whisper-rs returns times in centiseconds (1/100 sec), convert to milliseconds
-                    let start_ms = start * 10;
-                    let end_ms = end * 10;
-
-                    if !text.trim().is_empty() {
-                        segments.push((start_ms, end_ms, text));
+                    // whisper-rs returns times in centiseconds (1/100 sec); segment_cues converts to ms
+                    if !text.trim().is_empty() && passes_confidence(&segment, &text, min_confidence) {
+                        segments.extend(segment_cues(&segment, text, 0, tighten_cues));
                     }
                 }
             }
@@ -838,7 +985,9 @@ impl TranscriptionEngine for WhisperRsEngine {
         }
 
         // Generate SRT file
-        let srt_content = Self::generate_srt_from_segments(segments);
+        let segments = postprocess_segments(segments, style, &self.settings);
+        let segments = label_speakers(audio_path, segments, &self.settings, cancel_rx.clone()).await?;
+        let srt_content = generate_srt_from_segments(segments);
         let srt_path = audio_path.with_extension("srt");
 
         fs::write(&srt_path, srt_content)
@@ -856,3 +1005,69 @@ impl TranscriptionEngine for WhisperRsEngine {
         Ok(srt_path)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn below_threshold_segment_is_filtered() {
+        let segments = [(0.92, "Welcome back."), (0.18, "Thanks for watching!"), (0.5, "Let's begin.")];
+        let kept: Vec<&str> = segments
+            .iter()
+            .filter(|(confidence, text)| meets_confidence(*confidence, 0.5, text))
+            .map(|(_, text)| *text)
+            .collect();
+        assert_eq!(kept, ["Welcome back.", "Let's begin."]);
+    }
+
+    /// Write a 16kHz mono integer WAV to a fresh temp path
+    fn write_wav(bits: u16, samples: &[i32]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("zinc-test-{}.wav", uuid::Uuid::new_v4()));
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 16000,
+            bits_per_sample: bits,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for &sample in samples {
+            if bits == 8 {
+                writer.write_sample(sample as i8).unwrap();
+            } else {
+                writer.write_sample(sample).unwrap();
+            }
+        }
+        writer.finalize().unwrap();
+        path
+    }
+
+    async fn load(bits: u16, samples: &[i32]) -> Vec<f32> {
+        let path = write_wav(bits, samples);
+        let loaded = WhisperRsEngine::load_audio(&path).await;
+        let _ = std::fs::remove_file(&path);
+        loaded.unwrap()
+    }
+
+    fn assert_normalized(samples: &[f32], expected: &[f32]) {
+        assert_eq!(samples.len(), expected.len());
+        assert!(samples.iter().all(|s| (-1.0..=1.0).contains(s)));
+        for (got, want) in samples.iter().zip(expected) {
+            assert!((got - want).abs() < 1e-3, "{} != {}", got, want);
+        }
+    }
+
+    #[tokio::test]
+    async fn eight_bit_wav_is_recentered_and_normalized() {
+        // Stored unsigned on disk: 0, 128 and 255
+        let samples = load(8, &[-128, 0, 127]).await;
+        assert_normalized(&samples, &[-1.0, 0.0, 127.0 / 128.0]);
+    }
+
+    #[tokio::test]
+    async fn twenty_four_bit_wav_is_normalized() {
+        let max = (1 << 23) - 1;
+        let samples = load(24, &[-(1 << 23), -(1 << 22), 0, 1 << 22, max]).await;
+        assert_normalized(&samples, &[-1.0, -0.5, 0.0, 0.5, 1.0]);
+    }
+}
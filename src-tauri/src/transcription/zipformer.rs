@@ -0,0 +1,502 @@
+use super::{
+    generate_srt_from_segments, get_audio_duration, postprocess_segments, split_audio_segments, ChunkDir, validate_model_files,
+    InstallProgress, TranscribeProgress, TranscriptionEngine, TranscriptionModel,
+    TranscriptionSettings,
+};
+use super::diarization::label_speakers;
+use crate::error::AppError;
+use crate::sherpa_manager::SherpaManager;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::fs;
+use tokio::process::Command;
+use tokio::sync::{mpsc, watch};
+
+/// A Zipformer transducer model package from sherpa-onnx releases
+struct ZipformerModel {
+    id: &'static str,
+    name: &'static str,
+    size: &'static str,
+    dir_name: &'static str,
+    url: &'static str,
+    encoder: &'static str,
+    decoder: &'static str,
+    joiner: &'static str,
+}
+
+const ZIPFORMER_MODELS: &[ZipformerModel] = &[
+    ZipformerModel {
+        id: "small",
+        name: "Small (int8)",
+        size: "90 MB",
+        dir_name: "sherpa-onnx-zipformer-small-en-2023-06-26",
+        url: "https://github.com/k2-fsa/sherpa-onnx/releases/download/asr-models/sherpa-onnx-zipformer-small-en-2023-06-26.tar.bz2",
+        encoder: "encoder-epoch-99-avg-1.int8.onnx",
+        decoder: "decoder-epoch-99-avg-1.onnx",
+        joiner: "joiner-epoch-99-avg-1.int8.onnx",
+    },
+    ZipformerModel {
+        id: "gigaspeech",
+        name: "GigaSpeech (int8)",
+        size: "210 MB",
+        dir_name: "sherpa-onnx-zipformer-gigaspeech-2023-12-12",
+        url: "https://github.com/k2-fsa/sherpa-onnx/releases/download/asr-models/sherpa-onnx-zipformer-gigaspeech-2023-12-12.tar.bz2",
+        encoder: "encoder-epoch-30-avg-1.int8.onnx",
+        decoder: "decoder-epoch-30-avg-1.onnx",
+        joiner: "joiner-epoch-30-avg-1.int8.onnx",
+    },
+];
+
+/// Audio is fed to sherpa in fixed windows to keep memory flat on very long files
+const CHUNK_DURATION: f64 = 30.0;
+
+/// A pause longer than this starts a new subtitle cue in sentence mode
+const CUE_GAP_SECS: f64 = 0.8;
+/// Maximum words per subtitle cue in sentence mode
+const CUE_MAX_WORDS: usize = 12;
+/// Duration given to the last word of a chunk, which has no following token
+const LAST_WORD_SECS: f64 = 0.4;
+
+/// Zipformer transcription engine using the sherpa-onnx transducer CLI
+/// Lightweight CPU-only recognizer with token timestamps, suited to very long recordings
+pub struct ZipformerEngine {
+    settings: TranscriptionSettings,
+}
+
+impl ZipformerEngine {
+    pub fn new(settings: TranscriptionSettings) -> Self {
+        Self { settings }
+    }
+
+    /// Look up the model definition for a model ID (defaults to the small model)
+    fn get_model_entry(model: &str) -> &'static ZipformerModel {
+        ZIPFORMER_MODELS
+            .iter()
+            .find(|entry| entry.id == model)
+            .unwrap_or(&ZIPFORMER_MODELS[0])
+    }
+
+    /// Get the models directory for Zipformer
+    fn get_models_dir() -> Result<PathBuf, String> {
+        SherpaManager::get_models_dir("zipformer")
+    }
+
+    /// Check if a model is installed
+    fn is_model_installed(model: &str) -> bool {
+        if let Ok(models_dir) = Self::get_models_dir() {
+            let model_dir = models_dir.join(Self::get_model_entry(model).dir_name);
+            // Check for the tokens file as indicator that model is complete
+            model_dir.join("tokens.txt").exists()
+        } else {
+            false
+        }
+    }
+
+    /// Get the model configuration paths (encoder, decoder, joiner, tokens)
+    fn get_model_paths(model: &str) -> Result<(PathBuf, PathBuf, PathBuf, PathBuf), String> {
+        let entry = Self::get_model_entry(model);
+        let model_dir = Self::get_models_dir()?.join(entry.dir_name);
+
+        if !model_dir.exists() {
+            return Err(format!("Model '{}' is not installed", model));
+        }
+
+        Ok((
+            model_dir.join(entry.encoder),
+            model_dir.join(entry.decoder),
+            model_dir.join(entry.joiner),
+            model_dir.join("tokens.txt"),
+        ))
+    }
+
+    /// Parse token-level timestamps from sherpa-onnx JSON output.
+    /// Returns (seconds, token) pairs in order.
+    fn parse_token_timestamps(output: &str) -> Vec<(f64, String)> {
+        let json = output
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| line.starts_with('{') && line.contains("\"tokens\""))
+            .find_map(|line| serde_json::from_str::<serde_json::Value>(line).ok());
+
+        let Some(json) = json else {
+            return Vec::new();
+        };
+
+        let timestamps = json["timestamps"].as_array().cloned().unwrap_or_default();
+        let tokens = json["tokens"].as_array().cloned().unwrap_or_default();
+
+        timestamps
+            .iter()
+            .zip(tokens.iter())
+            .filter_map(|(ts, token)| Some((ts.as_f64()?, token.as_str()?.to_string())))
+            .collect()
+    }
+
+    /// Join BPE tokens into words with start/end times (seconds).
+    /// A token starting with '▁' or a space begins a new word.
+    fn tokens_to_words(tokens: &[(f64, String)]) -> Vec<(f64, f64, String)> {
+        let mut words: Vec<(f64, f64, String)> = Vec::new();
+
+        for (i, (start, token)) in tokens.iter().enumerate() {
+            let next_start = tokens
+                .get(i + 1)
+                .map(|(t, _)| *t)
+                .unwrap_or(start + LAST_WORD_SECS);
+
+            let starts_word = token.starts_with('▁') || token.starts_with(' ');
+            let piece = token.trim_start_matches('▁').trim_start();
+
+            match words.last_mut() {
+                Some(word) if !starts_word => {
+                    word.1 = next_start;
+                    word.2.push_str(piece);
+                }
+                _ => {
+                    if !piece.is_empty() {
+                        words.push((*start, next_start, piece.to_string()));
+                    }
+                }
+            }
+        }
+
+        words
+    }
+
+    /// Group timed words into subtitle cues (start_ms, end_ms, text).
+    /// "word" style emits one cue per word; "sentence" style breaks on pauses and length.
+    fn words_to_segments(words: &[(f64, f64, String)], style: &str) -> Vec<(i64, i64, String)> {
+        let to_ms = |secs: f64| (secs * 1000.0) as i64;
+
+        if style == "word" {
+            return words
+                .iter()
+                .map(|(start, end, text)| (to_ms(*start), to_ms(*end), text.clone()))
+                .collect();
+        }
+
+        let mut segments = Vec::new();
+        let mut current: Vec<&(f64, f64, String)> = Vec::new();
+
+        for word in words {
+            if let Some(last) = current.last() {
+                if word.0 - last.1 > CUE_GAP_SECS || current.len() >= CUE_MAX_WORDS {
+                    segments.push(Self::flush_cue(&current));
+                    current.clear();
+                }
+            }
+            current.push(word);
+        }
+
+        if !current.is_empty() {
+            segments.push(Self::flush_cue(&current));
+        }
+
+        segments
+    }
+
+    /// Build a single cue from a run of words
+    fn flush_cue(words: &[&(f64, f64, String)]) -> (i64, i64, String) {
+        let start = words.first().map(|w| w.0).unwrap_or(0.0);
+        let end = words.last().map(|w| w.1).unwrap_or(start);
+        let text = words
+            .iter()
+            .map(|w| w.2.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        ((start * 1000.0) as i64, (end * 1000.0) as i64, text)
+    }
+
+    /// Transcribe a single audio file, returning token timestamps relative to its start
+    async fn transcribe_single(
+        audio_path: &Path,
+        sherpa_binary: &Path,
+        encoder: &Path,
+        decoder: &Path,
+        joiner: &Path,
+        tokens: &Path,
+        decoding_args: &[String],
+    ) -> Result<Vec<(f64, String)>, String> {
+        let mut cmd = Command::new(sherpa_binary);
+        cmd.args([
+            &format!("--encoder={}", encoder.to_str().unwrap()),
+            &format!("--decoder={}", decoder.to_str().unwrap()),
+            &format!("--joiner={}", joiner.to_str().unwrap()),
+            &format!("--tokens={}", tokens.to_str().unwrap()),
+            "--provider=cpu",
+            "--num-threads=4",
+        ]);
+        cmd.args(decoding_args);
+        cmd.arg(audio_path);
+
+        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+
+        #[cfg(target_os = "windows")]
+        cmd.creation_flags(0x08000000);
+
+        log::info!("Running sherpa-onnx-offline for Zipformer transcription");
+
+        let output = cmd
+            .output()
+            .await
+            .map_err(|e| format!("Failed to run sherpa-onnx: {}", e))?;
+
+        let stdout_str = String::from_utf8_lossy(&output.stdout);
+        let stderr_str = String::from_utf8_lossy(&output.stderr);
+
+        if !output.status.success() {
+            return Err(format!(
+                "sherpa-onnx transcription failed: {}",
+                stderr_str.lines().next().unwrap_or("unknown error")
+            ));
+        }
+
+        // sherpa-onnx prints the JSON result to stderr on some platforms and stdout on others
+        let combined_output = format!("{}\n{}", stdout_str, stderr_str);
+        Ok(Self::parse_token_timestamps(&combined_output))
+    }
+}
+
+impl Default for ZipformerEngine {
+    fn default() -> Self {
+        Self::new(TranscriptionSettings::default())
+    }
+}
+
+#[async_trait::async_trait]
+impl TranscriptionEngine for ZipformerEngine {
+    fn id(&self) -> &'static str {
+        "zipformer"
+    }
+
+    fn name(&self) -> &'static str {
+        "Zipformer"
+    }
+
+    fn description(&self) -> &'static str {
+        "Low-memory CPU engine for long recordings (8-20x realtime)"
+    }
+
+    fn gpu_required(&self) -> bool {
+        false
+    }
+
+    async fn check_gpu_available(&self) -> bool {
+        false // Always runs on CPU
+    }
+
+    async fn is_available(&self) -> Result<bool, String> {
+        let sherpa_installed = SherpaManager::is_installed().await;
+        let has_model = ZIPFORMER_MODELS.iter().any(|entry| Self::is_model_installed(entry.id));
+        Ok(sherpa_installed && has_model)
+    }
+
+    async fn available_models(&self) -> Vec<TranscriptionModel> {
+        ZIPFORMER_MODELS
+            .iter()
+            .map(|entry| {
+                let (speed_gpu, speed_cpu) = self.speed_multiplier(entry.id);
+                TranscriptionModel {
+                    id: entry.id.to_string(),
+                    name: entry.name.to_string(),
+                    size: entry.size.to_string(),
+                    installed: Self::is_model_installed(entry.id),
+                    speed_gpu,
+                    speed_cpu,
+                }
+            })
+            .collect()
+    }
+
+    fn speed_multiplier(&self, model: &str) -> (f64, f64) {
+        // CPU-only: GPU speed matches CPU speed
+        match model {
+            "small" => (20.0, 20.0),
+            "gigaspeech" => (8.0, 8.0),
+            _ => (20.0, 20.0),
+        }
+    }
+
+    fn supports_word_timestamps(&self) -> bool {
+        true // Transducer token timestamps
+    }
+
+    fn supported_languages(&self) -> Vec<&'static str> {
+        // The bundled Zipformer models are English-only
+        vec!["en"]
+    }
+
+    async fn install(
+        &self,
+        progress_callback: Box<dyn Fn(InstallProgress) + Send + 'static>,
+        cancel_rx: watch::Receiver<bool>,
+    ) -> Result<(), AppError> {
+        if !SherpaManager::is_installed().await {
+            SherpaManager::install(progress_callback, cancel_rx).await?;
+        }
+        Ok(())
+    }
+
+    async fn download_model(
+        &self,
+        model: &str,
+        progress_callback: Box<dyn Fn(InstallProgress) + Send + 'static>,
+        cancel_rx: watch::Receiver<bool>,
+    ) -> Result<(), AppError> {
+        // Auto-install sherpa-onnx if not installed
+        if !SherpaManager::is_installed().await {
+            log::info!("sherpa-onnx not installed, installing automatically...");
+            SherpaManager::install(Box::new(move |progress| {
+                log::info!("Installing sherpa-onnx: {}% - {}", progress.percentage as i32, progress.stage);
+            }), cancel_rx.clone()).await?;
+        }
+
+        let entry = Self::get_model_entry(model);
+
+        SherpaManager::download_model("zipformer", entry.url, entry.dir_name, progress_callback, cancel_rx).await?;
+
+        Ok(())
+    }
+
+    fn model_storage(&self, model: &str) -> Vec<PathBuf> {
+        SherpaManager::model_storage("zipformer", Self::get_model_entry(model).dir_name)
+    }
+
+    async fn validate_model(&self, model: &str) -> Result<(), String> {
+        let (encoder, decoder, joiner, tokens) = Self::get_model_paths(model)?;
+        validate_model_files(model, &[encoder, decoder, joiner, tokens]).await
+    }
+
+    async fn transcribe(
+        &self,
+        audio_path: &Path,
+        model: &str,
+        _language: Option<&str>,
+        style: &str,
+        progress_tx: mpsc::Sender<TranscribeProgress>,
+        cancel_rx: watch::Receiver<bool>,
+    ) -> Result<PathBuf, String> {
+        if *cancel_rx.borrow() {
+            return Err("Cancelled".to_string());
+        }
+
+        let _ = progress_tx
+            .send(TranscribeProgress {
+                stage: "preparing".to_string(),
+                progress: 0.0,
+                message: "Loading Zipformer model...".to_string(),
+            })
+            .await;
+
+        let (encoder, decoder, joiner, tokens) = Self::get_model_paths(model)?;
+
+        for (name, path) in [
+            ("encoder", &encoder),
+            ("decoder", &decoder),
+            ("joiner", &joiner),
+            ("tokens", &tokens),
+        ] {
+            if !path.exists() {
+                return Err(format!(
+                    "Model file '{}' not found at {:?}. Please download the model first.",
+                    name, path
+                ));
+            }
+        }
+
+        let sherpa_binary = SherpaManager::get_binary_path()?;
+        if !sherpa_binary.exists() {
+            return Err("sherpa-onnx is not installed. Please install it first.".to_string());
+        }
+
+        let duration = get_audio_duration(audio_path).await.unwrap_or(60.0);
+
+        let temp_dir = ChunkDir::create(audio_path.parent().unwrap_or(Path::new(".")), ".zinc_zipformer_chunks").await?;
+        // Decode the source once into fixed windows; each is loaded by sherpa only when decoded
+        let chunk_paths = split_audio_segments(audio_path, temp_dir.path(), CHUNK_DURATION).await?;
+        let num_chunks = chunk_paths.len();
+
+        log::info!(
+            "Zipformer transcription: {:.1}s audio in {} chunk(s) of {:.0}s",
+            duration,
+            num_chunks,
+            CHUNK_DURATION
+        );
+
+        let mut all_words: Vec<(f64, f64, String)> = Vec::new();
+
+        for (i, chunk_path) in chunk_paths.iter().enumerate() {
+            if *cancel_rx.borrow() {
+                return Err("Cancelled".to_string());
+            }
+
+            let chunk_start = i as f64 * CHUNK_DURATION;
+            let progress = 5.0 + (85.0 * i as f64 / num_chunks as f64);
+            let _ = progress_tx
+                .send(TranscribeProgress {
+                    stage: "transcribing".to_string(),
+                    progress,
+                    message: format!("Processing chunk {}/{}...", i + 1, num_chunks),
+                })
+                .await;
+
+            let chunk_tokens = Self::transcribe_single(
+                chunk_path,
+                &sherpa_binary,
+                &encoder,
+                &decoder,
+                &joiner,
+                &tokens,
+                &self.settings.sherpa_args,
+            )
+            .await;
+
+            let _ = fs::remove_file(chunk_path).await;
+
+            match chunk_tokens {
+                Ok(chunk_tokens) => {
+                    let words = Self::tokens_to_words(&chunk_tokens);
+                    all_words.extend(
+                        words
+                            .into_iter()
+                            .map(|(start, end, text)| (start + chunk_start, end + chunk_start, text)),
+                    );
+                }
+                Err(e) => {
+                    log::warn!("Chunk {} failed: {}", i + 1, e);
+                    // Continue with other chunks
+                }
+            }
+        }
+
+
+        if all_words.is_empty() {
+            return Err("Transcription produced no text. The audio may be silent, corrupted, or in an unsupported format.".to_string());
+        }
+
+        let _ = progress_tx
+            .send(TranscribeProgress {
+                stage: "transcribing".to_string(),
+                progress: 90.0,
+                message: "Generating subtitles...".to_string(),
+            })
+            .await;
+
+        let segments = postprocess_segments(Self::words_to_segments(&all_words, style), style, &self.settings);
+        let segments = label_speakers(audio_path, segments, &self.settings, cancel_rx.clone()).await?;
+        let srt_path = audio_path.with_extension("srt");
+
+        fs::write(&srt_path, generate_srt_from_segments(segments))
+            .await
+            .map_err(|e| format!("Failed to write SRT file: {}", e))?;
+
+        let _ = progress_tx
+            .send(TranscribeProgress {
+                stage: "transcribing".to_string(),
+                progress: 95.0,
+                message: "Transcription complete, preparing to embed...".to_string(),
+            })
+            .await;
+
+        Ok(srt_path)
+    }
+}
@@ -1,27 +1,126 @@
+use crate::error::AppError;
+use crate::media::{ffmpeg_command, ffprobe_command, LOUDNORM_FILTER};
 use crate::transcription::{
-    EngineInfo, EngineStatus, InstallProgress, TranscribeProgress, TranscriptionDispatcher,
-    TranscriptionModel,
+    generate_lrc_from_segments, get_audio_duration, generate_srt_from_segments, parse_srt, shift_segments, EngineInfo, EngineStatus, InstallProgress, SubtitleCue, TranscribeProgress,
+    TranscriptionDispatcher, TranscriptionModel, TranscriptionSettings,
 };
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
 use tokio::fs;
 use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Command;
 use tokio::sync::{mpsc, watch};
 
 /// Maximum stderr to capture for error reporting (8KB)
 const MAX_STDERR_BYTES: usize = 8192;
 
+/// Subtitle file formats a task can produce; "lrc" is for audio only
+pub const SUBTITLE_FORMATS: &[&str] = &["srt", "lrc"];
+
+/// Audio-only inputs; these get an .srt next to them instead of embedded subtitles
+const AUDIO_EXTENSIONS: &[&str] = &["mp3", "m4a", "aac", "ogg", "opus", "wav", "flac"];
+/// Video inputs accepted for local transcription (matches the file picker)
+const VIDEO_EXTENSIONS: &[&str] = &["mp4", "mkv", "webm", "mov", "avi", "m4v", "wmv", "flv"];
+
+/// Lowercased file extension, if any
+fn extension_of(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+}
+
+/// Output position in seconds from one line of ffmpeg's `-progress` output
+/// Prefers out_time_us; falls back to out_time=HH:MM:SS.micro. "N/A" yields None
+fn parse_ffmpeg_out_time(line: &str) -> Option<f64> {
+    if let Some(us) = line.strip_prefix("out_time_us=") {
+        return us.trim().parse::<i64>().ok().filter(|us| *us > 0).map(|us| us as f64 / 1_000_000.0);
+    }
+    let time = line.strip_prefix("out_time=")?.trim();
+    let mut parts = time.split(':');
+    let (h, m, s) = (parts.next()?, parts.next()?, parts.next()?);
+    let secs = h.parse::<f64>().ok()? * 3600.0 + m.parse::<f64>().ok()? * 60.0 + s.parse::<f64>().ok()?;
+    (secs > 0.0).then_some(secs)
+}
+
+/// Number of transcriptions currently running across all tasks
+static ACTIVE_TRANSCRIPTIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Whether an engine error looks like the model didn't fit in (GPU) memory
+fn is_out_of_memory(error: &str) -> bool {
+    let lower = error.to_lowercase();
+    lower.contains("out of memory")
+        || lower.contains("failed to allocate")
+        || lower.contains("memoryallocation")
+        || lower.contains("bad_alloc")
+        // whisper.cpp reports a failed buffer allocation as a state creation failure
+        || lower.contains("failed to create whisper state")
+}
+
 /// Manages all transcription engines and provides a unified API
 pub struct TranscriptionManager {
     dispatcher: TranscriptionDispatcher,
+    settings: TranscriptionSettings,
+    log_tx: Option<mpsc::Sender<String>>, // Receives ffmpeg stderr lines for the task log
+    override_duration_guard: bool,        // The user confirmed a job over max_transcription_minutes
+    last_rtf: Mutex<Option<f64>>,         // Real-time factor of the last successful transcribe
 }
 
 impl TranscriptionManager {
+    /// A manager with the app's default settings; jobs should pass the caller's with with_settings
     pub fn new() -> Self {
+        let settings = TranscriptionSettings::default();
         Self {
-            dispatcher: TranscriptionDispatcher::new(),
+            dispatcher: TranscriptionDispatcher::new(&settings),
+            settings,
+            log_tx: None,
+            override_duration_guard: false,
+            last_rtf: Mutex::new(None),
+        }
+    }
+
+    /// Measured real-time factor (wall-clock seconds per second of audio) of the last
+    /// successful transcription; below 1.0 is faster than realtime
+    pub fn last_rtf(&self) -> Option<f64> {
+        *self.last_rtf.lock().unwrap()
+    }
+
+    /// Transcribe with `settings` (usually built from the caller's AppConfig) instead of the defaults
+    pub fn with_settings(mut self, settings: TranscriptionSettings) -> Self {
+        self.dispatcher = TranscriptionDispatcher::new(&settings);
+        self.settings = settings;
+        self
+    }
+
+    /// Forward ffmpeg stderr lines from process_video to a task log
+    pub fn with_log(mut self, log_tx: mpsc::Sender<String>) -> Self {
+        self.log_tx = Some(log_tx);
+        self
+    }
+
+    /// Let process_video run past max_transcription_minutes once the user has confirmed
+    pub fn with_duration_guard_override(mut self, override_guard: bool) -> Self {
+        self.override_duration_guard = override_guard;
+        self
+    }
+
+    /// Refuse inputs longer than `max_minutes` (max_transcription_minutes) so a huge job never starts
+    /// by accident. ConfirmationRequired lets the UI ask and retry with the override
+    pub fn check_duration_guard(duration_secs: Option<f64>, max_minutes: Option<u32>) -> Result<(), AppError> {
+        let (Some(limit), Some(secs)) = (max_minutes, duration_secs) else {
+            return Ok(());
+        };
+        if secs <= limit as f64 * 60.0 {
+            return Ok(());
         }
+        let minutes = (secs / 60.0).round() as u64;
+        Err(AppError::ConfirmationRequired(format!(
+            "Confirmation required: this is {}h {}m of audio, over the {} minute transcription limit",
+            minutes / 60,
+            minutes % 60,
+            limit
+        )))
     }
 
     /// Get info for all engines
@@ -38,6 +137,28 @@ impl TranscriptionManager {
         }
     }
 
+    /// Check a source language against the engine's supported codes
+    /// "auto" or an empty code means auto-detect and yields None
+    pub fn validate_language(&self, engine_id: &str, language: Option<&str>) -> Result<Option<String>, String> {
+        let language = match language.map(str::trim) {
+            None | Some("") | Some("auto") => return Ok(None),
+            Some(lang) => lang.to_lowercase(),
+        };
+
+        let engine = self
+            .dispatcher
+            .get_engine(engine_id)
+            .ok_or_else(|| format!("Engine '{}' not found", engine_id))?;
+
+        if !engine.supported_languages().contains(&language.as_str()) {
+            return Err(format!(
+                "Unsupported language '{}' for engine '{}'",
+                language, engine_id
+            ));
+        }
+        Ok(Some(language))
+    }
+
     /// Get available models for an engine
     pub async fn get_engine_models(&self, engine_id: &str) -> Vec<TranscriptionModel> {
         match self.dispatcher.get_engine(engine_id) {
@@ -46,21 +167,35 @@ impl TranscriptionManager {
         }
     }
 
+    /// A model id valid for the engine: the first of `preferred` it offers, else its
+    /// first installed model, else its first model. None for an unknown engine
+    pub async fn resolve_model(&self, engine_id: &str, preferred: &[Option<&str>]) -> Option<String> {
+        let models = self.get_engine_models(engine_id).await;
+        preferred
+            .iter()
+            .flatten()
+            .find(|id| models.iter().any(|m| m.id == **id))
+            .map(|id| id.to_string())
+            .or_else(|| models.iter().find(|m| m.installed).map(|m| m.id.clone()))
+            .or_else(|| models.first().map(|m| m.id.clone()))
+    }
+
     /// Install an engine
     pub async fn install_engine<F>(
         &self,
         engine_id: &str,
         progress_callback: F,
-    ) -> Result<(), String>
+        cancel_rx: watch::Receiver<bool>,
+    ) -> Result<(), AppError>
     where
         F: Fn(InstallProgress) + Send + 'static,
     {
         let engine = self
             .dispatcher
             .get_engine(engine_id)
-            .ok_or_else(|| format!("Engine '{}' not found", engine_id))?;
+            .ok_or_else(|| AppError::Unsupported(format!("Engine '{}' not found", engine_id)))?;
 
-        engine.install(Box::new(progress_callback)).await
+        engine.install(Box::new(progress_callback), cancel_rx).await
     }
 
     /// Download a model for an engine
@@ -69,32 +204,68 @@ impl TranscriptionManager {
         engine_id: &str,
         model_id: &str,
         progress_callback: F,
-    ) -> Result<(), String>
+        cancel_rx: watch::Receiver<bool>,
+    ) -> Result<(), AppError>
+    where
+        F: Fn(InstallProgress) + Send + 'static,
+    {
+        let engine = self
+            .dispatcher
+            .get_engine(engine_id)
+            .ok_or_else(|| AppError::Unsupported(format!("Engine '{}' not found", engine_id)))?;
+
+        engine.download_model(model_id, Box::new(progress_callback), cancel_rx).await
+    }
+
+    /// Delete whatever is on disk for a model, complete or not, and download it again
+    /// Returns the paths that were removed
+    pub async fn repair_model<F>(
+        &self,
+        engine_id: &str,
+        model_id: &str,
+        progress_callback: F,
+        cancel_rx: watch::Receiver<bool>,
+    ) -> Result<Vec<String>, AppError>
     where
         F: Fn(InstallProgress) + Send + 'static,
     {
         let engine = self
             .dispatcher
             .get_engine(engine_id)
-            .ok_or_else(|| format!("Engine '{}' not found", engine_id))?;
+            .ok_or_else(|| AppError::Unsupported(format!("Engine '{}' not found", engine_id)))?;
+        // Engines map unknown ids to a default model; don't wipe that one by mistake
+        if !engine.available_models().await.iter().any(|m| m.id == model_id) {
+            return Err(AppError::Unsupported(format!("Unknown model '{}' for engine '{}'", model_id, engine_id)));
+        }
+        // Checked before anything is deleted, so offline mode can't leave the model missing
+        crate::network::ensure_online("repairing a model")?;
+
+        let mut removed = Vec::new();
+        for path in engine.model_storage(model_id) {
+            let result = if path.is_dir() {
+                fs::remove_dir_all(&path).await
+            } else if path.exists() {
+                fs::remove_file(&path).await
+            } else {
+                continue;
+            };
+            result.map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+            log::info!("Removed {} while repairing {} model {}", path.display(), engine_id, model_id);
+            removed.push(path.to_string_lossy().to_string());
+        }
 
-        engine.download_model(model_id, Box::new(progress_callback)).await
+        engine.download_model(model_id, Box::new(progress_callback), cancel_rx).await?;
+        engine.validate_model(model_id).await?;
+        Ok(removed)
     }
 
     /// Check if ffmpeg is available
     pub async fn check_ffmpeg() -> bool {
-        let mut cmd = Command::new(if cfg!(target_os = "windows") {
-            "ffmpeg.exe"
-        } else {
-            "ffmpeg"
-        });
+        let mut cmd = ffmpeg_command();
         cmd.arg("-version")
             .stdout(Stdio::null())
             .stderr(Stdio::null());
 
-        #[cfg(target_os = "windows")]
-        cmd.creation_flags(0x08000000);
-
         cmd.status()
             .await
             .map(|s| s.success())
@@ -131,19 +302,209 @@ impl TranscriptionManager {
             });
         }
 
+        // Catch missing or truncated model files before starting a long run
+        engine.validate_model(model_id).await?;
+
+        // Smaller models to retry with if this one runs out of memory
+        let fallback_models = &self.settings.fallback_models;
+        let fallbacks = fallback_models
+            .iter()
+            .position(|m| m == model_id)
+            .map_or(&[][..], |i| &fallback_models[i + 1..]);
+
         // Run transcription
-        engine
-            .transcribe(file_path, model_id, language, style, progress_tx, cancel_rx)
+        ACTIVE_TRANSCRIPTIONS.fetch_add(1, Ordering::SeqCst);
+        let mut model = model_id.to_string();
+        let mut started = Instant::now();
+        let mut result = engine
+            .transcribe(file_path, &model, language, style, progress_tx.clone(), cancel_rx.clone())
+            .await;
+
+        for next in fallbacks {
+            match &result {
+                Err(e) if is_out_of_memory(e) && !*cancel_rx.borrow() => {}
+                _ => break,
+            }
+            // Only step down to models that are actually installed
+            if engine.validate_model(next).await.is_err() {
+                continue;
+            }
+
+            let message = format!("Model '{}' ran out of memory, retrying with '{}'", model, next);
+            log::warn!("{}", message);
+            let _ = progress_tx
+                .send(TranscribeProgress {
+                    stage: "preparing".to_string(),
+                    progress: 0.0,
+                    message,
+                })
+                .await;
+
+            // Drop the cached large model first, unless another job is still using it
+            if ACTIVE_TRANSCRIPTIONS.load(Ordering::SeqCst) == 1 {
+                engine.release().await;
+            }
+
+            model = next.clone();
+            started = Instant::now();
+            result = engine
+                .transcribe(file_path, &model, language, style, progress_tx.clone(), cancel_rx.clone())
+                .await;
+        }
+
+        // Free engine resources once the last running transcription finishes
+        if ACTIVE_TRANSCRIPTIONS.fetch_sub(1, Ordering::SeqCst) == 1 {
+            log::info!("Transcription queue drained, releasing engine '{}'", engine_id);
+            engine.release().await;
+        }
+
+        // Time only the attempt that produced the result, so the figure belongs to `model`
+        if result.is_ok() {
+            let elapsed = started.elapsed().as_secs_f64();
+            let rtf = get_audio_duration(file_path)
+                .await
+                .filter(|d| *d > 0.0)
+                .map(|d| elapsed / d);
+            if let Some(rtf) = rtf {
+                log::info!("Transcribed with {}/{} at RTF {:.3} ({:.1}s wall)", engine_id, model, rtf, elapsed);
+            }
+            *self.last_rtf.lock().unwrap() = rtf;
+        }
+
+        result
+    }
+
+    /// Transcribe only the first `seconds` of a file and return the text, to judge
+    /// model quality or the spoken language before a long run. Nothing is written next to the file
+    pub async fn preview_transcription(
+        &self,
+        file_path: &Path,
+        engine_id: &str,
+        model_id: &str,
+        language: Option<&str>,
+        seconds: f64,
+    ) -> Result<String, AppError> {
+        Self::validate_input(file_path).await?;
+        let language = self.validate_language(engine_id, language)?;
+
+        let temp_dir = std::env::temp_dir().join(format!("zinc_preview_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&temp_dir)
             .await
+            .map_err(|e| format!("Failed to create temp directory: {}", e))?;
+
+        let result = async {
+            let sample = temp_dir.join("sample.wav");
+            crate::media::extract_audio_sample(file_path, &sample, seconds, self.settings.normalize_audio).await?;
+
+            // Nobody listens to a preview's progress; the receiver is dropped right away
+            let (progress_tx, _) = mpsc::channel(1);
+            let (_cancel_tx, cancel_rx) = watch::channel(false);
+            let srt = self
+                .transcribe(&sample, engine_id, model_id, language.as_deref(), "sentence", progress_tx, cancel_rx)
+                .await?;
+            let content = fs::read_to_string(&srt)
+                .await
+                .map_err(|e| format!("Failed to read SRT file: {}", e))?;
+            Ok::<_, AppError>(parse_srt(&content).into_iter().map(|cue| cue.text).collect::<Vec<_>>().join("\n"))
+        }
+        .await;
+
+        let _ = fs::remove_dir_all(&temp_dir).await;
+        result
+    }
+
+    /// Check whether a file has at least one audio stream using ffprobe
+    /// Returns None when ffprobe can't tell (missing binary, unreadable file)
+    pub async fn has_audio_stream(path: &Path) -> Option<bool> {
+        let mut cmd = ffprobe_command();
+
+        cmd.args([
+            "-v", "error",
+            "-select_streams", "a",
+            "-show_entries", "stream=index",
+            "-of", "csv=p=0",
+            path.to_str().unwrap_or(""),
+        ]);
+
+        cmd.stdout(Stdio::piped()).stderr(Stdio::null());
+
+        let output = cmd.output().await.ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(!String::from_utf8_lossy(&output.stdout).trim().is_empty())
+    }
+
+    /// Subtitle codec a container (by lowercase extension) can hold
+    /// WebM only supports WebVTT, MKV takes SRT, MP4/MOV need mov_text
+    pub fn subtitle_codec_for(ext: &str) -> Result<&'static str, String> {
+        match ext {
+            "webm" => Ok("webvtt"),
+            "mkv" => Ok("srt"),
+            "mp4" | "m4v" | "mov" => Ok("mov_text"),
+            other => Err(format!(
+                "Cannot embed subtitles into .{} files. Use an mp4, mkv, webm or mov container.",
+                other
+            )),
+        }
+    }
+
+    /// Whether a file is an audio-only format by extension
+    pub fn is_audio_file(path: &Path) -> bool {
+        extension_of(path).is_some_and(|e| AUDIO_EXTENSIONS.contains(&e.as_str()))
+    }
+
+    /// True for any audio or video extension (no leading dot) transcription accepts
+    pub fn is_media_extension(ext: &str) -> bool {
+        let ext = ext.to_lowercase();
+        AUDIO_EXTENSIONS.contains(&ext.as_str()) || VIDEO_EXTENSIONS.contains(&ext.as_str())
+    }
+
+    /// Check a local file up front: known media type with a decodable audio stream
+    /// Skipped when ffprobe can't run; process_video reports the missing ffmpeg instead
+    pub async fn validate_input(path: &Path) -> Result<(), String> {
+        let ext = extension_of(path).unwrap_or_default();
+        if !AUDIO_EXTENSIONS.contains(&ext.as_str()) && !VIDEO_EXTENSIONS.contains(&ext.as_str()) {
+            return Err(format!(
+                "Unsupported file type '.{}'. Supported: {}",
+                ext,
+                VIDEO_EXTENSIONS.iter().chain(AUDIO_EXTENSIONS).copied().collect::<Vec<_>>().join(", ")
+            ));
+        }
+
+        let mut cmd = ffprobe_command();
+        cmd.args([
+            "-v", "error",
+            "-select_streams", "a:0",
+            "-show_entries", "stream=codec_name",
+            "-of", "csv=p=0",
+            path.to_str().unwrap_or(""),
+        ]);
+        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+
+        let Ok(output) = cmd.output().await else {
+            return Ok(());
+        };
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!(
+                "Unsupported or unreadable media file: {}",
+                stderr.lines().last().unwrap_or("ffprobe could not read it").trim()
+            ));
+        }
+
+        let codec = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if codec.is_empty() {
+            return Err("No audio track to transcribe".to_string());
+        }
+        log::info!("Validated local input {:?} (audio codec: {})", path, codec);
+        Ok(())
     }
 
     /// Get video duration in seconds using ffprobe
     async fn get_video_duration_secs(video_path: &Path) -> Option<f64> {
-        let mut cmd = Command::new(if cfg!(target_os = "windows") {
-            "ffprobe.exe"
-        } else {
-            "ffprobe"
-        });
+        let mut cmd = ffprobe_command();
 
         cmd.args([
             "-v", "error",
@@ -154,14 +515,68 @@ impl TranscriptionManager {
 
         cmd.stdout(Stdio::piped()).stderr(Stdio::null());
 
-        #[cfg(target_os = "windows")]
-        cmd.creation_flags(0x08000000);
-
         let output = cmd.output().await.ok()?;
         let stdout = String::from_utf8_lossy(&output.stdout);
         stdout.trim().parse::<f64>().ok()
     }
 
+    /// Move a verified output over the original in one rename, so a crash leaves either the old
+    /// file or the new one and never neither. If the rename fails (e.g. the output is on another
+    /// volume), copy it next to the original first and rename that copy over instead.
+    async fn replace_original(output_path: &Path, original_path: &Path) -> Result<(), String> {
+        let rename_err = match fs::rename(output_path, original_path).await {
+            Ok(()) => return Ok(()),
+            Err(e) => e,
+        };
+        log::warn!("Direct rename failed ({}), falling back to copy and replace", rename_err);
+
+        let file_name = original_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("output");
+        let staged_path = original_path.with_file_name(format!(".{}.zinc_replace", file_name));
+
+        if let Err(e) = fs::copy(output_path, &staged_path).await {
+            let _ = fs::remove_file(&staged_path).await;
+            return Err(format!("Failed to replace with subtitled version: {}", e));
+        }
+        if let Err(e) = fs::rename(&staged_path, original_path).await {
+            let _ = fs::remove_file(&staged_path).await;
+            return Err(format!("Failed to replace with subtitled version: {}", e));
+        }
+
+        let _ = fs::remove_file(output_path).await;
+        Ok(())
+    }
+
+    /// Check that a muxed output is non-empty, readable by ffprobe, and about as long as the source
+    async fn verify_output(source_path: &Path, output_path: &Path) -> Result<(), String> {
+        let size = fs::metadata(output_path)
+            .await
+            .map(|m| m.len())
+            .map_err(|e| format!("Output file missing: {}", e))?;
+        if size == 0 {
+            return Err("Output file is empty".to_string());
+        }
+
+        let output_duration = Self::get_video_duration_secs(output_path)
+            .await
+            .ok_or("ffprobe could not read the output file")?;
+
+        if let Some(source_duration) = Self::get_video_duration_secs(source_path).await {
+            // Allow small container rounding differences
+            let tolerance = (source_duration * 0.05).max(2.0);
+            if (source_duration - output_duration).abs() > tolerance {
+                return Err(format!(
+                    "Output duration {:.1}s does not match source {:.1}s",
+                    output_duration, source_duration
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Calculate a timeout duration based on file size.
     /// Returns 1 minute per GB, clamped between 5 minutes and 2 hours.
     fn compute_timeout(file_size_bytes: u64) -> std::time::Duration {
@@ -170,32 +585,70 @@ impl TranscriptionManager {
         std::time::Duration::from_secs((minutes * 60.0) as u64)
     }
 
-    /// Spawn a task that reads stderr line-by-line, keeping up to MAX_STDERR_BYTES.
+    /// Spawn a task that reads stderr line-by-line, keeping the last MAX_STDERR_BYTES
+    /// (ffmpeg prints the actual error at the end).
     /// This prevents the pipe buffer from filling up and blocking ffmpeg.
+    /// Lines are also forwarded to log_tx when set.
     fn spawn_stderr_drain(
         stderr: tokio::process::ChildStderr,
+        log_tx: Option<mpsc::Sender<String>>,
     ) -> tokio::task::JoinHandle<String> {
         tokio::spawn(async move {
             let reader = BufReader::new(stderr);
             let mut lines = reader.lines();
             let mut collected = String::new();
             while let Ok(Some(line)) = lines.next_line().await {
-                if collected.len() < MAX_STDERR_BYTES {
-                    if !collected.is_empty() {
-                        collected.push('\n');
+                if let Some(tx) = &log_tx {
+                    // Skip periodic progress lines, they would flush everything else out
+                    if !line.starts_with("size=") && !line.starts_with("frame=") {
+                        let _ = tx.try_send(line.clone());
+                    }
+                }
+                if !collected.is_empty() {
+                    collected.push('\n');
+                }
+                collected.push_str(&line);
+
+                // Drop whole lines from the front until back under the limit
+                while collected.len() > MAX_STDERR_BYTES {
+                    match collected.find('\n') {
+                        Some(i) => {
+                            collected.drain(..=i);
+                        }
+                        None => collected.clear(),
                     }
-                    collected.push_str(&line);
                 }
             }
             collected
         })
     }
 
+    /// Format the last few meaningful ffmpeg stderr lines for an error message.
+    /// Returns an empty string when there is nothing useful to show.
+    fn stderr_detail(stderr_output: &str) -> String {
+        let meaningful: Vec<&str> = stderr_output
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            // Skip periodic progress lines, they never explain a failure
+            .filter(|line| !line.starts_with("size=") && !line.starts_with("frame="))
+            .collect();
+
+        if meaningful.is_empty() {
+            return String::new();
+        }
+
+        let len = meaningful.len();
+        format!(": {}", meaningful[len.saturating_sub(3)..].join(" | "))
+    }
+
     /// Extract audio from video file to 16kHz mono WAV format (required by most transcription engines)
     async fn extract_audio(
+        &self,
         video_path: &Path,
         progress_tx: &mpsc::Sender<TranscribeProgress>,
         cancel_rx: &watch::Receiver<bool>,
+        log_tx: Option<mpsc::Sender<String>>,
     ) -> Result<PathBuf, String> {
         // Check for cancellation before starting
         if *cancel_rx.borrow() {
@@ -235,16 +688,19 @@ impl TranscriptionManager {
         }
 
         // Extract audio using ffmpeg: 16kHz mono WAV (required by sherpa-onnx and whisper)
-        let mut cmd = Command::new(if cfg!(target_os = "windows") {
-            "ffmpeg.exe"
-        } else {
-            "ffmpeg"
-        });
+        let mut cmd = ffmpeg_command();
 
         cmd.args([
             "-i",
             video_path.to_str().unwrap_or(""),
             "-vn",           // No video
+        ]);
+
+        if self.settings.normalize_audio {
+            cmd.args(["-af", LOUDNORM_FILTER]);
+        }
+
+        cmd.args([
             "-acodec", "pcm_s16le",  // PCM 16-bit little-endian
             "-ar", "16000",  // 16kHz sample rate
             "-ac", "1",      // Mono
@@ -254,9 +710,6 @@ impl TranscriptionManager {
 
         cmd.stdout(Stdio::null()).stderr(Stdio::piped());
 
-        #[cfg(target_os = "windows")]
-        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-
         log::info!("Extracting audio from {:?} to {:?}", video_path, audio_path);
 
         // Spawn the process and monitor for cancellation
@@ -266,7 +719,7 @@ impl TranscriptionManager {
 
         // Drain stderr in background to prevent pipe buffer deadlock
         let stderr = child.stderr.take().expect("stderr was piped");
-        let stderr_task = Self::spawn_stderr_drain(stderr);
+        let stderr_task = Self::spawn_stderr_drain(stderr, log_tx);
 
         let mut cancel_rx_clone = cancel_rx.clone();
 
@@ -276,11 +729,7 @@ impl TranscriptionManager {
                 let status = result.map_err(|e| format!("Failed to wait for ffmpeg: {}", e))?;
                 if !status.success() {
                     let stderr_output = stderr_task.await.unwrap_or_default();
-                    let detail = if stderr_output.is_empty() {
-                        String::new()
-                    } else {
-                        format!(": {}", stderr_output.lines().last().unwrap_or(&stderr_output))
-                    };
+                    let detail = Self::stderr_detail(&stderr_output);
                     return Err(format!("Audio extraction failed{}", detail));
                 }
             }
@@ -325,15 +774,17 @@ impl TranscriptionManager {
         model_id: &str,
         language: Option<&str>,
         style: &str,
+        in_place: bool,
         progress_tx: mpsc::Sender<TranscribeProgress>,
         cancel_rx: watch::Receiver<bool>,
     ) -> Result<PathBuf, String> {
         log::info!(
-            "process_video called for: {:?} with engine: {}, model: {}, style: {}",
+            "process_video called for: {:?} with engine: {}, model: {}, style: {}, in_place: {}",
             video_path,
             engine_id,
             model_id,
-            style
+            style,
+            in_place
         );
 
         // Check for cancellation
@@ -364,8 +815,18 @@ impl TranscriptionManager {
 
         log::info!("SRT path: {:?}, Output path: {:?}", srt_path, output_path);
 
+        // A silent file would extract to an empty WAV and fail later with a vague error
+        if Self::has_audio_stream(video_path).await == Some(false) {
+            return Err("No audio track to transcribe".to_string());
+        }
+
+        if !self.override_duration_guard {
+            Self::check_duration_guard(get_audio_duration(video_path).await, self.settings.max_transcription_minutes)?;
+        }
+
         // Step 1: Extract audio from video (16kHz mono WAV)
-        let audio_path = Self::extract_audio(video_path, &progress_tx, &cancel_rx).await?;
+        let audio_path =
+            self.extract_audio(video_path, &progress_tx, &cancel_rx, self.log_tx.clone()).await?;
 
         // Check for cancellation before transcription
         if *cancel_rx.borrow() {
@@ -416,6 +877,19 @@ impl TranscriptionManager {
             srt_path.exists()
         );
 
+        // Audio files can't carry a subtitle track; the .srt next to them is the result
+        if Self::is_audio_file(video_path) {
+            let _ = progress_tx
+                .send(TranscribeProgress {
+                    stage: "complete".to_string(),
+                    progress: 100.0,
+                    message: "Subtitles saved".to_string(),
+                })
+                .await;
+
+            return Ok(srt_path);
+        }
+
         // Check for cancellation before embedding
         if *cancel_rx.borrow() {
             let _ = fs::remove_file(&srt_path).await;
@@ -424,14 +898,22 @@ impl TranscriptionManager {
 
         // Step 3: Embed subtitles
         log::info!("Starting subtitle embedding...");
-        Self::embed_subtitles(video_path, &srt_path, &output_path, language, &progress_tx, &cancel_rx).await?;
+        Self::embed_subtitles(
+            video_path,
+            &srt_path,
+            &output_path,
+            language,
+            &progress_tx,
+            &cancel_rx,
+            self.log_tx.clone(),
+        )
+        .await?;
         log::info!(
             "Embedding complete, output exists: {}",
             output_path.exists()
         );
 
-        // Step 4: Replace original with subtitled version
-        log::info!("Replacing original with subtitled version...");
+        // Step 4: Replace original with subtitled version (or keep it as a copy)
         let _ = progress_tx
             .send(TranscribeProgress {
                 stage: "finalizing".to_string(),
@@ -440,18 +922,38 @@ impl TranscriptionManager {
             })
             .await;
 
-        // Rename: original -> backup, subtitled -> original
-        let backup_path = video_dir.join(format!("{}_original.{}", video_stem, video_ext));
-        fs::rename(video_path, &backup_path)
-            .await
-            .map_err(|e| format!("Failed to backup original: {}", e))?;
+        // Make sure the muxed file is playable before touching the original
+        if let Err(e) = Self::verify_output(video_path, &output_path).await {
+            log::error!("Subtitled output failed verification: {}", e);
+            let _ = fs::remove_file(&output_path).await;
+            let _ = fs::remove_file(&srt_path).await;
+            return Err(format!("Subtitled file is invalid, original kept: {}", e));
+        }
 
-        fs::rename(&output_path, video_path)
-            .await
-            .map_err(|e| format!("Failed to replace with subtitled version: {}", e))?;
+        if !in_place {
+            // Leave the source untouched; the subtitled copy is the result
+            let _ = fs::remove_file(&srt_path).await;
+            let temp_dir = video_dir.join(".zinc_temp");
+            let _ = fs::remove_dir_all(&temp_dir).await;
+
+            let _ = progress_tx
+                .send(TranscribeProgress {
+                    stage: "complete".to_string(),
+                    progress: 100.0,
+                    message: "Subtitled copy created".to_string(),
+                })
+                .await;
+
+            return Ok(output_path);
+        }
 
-        // Delete backup
-        let _ = fs::remove_file(&backup_path).await;
+        log::info!("Replacing original with subtitled version...");
+
+        if let Err(e) = Self::replace_original(&output_path, video_path).await {
+            let _ = fs::remove_file(&output_path).await;
+            let _ = fs::remove_file(&srt_path).await;
+            return Err(e);
+        }
 
         // Delete SRT file (subtitles are now embedded in video)
         let _ = fs::remove_file(&srt_path).await;
@@ -471,6 +973,172 @@ impl TranscriptionManager {
         Ok(video_path.to_path_buf())
     }
 
+    /// Check a subtitle output format; LRC lyrics are only written for audio
+    pub fn validate_subtitle_format(format: Option<&str>, is_audio: bool) -> Result<(), String> {
+        match format {
+            None | Some("srt") => Ok(()),
+            Some("lrc") if is_audio => Ok(()),
+            Some("lrc") => Err("LRC lyrics can only be generated for audio files".to_string()),
+            Some(other) => Err(format!(
+                "Unsupported subtitle format '{}'. Supported: {}",
+                other,
+                SUBTITLE_FORMATS.join(", ")
+            )),
+        }
+    }
+
+    /// Turn the .srt written next to an audio file into synchronized .lrc lyrics
+    /// Engines without timestamps have already spread their text evenly in the SRT
+    pub async fn convert_to_lrc(srt_path: &Path) -> Result<PathBuf, String> {
+        let content = fs::read_to_string(srt_path)
+            .await
+            .map_err(|e| format!("Failed to read SRT file: {}", e))?;
+        let segments = parse_srt(&content)
+            .into_iter()
+            .map(|cue| (cue.start, cue.end, cue.text))
+            .collect();
+
+        let lrc_path = srt_path.with_extension("lrc");
+        fs::write(&lrc_path, generate_lrc_from_segments(segments))
+            .await
+            .map_err(|e| format!("Failed to write LRC file: {}", e))?;
+        let _ = fs::remove_file(srt_path).await;
+        Ok(lrc_path)
+    }
+
+    /// Shift every cue of an .srt file, or of a video's embedded track, by `offset_ms`
+    /// Cues are clamped at zero and ones pushed entirely before it dropped; the file is rewritten in place
+    pub async fn shift_subtitles(
+        &self,
+        path: &Path,
+        offset_ms: i64,
+        progress_tx: mpsc::Sender<TranscribeProgress>,
+        cancel_rx: watch::Receiver<bool>,
+    ) -> Result<PathBuf, String> {
+        let content = if extension_of(path).as_deref() == Some("srt") {
+            fs::read_to_string(path)
+                .await
+                .map_err(|e| format!("Failed to read SRT file: {}", e))?
+        } else {
+            crate::media::extract_subtitles(path).await?
+        };
+
+        let segments = parse_srt(&content)
+            .into_iter()
+            .map(|cue| (cue.start, cue.end, cue.text))
+            .collect();
+        let cues: Vec<SubtitleCue> = shift_segments(segments, offset_ms)
+            .into_iter()
+            .enumerate()
+            .map(|(i, (start, end, text))| SubtitleCue { index: i + 1, start, end, text })
+            .collect();
+
+        self.replace_subtitles(path, &cues, None, progress_tx, cancel_rx).await
+    }
+
+    /// Write edited cues back to a finished file without transcribing again
+    /// Audio results are the .srt itself; videos get their old subtitle tracks
+    /// replaced by the edited one, in place
+    pub async fn replace_subtitles(
+        &self,
+        media_path: &Path,
+        cues: &[SubtitleCue],
+        language: Option<&str>,
+        progress_tx: mpsc::Sender<TranscribeProgress>,
+        cancel_rx: watch::Receiver<bool>,
+    ) -> Result<PathBuf, String> {
+        if cues.is_empty() {
+            return Err("No subtitle cues to save".to_string());
+        }
+        if let Some(cue) = cues.iter().find(|c| c.start < 0 || c.end <= c.start) {
+            return Err(format!("Cue {} ends before it starts", cue.index));
+        }
+
+        let segments = cues.iter().map(|c| (c.start, c.end, c.text.clone())).collect();
+        let srt_content = generate_srt_from_segments(segments);
+
+        if media_path.extension().is_some_and(|e| e.eq_ignore_ascii_case("srt")) {
+            fs::write(media_path, srt_content)
+                .await
+                .map_err(|e| format!("Failed to write SRT file: {}", e))?;
+            return Ok(media_path.to_path_buf());
+        }
+
+        let video_dir = media_path.parent().unwrap_or(Path::new("."));
+        let video_stem = media_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("video");
+        let video_ext = media_path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("mp4");
+
+        let srt_path = video_dir.join(format!("{}.edited.srt", video_stem));
+        let stripped_path = video_dir.join(format!("{}_nosubs.{}", video_stem, video_ext));
+        let output_path = video_dir.join(format!("{}_subtitled.{}", video_stem, video_ext));
+
+        fs::write(&srt_path, srt_content)
+            .await
+            .map_err(|e| format!("Failed to write SRT file: {}", e))?;
+
+        let result = async {
+            Self::strip_subtitles(media_path, &stripped_path).await?;
+            Self::embed_subtitles(
+                &stripped_path,
+                &srt_path,
+                &output_path,
+                language,
+                &progress_tx,
+                &cancel_rx,
+                self.log_tx.clone(),
+            )
+            .await?;
+            Self::verify_output(media_path, &output_path).await
+        }
+        .await;
+
+        let _ = fs::remove_file(&srt_path).await;
+        let _ = fs::remove_file(&stripped_path).await;
+        if let Err(e) = result {
+            let _ = fs::remove_file(&output_path).await;
+            return Err(e);
+        }
+
+        if let Err(e) = Self::replace_original(&output_path, media_path).await {
+            let _ = fs::remove_file(&output_path).await;
+            return Err(e);
+        }
+
+        let _ = progress_tx
+            .send(TranscribeProgress {
+                stage: "complete".to_string(),
+                progress: 100.0,
+                message: "Subtitles updated".to_string(),
+            })
+            .await;
+
+        Ok(media_path.to_path_buf())
+    }
+
+    /// Copy a file without its subtitle streams
+    async fn strip_subtitles(input_path: &Path, output_path: &Path) -> Result<(), String> {
+        let output = ffmpeg_command()
+            .args(["-v", "error", "-i"])
+            .arg(input_path)
+            .args(["-map", "0", "-map", "-0:s", "-c", "copy", "-y"])
+            .arg(output_path)
+            .output()
+            .await
+            .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Failed to remove old subtitles: {}", stderr.trim()));
+        }
+        Ok(())
+    }
+
     /// Convert ISO 639-1 language code to ISO 639-2 (3-letter) code and full name
     fn get_language_metadata(language: Option<&str>) -> (&'static str, &'static str) {
         match language {
@@ -525,6 +1193,7 @@ impl TranscriptionManager {
         language: Option<&str>,
         progress_tx: &mpsc::Sender<TranscribeProgress>,
         cancel_rx: &watch::Receiver<bool>,
+        log_tx: Option<mpsc::Sender<String>>,
     ) -> Result<PathBuf, String> {
         // Check for cancellation before starting
         if *cancel_rx.borrow() {
@@ -571,23 +1240,13 @@ impl TranscriptionManager {
             .unwrap_or("mp4")
             .to_lowercase();
 
-        // WebM only supports WebVTT subtitles
-        // MKV supports SRT
-        // MP4 supports mov_text
-        let (subtitle_codec, needs_conversion) = match ext.as_str() {
-            "webm" => ("webvtt", true),
-            "mkv" => ("srt", false),
-            _ => ("mov_text", false),
-        };
+        let subtitle_codec = Self::subtitle_codec_for(&ext)?;
+        let needs_conversion = subtitle_codec == "webvtt";
 
         // Get language metadata for the new subtitle stream
         let (lang_code, lang_title) = Self::get_language_metadata(language);
 
-        let mut cmd = Command::new(if cfg!(target_os = "windows") {
-            "ffmpeg.exe"
-        } else {
-            "ffmpeg"
-        });
+        let mut cmd = ffmpeg_command();
 
         // Build the metadata argument for the new subtitle stream
         let lang_metadata = format!("language={}", lang_code);
@@ -642,9 +1301,6 @@ impl TranscriptionManager {
 
         cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
 
-        #[cfg(target_os = "windows")]
-        cmd.creation_flags(0x08000000);
-
         log::info!("Running ffmpeg for subtitle embedding...");
 
         // Spawn the process and monitor for cancellation
@@ -654,70 +1310,77 @@ impl TranscriptionManager {
 
         // Drain stderr in background to prevent pipe buffer deadlock
         let stderr = child.stderr.take().expect("stderr was piped");
-        let stderr_task = Self::spawn_stderr_drain(stderr);
+        let stderr_task = Self::spawn_stderr_drain(stderr, log_tx);
 
         // Parse stdout for progress reporting (-progress pipe:1)
         let stdout = child.stdout.take().expect("stdout was piped");
         let progress_tx_clone = progress_tx.clone();
-        let progress_task = tokio::spawn(async move {
+        let mut progress_task = tokio::spawn(async move {
             let reader = BufReader::new(stdout);
             let mut lines = reader.lines();
+            let mut last_pct = -1.0;
             while let Ok(Some(line)) = lines.next_line().await {
-                // ffmpeg -progress outputs lines like: out_time_us=12345678
-                if let Some(time_us_str) = line.strip_prefix("out_time_us=") {
-                    if let Ok(time_us) = time_us_str.trim().parse::<i64>() {
-                        if time_us > 0 {
-                            if let Some(total) = total_duration_secs {
-                                let current_secs = time_us as f64 / 1_000_000.0;
-                                let pct = (current_secs / total * 100.0).min(99.0);
-                                let _ = progress_tx_clone
-                                    .send(TranscribeProgress {
-                                        stage: "embedding".to_string(),
-                                        progress: pct,
-                                        message: "Embedding subtitles...".to_string(),
-                                    })
-                                    .await;
-                            }
-                        }
-                    }
+                let (Some(total), Some(current_secs)) = (total_duration_secs, parse_ffmpeg_out_time(&line)) else {
+                    continue;
+                };
+                // Whole percents only; ffmpeg reports several times a second
+                let pct = (current_secs / total * 100.0).clamp(0.0, 99.0).floor();
+                if pct > last_pct {
+                    last_pct = pct;
+                    let _ = progress_tx_clone
+                        .send(TranscribeProgress {
+                            stage: "embedding".to_string(),
+                            progress: pct,
+                            message: "Embedding subtitles...".to_string(),
+                        })
+                        .await;
                 }
             }
         });
 
         let mut cancel_rx_clone = cancel_rx.clone();
+        let mut cancel_open = true;
+        let timeout = tokio::time::sleep(timeout_duration);
+        tokio::pin!(timeout);
 
         // Wait for process completion, cancellation, or timeout
-        tokio::select! {
-            result = child.wait() => {
-                let status = result.map_err(|e| format!("Failed to wait for ffmpeg: {}", e))?;
-                // Ensure progress task finishes so stdout is fully consumed
-                let _ = progress_task.await;
-                if !status.success() {
-                    let stderr_output = stderr_task.await.unwrap_or_default();
-                    let detail = if stderr_output.is_empty() {
-                        String::new()
-                    } else {
-                        format!(": {}", stderr_output.lines().last().unwrap_or(&stderr_output))
-                    };
-                    return Err(format!("ffmpeg muxing failed{}", detail));
+        let failure = loop {
+            tokio::select! {
+                result = child.wait() => {
+                    let status = result.map_err(|e| format!("Failed to wait for ffmpeg: {}", e))?;
+                    // Ensure progress task finishes so stdout is fully consumed
+                    let _ = (&mut progress_task).await;
+                    if !status.success() {
+                        let stderr_output = stderr_task.await.unwrap_or_default();
+                        let detail = Self::stderr_detail(&stderr_output);
+                        return Err(format!("ffmpeg muxing failed{}", detail));
+                    }
+                    break None;
                 }
-            }
-            _ = cancel_rx_clone.changed() => {
-                if *cancel_rx_clone.borrow() {
-                    let _ = child.kill().await;
-                    let _ = fs::remove_file(output_path).await;
-                    return Err("Cancelled".to_string());
+                changed = cancel_rx_clone.changed(), if cancel_open => {
+                    // A dropped sender can't cancel anymore; keep waiting on ffmpeg
+                    if changed.is_err() {
+                        cancel_open = false;
+                    } else if *cancel_rx_clone.borrow() {
+                        break Some("Cancelled".to_string());
+                    }
+                }
+                _ = &mut timeout => {
+                    log::error!("Subtitle embedding timed out after {:?} for {:?}", timeout_duration, video_path);
+                    break Some(format!(
+                        "Subtitle embedding timed out after {} minutes",
+                        timeout_duration.as_secs() / 60
+                    ));
                 }
             }
-            _ = tokio::time::sleep(timeout_duration) => {
-                log::error!("Subtitle embedding timed out after {:?} for {:?}", timeout_duration, video_path);
-                let _ = child.kill().await;
-                let _ = fs::remove_file(output_path).await;
-                return Err(format!(
-                    "Subtitle embedding timed out after {} minutes",
-                    timeout_duration.as_secs() / 60
-                ));
-            }
+        };
+
+        if let Some(error) = failure {
+            let _ = child.kill().await;
+            // Killing ffmpeg closes its stdout, which ends the progress task
+            let _ = progress_task.await;
+            let _ = fs::remove_file(output_path).await;
+            return Err(error);
         }
 
         log::info!("ffmpeg muxing successful");
@@ -753,3 +1416,65 @@ impl Default for TranscriptionManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rms(samples: &[i16]) -> f64 {
+        let sum: f64 = samples.iter().map(|&s| (s as f64).powi(2)).sum();
+        (sum / samples.len().max(1) as f64).sqrt()
+    }
+
+    #[tokio::test]
+    async fn normalized_audio_is_louder_and_still_16k_mono_pcm16() {
+        if ffmpeg_command().arg("-version").output().await.is_err() {
+            eprintln!("ffmpeg not found; skipping");
+            return;
+        }
+
+        // A quiet 440 Hz tone at about -40 dBFS, in a format the extraction has to convert
+        let dir = std::env::temp_dir().join(format!("zinc_loudnorm_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("quiet.wav");
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&input, spec).unwrap();
+        let mut quiet = Vec::new();
+        for i in 0..44100 * 3 {
+            let t = i as f64 / 44100.0;
+            let sample = (0.01 * (2.0 * std::f64::consts::PI * 440.0 * t).sin() * i16::MAX as f64) as i16;
+            writer.write_sample(sample).unwrap();
+            writer.write_sample(sample).unwrap();
+            quiet.push(sample);
+        }
+        writer.finalize().unwrap();
+
+        let settings = TranscriptionSettings {
+            normalize_audio: true,
+            ..TranscriptionSettings::default()
+        };
+        let (progress_tx, _progress_rx) = mpsc::channel(8);
+        let (_cancel_tx, cancel_rx) = watch::channel(false);
+        let audio = TranscriptionManager::new()
+            .with_settings(settings)
+            .extract_audio(&input, &progress_tx, &cancel_rx, None)
+            .await
+            .unwrap();
+
+        let mut reader = hound::WavReader::open(&audio).unwrap();
+        let out_spec = reader.spec();
+        let normalized: Vec<i16> = reader.samples::<i16>().map(Result::unwrap).collect();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(out_spec.sample_rate, 16000);
+        assert_eq!(out_spec.channels, 1);
+        assert_eq!(out_spec.bits_per_sample, 16);
+        assert_eq!(out_spec.sample_format, hound::SampleFormat::Int);
+        assert!(rms(&normalized) > rms(&quiet) * 4.0);
+    }
+}
@@ -1,6 +1,8 @@
 // Legacy whisper implementation - mostly superseded by transcription system
-// Only check_ffmpeg() is currently used
+// Only check_ffmpeg() and get_ffmpeg_info() are currently used
 
+use crate::ffmpeg_manager::FfmpegManager;
+use crate::media::ffmpeg_command;
 use crate::whisper_manager::WhisperManager;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
@@ -18,6 +20,15 @@ pub struct TranscribeProgress {
     pub message: String,
 }
 
+/// Where ffmpeg was found and which version it is
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FfmpegInfo {
+    pub found: bool,
+    pub path: Option<String>,
+    pub version: Option<String>,
+    pub install_hint: Option<String>,
+}
+
 #[allow(dead_code)]
 pub struct Whisper;
 
@@ -40,22 +51,92 @@ impl Whisper {
 
     /// Check if ffmpeg is available
     pub async fn check_ffmpeg() -> bool {
-        let mut cmd = Command::new(if cfg!(target_os = "windows") {
+        let mut cmd = ffmpeg_command();
+        cmd.arg("-version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+
+        cmd.status()
+            .await
+            .map(|s| s.success())
+            .unwrap_or(false)
+    }
+
+    /// Locate ffmpeg (managed binary first, then PATH) and read its version
+    /// On macOS, PATH already includes the Homebrew/MacPorts dirs added by fix_path_env
+    pub async fn get_ffmpeg_info() -> FfmpegInfo {
+        let binary = if cfg!(target_os = "windows") {
             "ffmpeg.exe"
         } else {
             "ffmpeg"
+        };
+
+        let managed = FfmpegManager::get_ffmpeg_path()
+            .ok()
+            .filter(|p| p.is_file());
+        let path = managed.or_else(|| {
+            std::env::var_os("PATH").and_then(|paths| {
+                std::env::split_paths(&paths)
+                    .map(|dir| dir.join(binary))
+                    .find(|candidate| candidate.is_file())
+            })
         });
+
+        let Some(path) = path else {
+            return FfmpegInfo {
+                found: false,
+                path: None,
+                version: None,
+                install_hint: Some(Self::ffmpeg_install_hint().to_string()),
+            };
+        };
+
+        let mut cmd = Command::new(&path);
         cmd.arg("-version")
-            .stdout(Stdio::null())
+            .stdout(Stdio::piped())
             .stderr(Stdio::null());
 
         #[cfg(target_os = "windows")]
         cmd.creation_flags(0x08000000);
 
-        cmd.status()
-            .await
-            .map(|s| s.success())
-            .unwrap_or(false)
+        let version = match cmd.output().await {
+            Ok(output) if output.status.success() => {
+                // First line looks like "ffmpeg version 6.1.1 Copyright (c) ..."
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .next()
+                    .and_then(|line| line.strip_prefix("ffmpeg version "))
+                    .and_then(|rest| rest.split_whitespace().next())
+                    .map(|v| v.to_string())
+            }
+            _ => {
+                // Present on PATH but not runnable (broken install, wrong arch)
+                return FfmpegInfo {
+                    found: false,
+                    path: Some(path.to_string_lossy().to_string()),
+                    version: None,
+                    install_hint: Some(Self::ffmpeg_install_hint().to_string()),
+                };
+            }
+        };
+
+        FfmpegInfo {
+            found: true,
+            path: Some(path.to_string_lossy().to_string()),
+            version,
+            install_hint: None,
+        }
+    }
+
+    /// Platform-specific instructions for installing ffmpeg
+    fn ffmpeg_install_hint() -> &'static str {
+        if cfg!(target_os = "windows") {
+            "Install ffmpeg with \"winget install Gyan.FFmpeg\" (or download it from ffmpeg.org and add its bin folder to PATH), then restart Zinc."
+        } else if cfg!(target_os = "macos") {
+            "Install ffmpeg with Homebrew (\"brew install ffmpeg\") or MacPorts (\"sudo port install ffmpeg\"), then restart Zinc."
+        } else {
+            "Install ffmpeg with your package manager (e.g. \"sudo apt install ffmpeg\" or \"sudo dnf install ffmpeg\"), then restart Zinc."
+        }
     }
 
     /// Extract audio from video file to WAV format suitable for whisper
@@ -73,11 +154,7 @@ impl Whisper {
             })
             .await;
 
-        let mut cmd = Command::new(if cfg!(target_os = "windows") {
-            "ffmpeg.exe"
-        } else {
-            "ffmpeg"
-        });
+        let mut cmd = ffmpeg_command();
 
         cmd.args([
             "-i",
@@ -94,9 +171,6 @@ impl Whisper {
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
 
-        #[cfg(target_os = "windows")]
-        cmd.creation_flags(0x08000000);
-
         let output = cmd
             .output()
             .await
@@ -152,11 +226,7 @@ impl Whisper {
             _ => ("mov_text", false), // For MP4 and others
         };
 
-        let mut cmd = Command::new(if cfg!(target_os = "windows") {
-            "ffmpeg.exe"
-        } else {
-            "ffmpeg"
-        });
+        let mut cmd = ffmpeg_command();
 
         if needs_conversion {
             // For WebM, we need to convert SRT to WebVTT during muxing
@@ -195,9 +265,6 @@ impl Whisper {
 
         cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
 
-        #[cfg(target_os = "windows")]
-        cmd.creation_flags(0x08000000);
-
         log::info!("Running ffmpeg for subtitle embedding...");
 
         let output = cmd
@@ -1,4 +1,4 @@
-use futures_util::StreamExt;
+use crate::error::AppError;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::process::Stdio;
@@ -8,6 +8,9 @@ use tokio::process::Command;
 
 const APP_IDENTIFIER: &str = "com.zinc.app";
 
+/// Records which release tag is installed, since whisper.cpp has no --version
+const VERSION_FILE: &str = "whisper-version.txt";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "status")]
 pub enum WhisperStatus {
@@ -134,9 +137,23 @@ impl WhisperManager {
         Ok("whisper.cpp (installed)".to_string())
     }
 
+    /// Release tag of the installed binary, from the install-time record or the --help banner
+    pub async fn installed_tag() -> Option<String> {
+        if let Ok(tag) = fs::read_to_string(Self::get_bin_dir().ok()?.join(VERSION_FILE)).await {
+            return Some(tag.trim().to_string());
+        }
+        Self::get_installed_version()
+            .await
+            .ok()?
+            .strip_prefix("whisper.cpp v")
+            .map(|v| format!("v{}", v))
+    }
+
     /// Fetch the latest version tag from GitHub API
     pub async fn get_latest_version() -> Result<String, String> {
-        let client = reqwest::Client::new();
+        crate::network::ensure_online("checking for whisper.cpp updates")?;
+
+        let client = crate::network::http_client();
         let response = client
             .get("https://api.github.com/repos/ggml-org/whisper.cpp/releases/latest")
             .header("User-Agent", "Zinc-App")
@@ -244,10 +261,10 @@ impl WhisperManager {
             "whisper-bin-x64.zip"
         };
 
-        format!(
+        crate::network::model_url(&format!(
             "https://github.com/ggml-org/whisper.cpp/releases/download/{}/{}",
             version, asset_name
-        )
+        ))
     }
 
     /// Install whisper by downloading from GitHub
@@ -263,6 +280,8 @@ impl WhisperManager {
             .await
             .map_err(|e| format!("Failed to create bin directory: {}", e))?;
 
+        crate::network::ensure_online("installing whisper.cpp")?;
+
         // Get latest version
         let version = Self::get_latest_version().await?;
         let download_url = Self::get_download_url(&version);
@@ -274,13 +293,12 @@ impl WhisperManager {
             stage: "Downloading whisper...".to_string(),
         });
 
-        let client = reqwest::Client::new();
-        let response = client
-            .get(&download_url)
-            .header("User-Agent", "Zinc-App")
-            .send()
-            .await
-            .map_err(|e| format!("Failed to download whisper: {}", e))?;
+        let client = crate::network::http_client();
+        let response = crate::network::send_with_retry(
+            client.get(&download_url).header("User-Agent", "Zinc-App"),
+        )
+        .await
+        .map_err(|e| format!("Failed to download whisper: {}", e))?;
 
         if !response.status().is_success() {
             return Err(format!(
@@ -300,7 +318,7 @@ impl WhisperManager {
         let mut downloaded: u64 = 0;
         let mut stream = response.bytes_stream();
 
-        while let Some(chunk) = stream.next().await {
+        while let Some(chunk) = crate::network::next_chunk(&mut stream).await? {
             let chunk = chunk.map_err(|e| format!("Download error: {}", e))?;
             file.write_all(&chunk)
                 .await
@@ -333,7 +351,7 @@ impl WhisperManager {
         });
 
         // Extract the zip file
-        Self::extract_zip(&temp_zip, &bin_dir, &binary_path).await?;
+        Self::extract_zip(&temp_zip, &bin_dir, &binary_path, progress_callback).await?;
 
         // Clean up zip file
         let _ = fs::remove_file(&temp_zip).await;
@@ -352,15 +370,21 @@ impl WhisperManager {
                 .map_err(|e| format!("Failed to set executable permission: {}", e))?;
         }
 
+        let _ = fs::write(bin_dir.join(VERSION_FILE), &version).await;
+
         Ok(version)
     }
 
     /// Extract zip file - extracts whisper-cli and required DLLs
-    async fn extract_zip(
+    async fn extract_zip<F>(
         zip_path: &PathBuf,
         bin_dir: &PathBuf,
         target_path: &PathBuf,
-    ) -> Result<(), String> {
+        progress_callback: F,
+    ) -> Result<(), String>
+    where
+        F: Fn(InstallProgress) + Send + 'static,
+    {
         // Read zip file
         let zip_data = fs::read(zip_path)
             .await
@@ -373,6 +397,7 @@ impl WhisperManager {
         tokio::task::spawn_blocking(move || {
             use std::io::{Cursor, Read};
 
+            let total_bytes = zip_data.len() as u64;
             let reader = Cursor::new(zip_data);
             let mut archive = zip::ZipArchive::new(reader)
                 .map_err(|e| format!("Failed to open zip archive: {}", e))?;
@@ -396,12 +421,22 @@ impl WhisperManager {
             ];
 
             let mut extracted_main = false;
+            let total_entries = archive.len();
+            let mut processed_bytes: u64 = 0;
 
-            for i in 0..archive.len() {
+            for i in 0..total_entries {
                 let mut file = archive
                     .by_index(i)
                     .map_err(|e| format!("Failed to read zip entry: {}", e))?;
 
+                processed_bytes += file.compressed_size();
+                progress_callback(InstallProgress {
+                    downloaded: processed_bytes.min(total_bytes),
+                    total: Some(total_bytes),
+                    percentage: (i + 1) as f64 / total_entries as f64 * 100.0,
+                    stage: "Extracting...".to_string(),
+                });
+
                 let name = file.name().to_string();
                 if name.contains("__MACOSX") {
                     continue;
@@ -459,13 +494,13 @@ impl WhisperManager {
     }
 
     /// Download a model file from Hugging Face
-    pub async fn download_model<F>(model: &str, progress_callback: F) -> Result<(), String>
+    pub async fn download_model<F>(model: &str, progress_callback: F) -> Result<(), AppError>
     where
         F: Fn(InstallProgress) + Send + 'static,
     {
         let valid_models = ["tiny", "base", "small"];
         if !valid_models.contains(&model) {
-            return Err(format!("Invalid model: {}. Valid models: {:?}", model, valid_models));
+            return Err(AppError::Unsupported(format!("Invalid model: {}. Valid models: {:?}", model, valid_models)));
         }
 
         let models_dir = Self::get_models_dir()?;
@@ -476,10 +511,12 @@ impl WhisperManager {
             .await
             .map_err(|e| format!("Failed to create models directory: {}", e))?;
 
-        let download_url = format!(
+        let download_url = crate::network::model_url(&format!(
             "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-{}.bin",
             model
-        );
+        ));
+
+        crate::network::ensure_online("downloading a model")?;
 
         progress_callback(InstallProgress {
             downloaded: 0,
@@ -488,19 +525,15 @@ impl WhisperManager {
             stage: format!("Downloading {} model...", model),
         });
 
-        let client = reqwest::Client::new();
-        let response = client
-            .get(&download_url)
-            .header("User-Agent", "Zinc-App")
-            .send()
-            .await
-            .map_err(|e| format!("Failed to download model: {}", e))?;
+        let client = crate::network::http_client();
+        let response = crate::network::send_with_retry(
+            crate::network::with_hf_auth(client.get(&download_url), &download_url).header("User-Agent", "Zinc-App"),
+        )
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to download model: {}", e)))?;
 
         if !response.status().is_success() {
-            return Err(format!(
-                "Download failed with status: {}",
-                response.status()
-            ));
+            return Err(crate::network::model_download_error(response.status(), &download_url));
         }
 
         let total_size = response.content_length();
@@ -514,8 +547,8 @@ impl WhisperManager {
         let mut downloaded: u64 = 0;
         let mut stream = response.bytes_stream();
 
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk.map_err(|e| format!("Download error: {}", e))?;
+        while let Some(chunk) = crate::network::next_chunk(&mut stream).await? {
+            let chunk = chunk.map_err(|e| AppError::Network(format!("Download error: {}", e)))?;
             file.write_all(&chunk)
                 .await
                 .map_err(|e| format!("Failed to write file: {}", e))?;
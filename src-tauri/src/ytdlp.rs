@@ -1,4 +1,6 @@
 use crate::deno_manager::DenoManager;
+use crate::error::AppError;
+use crate::pipeline::TaskStatus;
 use crate::ytdlp_manager::YtDlpManager;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
@@ -24,6 +26,46 @@ pub struct VideoFormat {
     pub vbr: Option<f64>,
 }
 
+impl VideoFormat {
+    fn has_video(&self) -> bool {
+        self.vcodec.as_deref().is_some_and(|c| c != "none")
+    }
+
+    fn has_audio(&self) -> bool {
+        self.acodec.as_deref().is_some_and(|c| c != "none")
+    }
+
+    /// Frame height parsed from a "1920x1080" resolution
+    fn height(&self) -> Option<u32> {
+        self.resolution
+            .as_deref()?
+            .split_once('x')?
+            .1
+            .parse()
+            .ok()
+    }
+
+    /// Exact size when known, otherwise yt-dlp's approximation
+    fn size(&self) -> Option<u64> {
+        self.filesize.or(self.filesize_approx)
+    }
+}
+
+/// Formats grouped by stream type for building a format picker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FormatDetails {
+    pub video_only: Vec<VideoFormat>, // Highest resolution first
+    pub audio_only: Vec<VideoFormat>, // Highest bitrate first
+    pub combined: Vec<VideoFormat>,   // Highest resolution first
+}
+
+/// Expected size of a download, summed over the selected streams
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SizeEstimate {
+    pub bytes: u64,
+    pub human: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VideoInfo {
     pub id: String,
@@ -41,13 +83,14 @@ pub struct VideoInfo {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DownloadProgress {
     pub download_id: String,
-    pub status: String,
+    pub status: TaskStatus, // Downloading or Postprocessing
     pub progress: f64,
     pub speed: Option<String>,
     pub eta: Option<String>,
     pub filename: Option<String>,
     pub total_bytes: Option<u64>,
     pub downloaded_bytes: Option<u64>,
+    pub codec: Option<String>, // "vcodec/acodec" actually selected, when a preference is set
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,12 +98,58 @@ pub struct DownloadOptions {
     pub format: String,
     pub output_dir: PathBuf,
     pub filename_template: Option<String>,
-    pub container_format: Option<String>,
+    pub output_container: Option<String>, // Passed as --merge-output-format
     #[serde(default)]
     pub generate_subtitles: bool,
     pub whisper_model: Option<String>,
     pub source_address: Option<String>, // IPv4 address to bind downloads to
-    pub cookies_browser: Option<String>, // Browser name for --cookies-from-browser
+    pub cookies_browser: Option<String>, // "browser" or "browser:profile" for --cookies-from-browser
+    #[serde(default = "default_restrict_filenames")]
+    pub restrict_filenames: bool, // Pass --restrict-filenames (ASCII-only output names)
+    #[serde(default)]
+    pub embed_native_subs: bool, // Pass --write-subs --embed-subs; yt-dlp embeds during its merge step
+    pub preferred_vcodec: Option<String>, // "h264", "hevc", "vp9" or "av1"
+    pub preferred_acodec: Option<String>, // "aac", "opus" or "mp3"
+    #[serde(default)]
+    pub format_sort: Option<Vec<String>>, // Passed as --format-sort, e.g. ["res:1080", "fps", "vcodec:av01"]
+    #[serde(default)]
+    pub max_filesize: Option<String>, // Passed as --max-filesize, e.g. "200M"
+    #[serde(default)]
+    pub audio_format: Option<String>, // Passed as --extract-audio --audio-format, e.g. "mp3"
+    #[serde(default)]
+    pub audio_quality: Option<String>, // Passed as --audio-quality: "0" (best VBR) or a bitrate like "320K"
+    #[serde(default)]
+    pub credentials: Credentials,
+}
+
+/// Site login for content cookies can't reach (age-restricted, members-only)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Credentials {
+    pub username: Option<String>,
+    #[serde(skip)]
+    pub password: Option<String>, // Held in memory only, never written out
+    pub use_netrc: bool,          // Read logins from ~/.netrc instead
+}
+
+impl Credentials {
+    fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.use_netrc {
+            args.push("--netrc".to_string());
+        } else if let Some(username) = self.username.as_deref().filter(|u| !u.is_empty()) {
+            args.push("--username".to_string());
+            args.push(username.to_string());
+            if let Some(password) = &self.password {
+                args.push("--password".to_string());
+                args.push(password.clone());
+            }
+        }
+        args
+    }
+}
+
+fn default_restrict_filenames() -> bool {
+    true
 }
 
 impl Default for DownloadOptions {
@@ -69,25 +158,94 @@ impl Default for DownloadOptions {
             format: "best".to_string(),
             output_dir: dirs::download_dir().unwrap_or_else(|| PathBuf::from(".")),
             filename_template: None,
-            container_format: Some("mp4".to_string()),
+            output_container: Some("mp4".to_string()),
             generate_subtitles: false,
             whisper_model: None,
             source_address: None,
             cookies_browser: None,
+            restrict_filenames: true,
+            embed_native_subs: false,
+            preferred_vcodec: None,
+            preferred_acodec: None,
+            format_sort: None,
+            max_filesize: None,
+            audio_format: None,
+            audio_quality: None,
+            credentials: Credentials::default(),
         }
     }
 }
 
+/// yt-dlp postprocessor output (merging formats, extracting audio, converting)
+fn is_postprocessor_line(line: &str) -> bool {
+    ["[Merger]", "[ExtractAudio]", "[VideoConvertor]", "[VideoRemuxer]"]
+        .iter()
+        .any(|tag| line.starts_with(tag))
+}
+
 fn try_capture_filename(regex: &Option<Regex>, line: &str) -> Option<String> {
     regex.as_ref()
         .and_then(|r| r.captures(line))
         .map(|caps| caps[1].to_string())
 }
 
+/// Browsers supported by yt-dlp's --cookies-from-browser
+const SUPPORTED_COOKIE_BROWSERS: &[&str] = &[
+    "brave", "chrome", "chromium", "edge", "firefox", "opera", "safari", "vivaldi", "whale",
+];
+
+/// Codec names accepted for preferred_vcodec / preferred_acodec
+const SUPPORTED_VCODECS: &[&str] = &["h264", "hevc", "vp9", "av1"];
+const SUPPORTED_ACODECS: &[&str] = &["aac", "opus", "mp3"];
+
+/// Field names yt-dlp's --format-sort understands
+const FORMAT_SORT_FIELDS: &[&str] = &[
+    "hasvid", "hasaud", "ie_pref", "lang", "quality", "source", "proto", "vcodec", "acodec",
+    "codec", "vext", "aext", "ext", "filesize", "fs_approx", "size", "height", "width", "res",
+    "fps", "hdr", "channels", "tbr", "vbr", "abr", "br", "asr", "id",
+];
+
+/// yt-dlp filter matching a video codec family (vcodec strings look like "avc1.640028")
+fn vcodec_filter(codec: &str) -> Option<&'static str> {
+    match codec {
+        "h264" | "avc" => Some("[vcodec~='^(avc1|h264)']"),
+        "hevc" | "h265" => Some("[vcodec~='^(hvc1|hev1|h265)']"),
+        "vp9" => Some("[vcodec~='^vp0?9']"),
+        "av1" => Some("[vcodec~='^(av01|av1)']"),
+        _ => None,
+    }
+}
+
+/// yt-dlp filter matching an audio codec family (acodec strings look like "mp4a.40.2")
+fn acodec_filter(codec: &str) -> Option<&'static str> {
+    match codec {
+        "aac" => Some("[acodec~='^(mp4a|aac)']"),
+        "opus" => Some("[acodec^=opus]"),
+        "mp3" => Some("[acodec^=mp3]"),
+        _ => None,
+    }
+}
+
+/// Audio-only presets: (id, --audio-format, --audio-quality, source codec worth preferring)
+/// "audio" keeps the site's stream as-is; the rest extract, and a matching source codec
+/// lets yt-dlp skip the re-encode
+const AUDIO_PRESETS: &[(&str, Option<&str>, &str, Option<&str>)] = &[
+    ("audio", None, "0", None),
+    ("mp3", Some("mp3"), "0", Some("mp3")),
+    ("mp3-320", Some("mp3"), "320K", Some("mp3")),
+    ("mp3-128", Some("mp3"), "128K", Some("mp3")),
+    ("m4a", Some("m4a"), "0", Some("aac")),
+    ("opus", Some("opus"), "0", Some("opus")),
+    ("flac", Some("flac"), "0", None), // Lossless, so any source works
+];
+
+/// Containers yt-dlp can merge into that we can also embed subtitles in
+const SUPPORTED_OUTPUT_CONTAINERS: &[&str] = &["mp4", "mkv", "webm", "mov"];
+
 pub struct YtDlp;
 
 impl YtDlp {
-    fn get_command() -> PathBuf {
+    pub fn get_command() -> PathBuf {
         // Try managed binary first
         if let Ok(path) = YtDlpManager::get_binary_path() {
             if path.exists() {
@@ -102,25 +260,204 @@ impl YtDlp {
         })
     }
 
+    /// Whether a string is worth handing to yt-dlp: an http(s) URL with a host
+    /// Deliberately loose beyond that, since yt-dlp's generic extractor handles most sites
+    pub fn is_supported_url(url: &str) -> bool {
+        reqwest::Url::parse(url.trim()).is_ok_and(|u| {
+            matches!(u.scheme(), "http" | "https") && u.host_str().is_some_and(|h| !h.is_empty())
+        })
+    }
+
+    /// is_supported_url with a message for the user, so typos fail before yt-dlp runs
+    pub fn validate_url(url: &str) -> Result<(), String> {
+        if Self::is_supported_url(url) {
+            Ok(())
+        } else {
+            Err(format!(
+                "That doesn't look like a valid link: '{}'. Paste a full http:// or https:// address",
+                url.trim()
+            ))
+        }
+    }
+
+    /// Check a cookies source against the browsers yt-dlp can read cookies from
+    /// Accepts "browser" or "browser:profile"; the profile is passed through unchecked
+    pub fn validate_cookies_browser(spec: &str) -> Result<(), String> {
+        let (browser, profile) = match spec.split_once(':') {
+            Some((browser, profile)) => (browser, Some(profile)),
+            None => (spec, None),
+        };
+        if profile.is_some_and(|p| p.trim().is_empty()) {
+            return Err(format!("Missing profile name after '{}:'", browser));
+        }
+        if SUPPORTED_COOKIE_BROWSERS.contains(&browser) {
+            Ok(())
+        } else {
+            Err(format!(
+                "Unsupported cookies browser '{}'. Supported: {}",
+                browser,
+                SUPPORTED_COOKIE_BROWSERS.join(", ")
+            ))
+        }
+    }
+
+    /// Check an output container against what we can merge into
+    pub fn validate_output_container(container: &str, audio_only: bool) -> Result<(), String> {
+        if !SUPPORTED_OUTPUT_CONTAINERS.contains(&container) {
+            return Err(format!(
+                "Unsupported output container '{}'. Supported: {}",
+                container,
+                SUPPORTED_OUTPUT_CONTAINERS.join(", ")
+            ));
+        }
+        if audio_only {
+            return Err(format!(
+                "Audio-only downloads cannot be saved as {}; choose a video quality or drop the container",
+                container
+            ));
+        }
+        Ok(())
+    }
+
     /// Configure YouTube-specific args: Deno runtime for EJS and cookies/player client.
     ///
     /// With cookies, yt-dlp uses optimal authenticated defaults — don't override.
     /// Without cookies, exclude broken android_sdkless client (causes 403 errors).
     /// See: https://github.com/yt-dlp/yt-dlp/issues/15712
-    fn apply_youtube_args(cmd: &mut Command, cookies_browser: Option<&str>) {
+    /// The configured proxy and site login ride along, since every yt-dlp network call goes through here
+    fn apply_youtube_args(cmd: &mut Command, cookies_browser: Option<&str>, credentials: &Credentials) {
+        cmd.args(Self::youtube_args(cookies_browser, credentials));
+    }
+
+    fn youtube_args(cookies_browser: Option<&str>, credentials: &Credentials) -> Vec<String> {
+        let mut args = Vec::new();
+
         if let Ok(deno_path) = DenoManager::get_binary_path() {
             if deno_path.exists() {
-                cmd.args(["--js-runtimes", &format!("deno:{}", deno_path.display())]);
+                args.push("--js-runtimes".to_string());
+                args.push(format!("deno:{}", deno_path.display()));
             }
         }
 
         if let Some(browser) = cookies_browser {
-            cmd.args(["--cookies-from-browser", browser]);
+            args.push("--cookies-from-browser".to_string());
+            args.push(browser.to_string());
+        } else {
+            args.push("--extractor-args".to_string());
+            args.push("youtube:player_client=default,-android_sdkless".to_string());
+        }
+
+        if let Some(proxy) = crate::network::proxy() {
+            args.push("--proxy".to_string());
+            args.push(proxy);
+        }
+
+        args.extend(credentials.args());
+        args
+    }
+
+    /// Reword yt-dlp's login and sign-in errors so they aren't reported as plain download failures
+    pub fn auth_error(stderr: &str) -> Option<AppError> {
+        let lower = stderr.to_lowercase();
+        if lower.contains("incorrect username") || lower.contains("invalid password") || lower.contains("unable to login") || lower.contains("unable to log in") {
+            Some(AppError::AuthRequired("Authentication failed: the site rejected the username or password".to_string()))
+        } else if lower.contains("sign in to confirm")
+            || lower.contains("login required")
+            || lower.contains("members-only")
+            || lower.contains("available to this channel's members")
+            || lower.contains("--username")
+        {
+            Some(AppError::AuthRequired(
+                "Authentication required: this video needs a signed-in account (set cookies or a login in Settings)".to_string(),
+            ))
         } else {
-            cmd.args(["--extractor-args", "youtube:player_client=default,-android_sdkless"]);
+            None
         }
     }
 
+    /// Assemble the yt-dlp arguments for a download (everything after the program name)
+    /// Shared by start_download and the command preview
+    pub fn build_download_args(url: &str, options: &DownloadOptions) -> Vec<String> {
+        let output_template = options
+            .filename_template
+            .clone()
+            .unwrap_or_else(|| "%(title)s_%(id)s.%(ext)s".to_string());
+
+        let output_path = options.output_dir.join(&output_template);
+
+        let mut args: Vec<String> = [
+            "--newline",
+            "--progress",
+            "--no-warnings",
+            "--no-playlist",
+            "--print",
+            "after_move:AFTER_MOVE:%(filepath)s",
+            "-f",
+            &options.format,
+            "-o",
+            output_path.to_str().unwrap_or("%(title)s.%(ext)s"),
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        // Without it, yt-dlp keeps unicode titles and only strips characters the OS forbids
+        if options.restrict_filenames {
+            args.push("--restrict-filenames".to_string());
+        }
+
+        // The site's own subtitles go in during yt-dlp's merge, keeping their styling and
+        // saving a second ffmpeg pass. Transcribed subtitles are still embedded by Zinc afterwards
+        if options.embed_native_subs {
+            args.push("--write-subs".to_string());
+            args.push("--embed-subs".to_string());
+        }
+
+        // Audio presets other than "audio" convert the downloaded stream after the fact
+        if let Some(ref format) = options.audio_format {
+            args.push("--extract-audio".to_string());
+            args.push("--audio-format".to_string());
+            args.push(format.clone());
+            args.push("--audio-quality".to_string());
+            args.push(options.audio_quality.clone().unwrap_or_else(|| "0".to_string()));
+        }
+
+        // Set container format for merged output (video+audio)
+        if let Some(ref container) = options.output_container {
+            args.push("--merge-output-format".to_string());
+            args.push(container.clone());
+        }
+
+        // Bind to specific network interface
+        if let Some(ref addr) = options.source_address {
+            args.push("--source-address".to_string());
+            args.push(addr.clone());
+        }
+
+        // -f decides which formats are eligible (and tries its "/" alternatives left to right);
+        // -S only orders the formats each alternative matches
+        if let Some(sort) = options.format_sort.as_ref().filter(|s| !s.is_empty()) {
+            args.push("--format-sort".to_string());
+            args.push(sort.join(","));
+        }
+
+        // The selector already prefers streams under the cap; this stops the ones that aren't
+        if let Some(ref size) = options.max_filesize {
+            args.push("--max-filesize".to_string());
+            args.push(size.clone());
+        }
+
+        // Report the codecs yt-dlp actually selected, so fallbacks are visible
+        if options.preferred_vcodec.is_some() || options.preferred_acodec.is_some() {
+            args.push("--print".to_string());
+            args.push("before_dl:CODECS:%(vcodec)s/%(acodec)s".to_string());
+        }
+
+        args.extend(Self::youtube_args(options.cookies_browser.as_deref(), &options.credentials));
+        args.push(url.to_string());
+        args
+    }
+
     pub async fn check_installed() -> bool {
         let mut cmd = Command::new(Self::get_command());
         cmd.arg("--version")
@@ -136,7 +473,14 @@ impl YtDlp {
             .unwrap_or(false)
     }
 
-    pub async fn get_video_info(url: &str, cookies_browser: Option<&str>) -> Result<VideoInfo, String> {
+    pub async fn get_video_info(
+        url: &str,
+        cookies_browser: Option<&str>,
+        credentials: &Credentials,
+    ) -> Result<VideoInfo, AppError> {
+        Self::validate_url(url)?;
+        crate::network::ensure_online("fetching video info")?;
+
         let mut cmd = Command::new(Self::get_command());
         cmd.args([
             "--dump-json",
@@ -145,7 +489,7 @@ impl YtDlp {
             "--no-playlist",
         ]);
 
-        Self::apply_youtube_args(&mut cmd, cookies_browser);
+        Self::apply_youtube_args(&mut cmd, cookies_browser, credentials);
 
         cmd.arg(url);
 
@@ -159,7 +503,7 @@ impl YtDlp {
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(format!("yt-dlp error: {}", stderr));
+            return Err(Self::auth_error(&stderr).unwrap_or_else(|| format!("yt-dlp error: {}", stderr).into()));
         }
 
         let stdout = String::from_utf8_lossy(&output.stdout);
@@ -209,41 +553,15 @@ impl YtDlp {
         progress_tx: mpsc::Sender<DownloadProgress>,
         download_id: String,
         cancel_rx: watch::Receiver<bool>,
+        log_tx: mpsc::Sender<String>,
     ) -> Result<PathBuf, String> {
-        let output_template = options
-            .filename_template
-            .unwrap_or_else(|| "%(title)s_%(id)s.%(ext)s".to_string());
-
-        let output_path = options.output_dir.join(&output_template);
+        crate::network::ensure_online("downloading a video")?;
 
         let mut cmd = Command::new(Self::get_command());
-        cmd.args([
-            "--newline",
-            "--progress",
-            "--no-warnings",
-            "--no-playlist",
-            "--restrict-filenames",
-            "--print",
-            "after_move:AFTER_MOVE:%(filepath)s",
-            "-f",
-            &options.format,
-            "-o",
-            output_path.to_str().unwrap_or("%(title)s.%(ext)s"),
-        ]);
-
-        // Set container format for merged output (video+audio)
-        if let Some(ref container) = options.container_format {
-            cmd.args(["--merge-output-format", container]);
-        }
-
-        // Bind to specific network interface
-        if let Some(ref addr) = options.source_address {
-            cmd.args(["--source-address", addr]);
-        }
-
-        Self::apply_youtube_args(&mut cmd, options.cookies_browser.as_deref());
-
-        cmd.arg(url)
+        cmd.args(Self::build_download_args(url, &options))
+            // Piped output otherwise uses the Windows ANSI code page, which turns unicode
+            // file paths into '?' (or invalid UTF-8) before they reach final_filename
+            .env("PYTHONIOENCODING", "utf-8")
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
 
@@ -269,11 +587,32 @@ impl YtDlp {
         let merger_regex = Regex::new(r#"\[Merger\]\s+Merging formats into "(.+)""#).ok();
         let after_move_regex = Regex::new(r"^AFTER_MOVE:(.+)$").ok();
 
+        let mut selected_codec: Option<String> = None;
+        // yt-dlp skips a file over --max-filesize and still exits successfully
+        let mut over_size_cap = false;
+
         let mut final_filename: Option<String> = None;
         let mut cancel_rx = cancel_rx;
 
+        // stderr is read as it arrives so the task log stays current
+        let mut stderr_open = true;
+        let mut error_lines: Vec<String> = Vec::new();
+
         loop {
             tokio::select! {
+                line_result = stderr_reader.next_line(), if stderr_open => {
+                    match line_result {
+                        Ok(Some(line)) if !line.is_empty() => {
+                            let _ = log_tx.try_send(line.clone());
+                            error_lines.push(line);
+                            if error_lines.len() > 3 {
+                                error_lines.remove(0);
+                            }
+                        }
+                        Ok(Some(_)) => {}
+                        _ => stderr_open = false,
+                    }
+                }
                 // Check for cancellation
                 _ = cancel_rx.changed() => {
                     if *cancel_rx.borrow() {
@@ -295,35 +634,63 @@ impl YtDlp {
                                 final_filename = Some(filename);
                             }
 
+                            if line.contains("File is larger than max-filesize") {
+                                log::warn!("{}", line);
+                                over_size_cap = true;
+                            }
+
+                            if let Some(codecs) = line.strip_prefix("CODECS:") {
+                                log::info!("yt-dlp selected codecs: {}", codecs);
+                                selected_codec = Some(codecs.trim().to_string());
+                            }
+
                             if let Some(ref regex) = progress_regex {
                                 if let Some(caps) = regex.captures(&line) {
                                     let progress: f64 = caps[1].parse().unwrap_or(0.0);
                                     let _ = progress_tx
                                         .send(DownloadProgress {
                                             download_id: download_id.clone(),
-                                            status: "downloading".to_string(),
+                                            status: TaskStatus::Downloading,
                                             progress,
                                             speed: Some(caps[3].to_string()),
                                             eta: Some(caps[4].to_string()),
                                             filename: final_filename.clone(),
                                             total_bytes: None,
                                             downloaded_bytes: None,
+                                            codec: selected_codec.clone(),
                                         })
                                         .await;
                                 }
                             }
 
-                            if line.contains("[download] 100%") {
+                            // Merging/converting runs after the last byte arrives; the file
+                            // isn't usable until yt-dlp exits, so don't report completed here
+                            if is_postprocessor_line(&line) {
                                 let _ = progress_tx
                                     .send(DownloadProgress {
                                         download_id: download_id.clone(),
-                                        status: "completed".to_string(),
+                                        status: TaskStatus::Postprocessing,
                                         progress: 100.0,
                                         speed: None,
                                         eta: None,
                                         filename: final_filename.clone(),
                                         total_bytes: None,
                                         downloaded_bytes: None,
+                                        codec: selected_codec.clone(),
+                                    })
+                                    .await;
+                            } else if line.contains("[download] 100%") {
+                                let _ = progress_tx
+                                    .send(DownloadProgress {
+                                        download_id: download_id.clone(),
+                                        status: TaskStatus::Downloading,
+                                        progress: 100.0,
+                                        speed: None,
+                                        eta: None,
+                                        filename: final_filename.clone(),
+                                        total_bytes: None,
+                                        downloaded_bytes: None,
+                                        codec: selected_codec.clone(),
                                     })
                                     .await;
                             }
@@ -340,14 +707,19 @@ impl YtDlp {
             .await
             .map_err(|e| format!("Failed to wait for download: {}", e))?;
 
-        if !status.success() {
-            // Collect stderr for error details
-            let mut error_lines = Vec::new();
-            while let Ok(Some(line)) = stderr_reader.next_line().await {
-                if !line.is_empty() {
+        // Collect whatever stderr is left after stdout closed
+        while stderr_open {
+            match stderr_reader.next_line().await {
+                Ok(Some(line)) if !line.is_empty() => {
+                    let _ = log_tx.try_send(line.clone());
                     error_lines.push(line);
                 }
+                Ok(Some(_)) => {}
+                _ => stderr_open = false,
             }
+        }
+
+        if !status.success() {
             let error_msg = if error_lines.is_empty() {
                 "Download failed with unknown error".to_string()
             } else {
@@ -355,7 +727,14 @@ impl YtDlp {
                 let len = error_lines.len();
                 error_lines.into_iter().skip(len.saturating_sub(3)).collect::<Vec<_>>().join(" | ")
             };
-            return Err(error_msg);
+            return Err(Self::auth_error(&error_msg).map_or(error_msg, String::from));
+        }
+
+        if over_size_cap {
+            return Err(format!(
+                "No format of this video fits the {} size cap; raise the cap or pick a lower quality",
+                options.max_filesize.as_deref().unwrap_or_default()
+            ));
         }
 
         Ok(final_filename
@@ -363,16 +742,256 @@ impl YtDlp {
             .unwrap_or_else(|| options.output_dir))
     }
 
-    pub fn get_format_presets() -> HashMap<String, String> {
+    /// Check a --max-filesize value: a positive number with an optional K, M, G or T
+    /// unit, e.g. "200M" or "1.5G"
+    pub fn validate_max_filesize(size: &str) -> Result<(), String> {
+        let trimmed = size.trim();
+        let number = trimmed.strip_suffix(['b', 'B']).unwrap_or(trimmed);
+        let number = number.strip_suffix(['i', 'I']).unwrap_or(number);
+        let number = number.strip_suffix(['k', 'K', 'm', 'M', 'g', 'G', 't', 'T']).unwrap_or(number);
+        match number.parse::<f64>() {
+            Ok(n) if n > 0.0 && n.is_finite() => Ok(()),
+            _ => Err(format!(
+                "Invalid maximum file size '{}'. Use a number with an optional unit, e.g. 200M or 1.5G",
+                size
+            )),
+        }
+    }
+
+    /// Check --format-sort entries ("field", "+field" for ascending, "field:limit" or "field~target")
+    /// The field names are checked here; limits are left for yt-dlp to interpret
+    pub fn validate_format_sort(entries: &[String]) -> Result<(), String> {
+        for entry in entries {
+            let field = entry
+                .trim()
+                .trim_start_matches('+')
+                .split([':', '~'])
+                .next()
+                .unwrap_or_default();
+            if !FORMAT_SORT_FIELDS.contains(&field) {
+                return Err(format!(
+                    "Unsupported format sort field '{}'. Supported: {}",
+                    entry,
+                    FORMAT_SORT_FIELDS.join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check codec preferences against the codecs we know how to select
+    pub fn validate_codec_preference(vcodec: Option<&str>, acodec: Option<&str>) -> Result<(), String> {
+        if let Some(codec) = vcodec {
+            if vcodec_filter(codec).is_none() {
+                return Err(format!(
+                    "Unsupported video codec preference '{}'. Supported: {}",
+                    codec,
+                    SUPPORTED_VCODECS.join(", ")
+                ));
+            }
+        }
+        if let Some(codec) = acodec {
+            if acodec_filter(codec).is_none() {
+                return Err(format!(
+                    "Unsupported audio codec preference '{}'. Supported: {}",
+                    codec,
+                    SUPPORTED_ACODECS.join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a preset id downloads audio only
+    pub fn is_audio_preset(quality: &str) -> bool {
+        AUDIO_PRESETS.iter().any(|(id, ..)| *id == quality)
+    }
+
+    /// --audio-format and --audio-quality for an extracting audio preset
+    pub fn audio_extraction(quality: &str) -> Option<(String, String)> {
+        AUDIO_PRESETS
+            .iter()
+            .find(|(id, ..)| *id == quality)
+            .and_then(|(_, format, audio_quality, _)| Some((format.as_ref()?.to_string(), audio_quality.to_string())))
+    }
+
+    /// Format selectors for the named presets, preferring the given codecs
+    /// Each selector falls back to any codec when the preferred one isn't offered.
+    /// With a size cap, streams reported to fit it are tried first
+    pub fn get_format_presets(
+        preferred_vcodec: Option<&str>,
+        preferred_acodec: Option<&str>,
+        max_filesize: Option<&str>,
+    ) -> HashMap<String, String> {
+        let v = preferred_vcodec.and_then(vcodec_filter).unwrap_or_default();
+        let a = preferred_acodec.and_then(acodec_filter).unwrap_or_default();
+
+        // Filters are per stream, so a merged download can still total more than the cap;
+        // streams without a reported size pass. The uncapped alternatives stay last so that
+        // --max-filesize, not an unmatched selector, reports when nothing fits
+        let cap = max_filesize
+            .map(|size| format!("[filesize<=?{s}][filesize_approx<=?{s}]", s = size))
+            .unwrap_or_default();
+        let join = |alternatives: Vec<String>| {
+            if cap.is_empty() {
+                return alternatives.join("/");
+            }
+            let capped = alternatives.iter().map(|alternative| {
+                alternative
+                    .split('+')
+                    .map(|stream| format!("{}{}", stream, cap))
+                    .collect::<Vec<_>>()
+                    .join("+")
+            });
+            capped.chain(alternatives.iter().cloned()).collect::<Vec<_>>().join("/")
+        };
+
+        let video_selector = |height: &str| {
+            let mut alternatives = Vec::new();
+            if !v.is_empty() || !a.is_empty() {
+                alternatives.push(format!("bestvideo{h}{v}+bestaudio{a}", h = height));
+            }
+            if !v.is_empty() && !a.is_empty() {
+                // Keep the video codec even if the audio codec is unavailable
+                alternatives.push(format!("bestvideo{h}{v}+bestaudio", h = height));
+            }
+            alternatives.push(format!("bestvideo{h}+bestaudio", h = height));
+            alternatives.push(format!("best{h}", h = height));
+            join(alternatives)
+        };
+        let audio_selector = |filter: &str| {
+            let mut alternatives = Vec::new();
+            if !filter.is_empty() {
+                alternatives.push(format!("bestaudio{}", filter));
+            }
+            alternatives.push("bestaudio".to_string());
+            alternatives.push("best".to_string());
+            join(alternatives)
+        };
+
         let mut presets = HashMap::new();
-        presets.insert("best".to_string(), "bestvideo+bestaudio/best".to_string());
-        presets.insert("4k".to_string(), "bestvideo[height<=2160]+bestaudio/best[height<=2160]".to_string());
-        presets.insert("2k".to_string(), "bestvideo[height<=1440]+bestaudio/best[height<=1440]".to_string());
-        presets.insert("1080p".to_string(), "bestvideo[height<=1080]+bestaudio/best[height<=1080]".to_string());
-        presets.insert("720p".to_string(), "bestvideo[height<=720]+bestaudio/best[height<=720]".to_string());
-        presets.insert("480p".to_string(), "bestvideo[height<=480]+bestaudio/best[height<=480]".to_string());
-        presets.insert("audio".to_string(), "bestaudio/best".to_string());
-        presets.insert("mp3".to_string(), "bestaudio/best".to_string());
+        presets.insert("best".to_string(), video_selector(""));
+        presets.insert("4k".to_string(), video_selector("[height<=2160]"));
+        presets.insert("2k".to_string(), video_selector("[height<=1440]"));
+        presets.insert("1080p".to_string(), video_selector("[height<=1080]"));
+        presets.insert("720p".to_string(), video_selector("[height<=720]"));
+        presets.insert("480p".to_string(), video_selector("[height<=480]"));
+        for (id, _, _, source) in AUDIO_PRESETS {
+            // The extraction target's codec wins over the general preference
+            let filter = source.and_then(acodec_filter).unwrap_or(a);
+            presets.insert(id.to_string(), audio_selector(filter));
+        }
         presets
     }
+
+    /// Group formats into video-only, audio-only and combined streams
+    /// Formats with neither stream (storyboards) are dropped
+    pub fn group_formats(info: &VideoInfo) -> FormatDetails {
+        let by_height = |a: &VideoFormat, b: &VideoFormat| {
+            b.height()
+                .cmp(&a.height())
+                .then(b.fps.unwrap_or(0.0).total_cmp(&a.fps.unwrap_or(0.0)))
+        };
+
+        let mut video_only = Vec::new();
+        let mut audio_only = Vec::new();
+        let mut combined = Vec::new();
+        for format in &info.formats {
+            match (format.has_video(), format.has_audio()) {
+                (true, true) => combined.push(format.clone()),
+                (true, false) => video_only.push(format.clone()),
+                (false, true) => audio_only.push(format.clone()),
+                (false, false) => {}
+            }
+        }
+
+        video_only.sort_by(by_height);
+        combined.sort_by(by_height);
+        audio_only.sort_by(|a, b| b.abr.unwrap_or(0.0).total_cmp(&a.abr.unwrap_or(0.0)));
+
+        FormatDetails {
+            video_only,
+            audio_only,
+            combined,
+        }
+    }
+
+    /// Estimate the download size for a preset from the formats yt-dlp reported
+    /// Mirrors the presets: best video-only stream under the height cap plus the best
+    /// audio stream, falling back to the best combined stream. None when sizes are unknown
+    pub fn estimate_size(info: &VideoInfo, quality: &str) -> Option<SizeEstimate> {
+        let best_audio = info
+            .formats
+            .iter()
+            .filter(|f| f.has_audio() && !f.has_video())
+            .max_by(|a, b| a.abr.unwrap_or(0.0).total_cmp(&b.abr.unwrap_or(0.0)));
+
+        let bytes = if Self::is_audio_preset(quality) {
+            best_audio?.size()?
+        } else {
+            let max_height = match quality {
+                "4k" => Some(2160),
+                "2k" => Some(1440),
+                "1080p" => Some(1080),
+                "720p" => Some(720),
+                "480p" => Some(480),
+                _ => None, // "best" and unknown presets take the highest resolution
+            };
+            let fits = |f: &&VideoFormat| match (max_height, f.height()) {
+                (Some(max), Some(h)) => h <= max,
+                (Some(_), None) => false,
+                (None, _) => true,
+            };
+            let rank = |f: &VideoFormat| {
+                (
+                    f.height().unwrap_or(0),
+                    (f.fps.unwrap_or(0.0) * 1000.0) as u64,
+                    (f.vbr.unwrap_or(0.0) * 1000.0) as u64,
+                )
+            };
+
+            let best_video = info
+                .formats
+                .iter()
+                .filter(|f| f.has_video() && !f.has_audio())
+                .filter(fits)
+                .max_by_key(|f| rank(f));
+
+            let merged = best_video
+                .zip(best_audio)
+                .and_then(|(v, a)| Some(v.size()? + a.size()?));
+
+            match merged {
+                Some(bytes) => bytes,
+                None => info
+                    .formats
+                    .iter()
+                    .filter(|f| f.has_video() && f.has_audio())
+                    .filter(fits)
+                    .max_by_key(|f| rank(f))?
+                    .size()?,
+            }
+        };
+
+        Some(SizeEstimate {
+            bytes,
+            human: format_bytes(bytes),
+        })
+    }
+}
+
+/// Format a byte count like "1.4 GB"
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
 }
@@ -1,13 +1,20 @@
-use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 use tokio::process::Command;
 
 const APP_IDENTIFIER: &str = "com.zinc.app";
 
+/// How long a fetched latest version is trusted before asking GitHub again
+const LATEST_VERSION_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Last-known latest version per channel: (channel, version, fetched_at)
+static LATEST_VERSION_CACHE: Mutex<Vec<(String, String, Instant)>> = Mutex::new(Vec::new());
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "status")]
 pub enum YtDlpStatus {
@@ -93,27 +100,58 @@ impl YtDlpManager {
         Ok(version)
     }
 
-    /// Returns the GitHub repo path for the given channel
+    /// Returns the GitHub repo path for the given channel (unknown channels use stable)
     fn repo_for_channel(channel: &str) -> &'static str {
         match channel {
-            "stable" => "yt-dlp/yt-dlp",
+            "nightly" => "yt-dlp/yt-dlp-nightly-builds",
             "master" => "yt-dlp/yt-dlp-master-builds",
-            _ => "yt-dlp/yt-dlp-nightly-builds",
+            _ => "yt-dlp/yt-dlp",
         }
     }
 
-    /// Fetch the latest version from GitHub API
+    /// Get the latest version for a channel, hitting GitHub at most once per hour
     pub async fn get_latest_version(channel: &str) -> Result<String, String> {
+        if let Ok(cache) = LATEST_VERSION_CACHE.lock() {
+            if let Some((_, version, fetched_at)) = cache.iter().find(|(c, _, _)| c == channel) {
+                if fetched_at.elapsed() < LATEST_VERSION_TTL {
+                    return Ok(version.clone());
+                }
+            }
+        }
+
+        // Offline: a stale cached version is still better than an error
+        if crate::network::is_offline() {
+            return LATEST_VERSION_CACHE
+                .lock()
+                .ok()
+                .and_then(|cache| {
+                    cache
+                        .iter()
+                        .find(|(c, _, _)| c == channel)
+                        .map(|(_, version, _)| version.clone())
+                })
+                .ok_or_else(|| "Offline mode is on: checking for yt-dlp updates needs a network connection".to_string());
+        }
+
+        let version = Self::fetch_latest_version(channel).await?;
+
+        if let Ok(mut cache) = LATEST_VERSION_CACHE.lock() {
+            cache.retain(|(c, _, _)| c != channel);
+            cache.push((channel.to_string(), version.clone(), Instant::now()));
+        }
+
+        Ok(version)
+    }
+
+    /// Fetch the latest version from GitHub API
+    async fn fetch_latest_version(channel: &str) -> Result<String, String> {
         let repo = Self::repo_for_channel(channel);
         let url = format!("https://api.github.com/repos/{}/releases/latest", repo);
 
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(5))
-            .build()
-            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
-        let response = client
+        let response = crate::network::http_client()
             .get(&url)
             .header("User-Agent", "Zinc-App")
+            .timeout(std::time::Duration::from_secs(5))
             .send()
             .await
             .map_err(|e| format!("Failed to fetch latest version: {}", e))?;
@@ -200,7 +238,7 @@ impl YtDlpManager {
         } else {
             "yt-dlp_linux"
         };
-        format!("https://github.com/{}/releases/latest/download/{}", repo, binary)
+        crate::network::ytdlp_url(&format!("https://github.com/{}/releases/latest/download/{}", repo, binary))
     }
 
     /// Install yt-dlp by downloading from GitHub
@@ -216,15 +254,16 @@ impl YtDlpManager {
             .await
             .map_err(|e| format!("Failed to create bin directory: {}", e))?;
 
+        crate::network::ensure_online("installing yt-dlp")?;
+
         let download_url = Self::get_download_url(channel);
 
-        let client = reqwest::Client::new();
-        let response = client
-            .get(&download_url)
-            .header("User-Agent", "Zinc-App")
-            .send()
-            .await
-            .map_err(|e| format!("Failed to download yt-dlp: {}", e))?;
+        let client = crate::network::http_client();
+        let response = crate::network::send_with_retry(
+            client.get(&download_url).header("User-Agent", "Zinc-App"),
+        )
+        .await
+        .map_err(|e| format!("Failed to download yt-dlp: {}", e))?;
 
         if !response.status().is_success() {
             return Err(format!(
@@ -244,7 +283,7 @@ impl YtDlpManager {
         let mut downloaded: u64 = 0;
         let mut stream = response.bytes_stream();
 
-        while let Some(chunk) = stream.next().await {
+        while let Some(chunk) = crate::network::next_chunk(&mut stream).await? {
             let chunk = chunk.map_err(|e| format!("Download error: {}", e))?;
             file.write_all(&chunk)
                 .await
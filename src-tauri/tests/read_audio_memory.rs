@@ -0,0 +1,99 @@
+// Peak memory of WAV loading, measured with a counting global allocator
+// Lives in its own test binary so the crate's other tests keep the system allocator
+
+use std::path::Path;
+use zinc_lib::WhisperRsEngine;
+
+/// Counts heap bytes per thread so a test can measure its own peak while others run
+struct PeakAlloc;
+
+thread_local! {
+    static HEAP: std::cell::Cell<isize> = const { std::cell::Cell::new(0) };
+    static PEAK: std::cell::Cell<isize> = const { std::cell::Cell::new(0) };
+}
+
+fn track(delta: isize) {
+    let _ = HEAP.try_with(|heap| {
+        heap.set(heap.get() + delta);
+        let _ = PEAK.try_with(|peak| peak.set(peak.get().max(heap.get())));
+    });
+}
+
+unsafe impl std::alloc::GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+        let ptr = std::alloc::System.alloc(layout);
+        if !ptr.is_null() {
+            track(layout.size() as isize);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+        std::alloc::System.dealloc(ptr, layout);
+        track(-(layout.size() as isize));
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: std::alloc::Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = std::alloc::System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            track(new_size as isize - layout.size() as isize);
+        }
+        new_ptr
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakAlloc = PeakAlloc;
+
+/// Peak heap growth on this thread while `f` runs
+fn peak_heap<T>(f: impl FnOnce() -> T) -> (T, isize) {
+    let start = HEAP.with(|heap| heap.get());
+    PEAK.with(|peak| peak.set(start));
+    let result = f();
+    (result, PEAK.with(|peak| peak.get()) - start)
+}
+
+/// load_audio before it streamed the downmix: every interleaved sample, then a mono copy
+fn read_audio_buffered(path: &Path) -> Vec<f32> {
+    let reader = hound::WavReader::open(path).unwrap();
+    let channels = reader.spec().channels as usize;
+    let samples: Vec<f32> = reader
+        .into_samples::<i16>()
+        .filter_map(|s| s.ok())
+        .map(|s| s as f32 / 32768.0)
+        .collect();
+    samples
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
+
+#[test]
+fn streamed_downmix_keeps_peak_memory_to_the_mono_output() {
+    // A minute of 16kHz stereo: 7.7MB as interleaved f32, 3.8MB once mono
+    let path = std::env::temp_dir().join(format!("zinc-test-{}.wav", uuid::Uuid::new_v4()));
+    let spec = hound::WavSpec {
+        channels: 2,
+        sample_rate: 16000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for i in 0..16000 * 60 {
+        let sample = ((i % 200) as i16 - 100) * 300;
+        writer.write_sample(sample).unwrap();
+        writer.write_sample(-sample).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let (before, before_peak) = peak_heap(|| read_audio_buffered(&path));
+    let (after, after_peak) = peak_heap(|| WhisperRsEngine::read_audio(&path).unwrap());
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(before.len(), after.len());
+    let mono_bytes = (after.len() * std::mem::size_of::<f32>()) as isize;
+    // The streamed read holds little beyond its output; the buffered one held the
+    // interleaved copy too (at least 3x the output)
+    assert!(after_peak < mono_bytes + mono_bytes / 10, "{} bytes", after_peak);
+    assert!(before_peak >= 3 * mono_bytes, "{} bytes", before_peak);
+}
@@ -10,6 +10,7 @@ import { useDownload } from './hooks/useDownload';
 import { useSettings } from './hooks/useSettings';
 import { useToast } from './hooks/useToast';
 import { getVideoInfo, getYtdlpStatus, getYtdlpStatusFast, installYtdlp, onYtdlpInstallProgress } from './lib/tauri';
+import { getErrorCode, getErrorMessage } from './lib/utils';
 // Dark theme is now the only theme - no light mode support
 import type { VideoInfo, YtDlpStatus, YtDlpInstallProgress, SubtitleSettings } from './lib/types';
 
@@ -33,6 +34,7 @@ function App() {
     hasCompletedDownloads,
     startDownload,
     cancelDownload,
+    cancelAll,
     clearDownload,
     clearCompleted,
     addLocalTranscription,
@@ -103,7 +105,7 @@ function App() {
       const info = await getVideoInfo(url);
       setVideoInfo(info);
     } catch (err) {
-      error(err instanceof Error ? err.message : 'Failed to get video info');
+      error(getErrorMessage(err, 'Failed to get video info'));
     } finally {
       setIsLoadingInfo(false);
     }
@@ -123,7 +125,20 @@ function App() {
         setDownloadTransitionPending(true);
       }
 
-      await startDownload(videoInfo, format, subtitleSettings);
+      // Over the configured transcription length, or already in the queue: ask once for
+      // each, then retry past that check
+      const start = async (overrideDurationGuard = false, forceDuplicate = false): Promise<string> => {
+        try {
+          return await startDownload(videoInfo, format, subtitleSettings, null, overrideDurationGuard, forceDuplicate);
+        } catch (err) {
+          const code = getErrorCode(err);
+          if ((code !== 'confirmation_required' && code !== 'already_queued') || !window.confirm(`${getErrorMessage(err, '')}. Continue?`)) {
+            throw err;
+          }
+          return start(overrideDurationGuard || code === 'confirmation_required', forceDuplicate || code === 'already_queued');
+        }
+      };
+      await start();
       success(`Started downloading "${videoInfo.title}"`);
       setVideoInfo(null);
     } catch (err) {
@@ -132,10 +147,25 @@ function App() {
         setShowDownloadsDelayed(true);
         setDownloadTransitionPending(false);
       }
-      error(err instanceof Error ? err.message : 'Failed to start download');
+      error(getErrorMessage(err, 'Failed to start download'));
     }
   }, [videoInfo, startDownload, success, error, downloads.length]);
 
+  const handleStartLocalTranscription = useCallback(async (taskId: string) => {
+    try {
+      try {
+        await startLocalTranscription(taskId);
+      } catch (err) {
+        if (getErrorCode(err) !== 'confirmation_required' || !window.confirm(`${getErrorMessage(err, '')}. Continue?`)) {
+          throw err;
+        }
+        await startLocalTranscription(taskId, true);
+      }
+    } catch (err) {
+      error(getErrorMessage(err, 'Failed to start transcription'));
+    }
+  }, [startLocalTranscription, error]);
+
   // Track if card exit animation has completed (for sequenced animation)
   const [cardExitComplete, setCardExitComplete] = useState(true);
 
@@ -166,15 +196,26 @@ function App() {
 
   const handleLocalFile = useCallback(async (filePath: string) => {
     setVideoInfo(null); // Close any open video preview
+    const add = (forceDuplicate?: boolean) => addLocalTranscription(
+      filePath,
+      config?.transcription_engine ?? 'whisper_rs',
+      config?.transcription_model ?? 'base',
+      'sentence',  // Default style
+      null,
+      null,
+      forceDuplicate
+    );
     try {
-      await addLocalTranscription(
-        filePath,
-        config?.transcription_engine ?? 'whisper_rs',
-        config?.transcription_model ?? 'base',
-        'sentence'  // Default style
-      );
+      try {
+        await add();
+      } catch (err) {
+        if (getErrorCode(err) !== 'already_queued' || !window.confirm(`${getErrorMessage(err, '')}. Add it again?`)) {
+          throw err;
+        }
+        await add(true);
+      }
     } catch (err) {
-      error(err instanceof Error ? err.message : 'Failed to add transcription task');
+      error(getErrorMessage(err, 'Failed to add transcription task'));
     }
   }, [addLocalTranscription, config, error]);
 
@@ -349,10 +390,11 @@ function App() {
               <DownloadsSection
                 downloads={downloads}
                 onCancel={cancelDownload}
+                onCancelAll={cancelAll}
                 onClear={clearDownload}
                 onClearCompleted={clearCompleted}
                 hasCompletedDownloads={hasCompletedDownloads}
-                onStartLocalTranscription={startLocalTranscription}
+                onStartLocalTranscription={handleStartLocalTranscription}
                 onUpdateTranscriptionSettings={updateTranscriptionSettings}
               />
             )}
@@ -51,7 +51,8 @@ export const DownloadCard = memo(function DownloadCard({
     if (download.output_path) onOpenFolder(download.output_path);
   }, [onOpenFolder, download.output_path]);
 
-  const isDownloading = download.status === 'downloading' || download.status === 'pending';
+  const isPostprocessing = download.status === 'postprocessing';
+  const isDownloading = download.status === 'downloading' || download.status === 'pending' || isPostprocessing;
   const isTranscribing = download.status === 'transcribing' || download.status.startsWith('transcribing:');
   const isActive = isDownloading || isTranscribing;
   const isCompleted = download.status === 'completed';
@@ -125,7 +126,7 @@ export const DownloadCard = memo(function DownloadCard({
         {isDownloading && (
           <div className="absolute bottom-0 left-0 right-0 h-1 bg-black/50">
             <motion.div
-              className="h-full progress-shimmer"
+              className={cn('h-full progress-shimmer', isPostprocessing && 'animate-pulse')}
               initial={{ width: 0 }}
               animate={{ width: `${download.progress}%` }}
               transition={{ duration: 0.3, ease: 'easeOut' }}
@@ -208,7 +209,9 @@ export const DownloadCard = memo(function DownloadCard({
         {/* Active download info */}
         {isDownloading && (
           <div className="flex items-center justify-between text-xs text-text-secondary mt-2">
-            <span className="tabular-nums font-medium text-accent">{download.progress.toFixed(1)}%</span>
+            <span className="tabular-nums font-medium text-accent">
+              {isPostprocessing ? 'Finalizing...' : `${download.progress.toFixed(1)}%`}
+            </span>
             <span className="flex items-center gap-2">
               {download.speed && <span className="tabular-nums">{download.speed}</span>}
               {download.eta && <span className="tabular-nums text-text-tertiary">ETA {download.eta}</span>}
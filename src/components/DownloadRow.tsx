@@ -3,7 +3,7 @@ import { motion, AnimatePresence } from 'motion/react';
 import type { Download, TranscriptionEngine } from '@/lib/types';
 import { cn } from '@/lib/utils';
 import { getSpeedMultiplier } from '@/lib/constants';
-import { getTranscriptionEngines } from '@/lib/tauri';
+import { getDownloadLog, getTranscriptionEngines } from '@/lib/tauri';
 import { XIcon, FolderIcon, PlayIcon, TrashIcon, ChevronDownIcon, SubtitlesIcon, CheckIcon } from './Icons';
 
 const TRANSCRIBE_STAGE_MESSAGES: Record<string, string> = {
@@ -48,7 +48,8 @@ export const DownloadRow = memo(function DownloadRow({
   onUpdateTranscriptionSettings,
 }: DownloadRowProps) {
   const isPendingLocalTranscribe = download.task_type === 'local_transcribe' && download.status === 'pending';
-  const isDownloading = download.task_type === 'download' && (download.status === 'downloading' || download.status === 'pending');
+  const isPostprocessing = download.status === 'postprocessing';
+  const isDownloading = download.task_type !== 'local_transcribe' && (download.status === 'downloading' || download.status === 'pending' || isPostprocessing);
   const isTranscribing = download.status === 'transcribing' || download.status.startsWith('transcribing:');
   const isActive = isDownloading || isTranscribing;
 
@@ -106,6 +107,16 @@ export const DownloadRow = memo(function DownloadRow({
     return currentEngine?.models.filter(m => m.installed) || [];
   }, [currentEngine]);
 
+  // Captured yt-dlp/ffmpeg output, loaded on demand for failed items
+  const [logLines, setLogLines] = useState<string[] | null>(null);
+  const handleToggleLog = useCallback(() => {
+    if (logLines) {
+      setLogLines(null);
+      return;
+    }
+    getDownloadLog(download.id).then(setLogLines).catch(() => setLogLines([]));
+  }, [logLines, download.id]);
+
   const handleCancel = useCallback(() => onCancel(download.id), [onCancel, download.id]);
   const handleClear = useCallback(() => onClear(download.id), [onClear, download.id]);
   const handleOpenFile = useCallback(() => {
@@ -164,6 +175,7 @@ export const DownloadRow = memo(function DownloadRow({
   // Status text for collapsed view - combined progress + speed
   const statusText = useMemo(() => {
     if (isPendingLocalTranscribe) return 'Ready';
+    if (isPostprocessing) return 'Finalizing...';
     if (isDownloading) {
       const pct = `${download.progress.toFixed(1)}%`;
       return download.speed ? `${pct} ${download.speed}` : pct;
@@ -179,7 +191,7 @@ export const DownloadRow = memo(function DownloadRow({
     if (isError) return formatErrorMessage(download.error || 'Error');
     if (isCancelled) return 'Cancelled';
     return '';
-  }, [isPendingLocalTranscribe, isDownloading, isTranscribing, isCompleted, isError, isCancelled, download.speed, download.progress, download.transcription_message, download.transcription_progress, transcribeStage, download.error]);
+  }, [isPendingLocalTranscribe, isPostprocessing, isDownloading, isTranscribing, isCompleted, isError, isCancelled, download.speed, download.progress, download.transcription_message, download.transcription_progress, transcribeStage, download.error]);
 
   return (
     <motion.div
@@ -207,7 +219,8 @@ export const DownloadRow = memo(function DownloadRow({
             className={cn(
               'progress-bar-inline-fill',
               isDownloading && 'downloading',
-              isTranscribing && 'transcribing'
+              isTranscribing && 'transcribing',
+              isPostprocessing && 'animate-pulse'
             )}
             initial={{ width: 0 }}
             animate={{ width: `${progressValue}%` }}
@@ -253,7 +266,8 @@ export const DownloadRow = memo(function DownloadRow({
                 handleOpenFile();
               }}
               className="p-1.5 rounded-md hover:bg-white/10 text-text-secondary hover:text-text-primary transition-colors"
-              aria-label="Play file"
+              aria-label={download.in_place ? 'Play file' : 'Open subtitled copy'}
+              title={download.in_place ? undefined : 'Open subtitled copy'}
             >
               <PlayIcon className="w-4 h-4" />
             </button>
@@ -340,7 +354,16 @@ export const DownloadRow = memo(function DownloadRow({
                   {/* Completed info */}
                   {isCompleted && (
                     <p className="text-xs text-text-tertiary">
-                      {download.task_type === 'local_transcribe' ? 'Subtitles added' : download.format.toUpperCase()}
+                      {download.task_type === 'local_transcribe'
+                        ? (download.in_place ? 'Subtitles added' : 'Subtitled copy created')
+                        : download.format.toUpperCase()}
+                      {download.codec && (
+                        <span className="ml-2">{download.codec}</span>
+                      )}
+                      {/* Note left when transcription was skipped (e.g. no audio track) */}
+                      {download.transcription_progress == null && download.transcription_message && (
+                        <span className="ml-2">{download.transcription_message}</span>
+                      )}
                       {download.error && (
                         <span className="ml-2 text-warning">{download.error}</span>
                       )}
@@ -362,6 +385,23 @@ export const DownloadRow = memo(function DownloadRow({
                 </div>
               </div>
 
+              {/* Log details for failed items */}
+              {(isError || (isCompleted && download.error)) && (
+                <div className="flex flex-col gap-2">
+                  <button
+                    onClick={handleToggleLog}
+                    className="self-start text-xs text-text-secondary hover:text-text-primary transition-colors"
+                  >
+                    {logLines ? 'Hide details' : 'Details'}
+                  </button>
+                  {logLines && (
+                    <pre className="max-h-40 overflow-auto rounded-md bg-bg-tertiary p-2 text-[11px] leading-snug text-text-tertiary whitespace-pre-wrap break-all">
+                      {logLines.length > 0 ? logLines.join('\n') : 'No output was captured for this task.'}
+                    </pre>
+                  )}
+                </div>
+              )}
+
               {/* Engine/Model selectors and Start button for pending local transcription */}
               {isPendingLocalTranscribe && availableEngines.length > 0 && (
                 <div className="flex flex-wrap items-center gap-2">
@@ -8,6 +8,7 @@ import { ChevronDownIcon, TrashIcon } from './Icons';
 interface DownloadsSectionProps {
   downloads: Download[];
   onCancel: (id: string) => void;
+  onCancelAll: () => void;
   onClear: (id: string) => void;
   onClearCompleted: () => void;
   hasCompletedDownloads: boolean;
@@ -18,6 +19,7 @@ interface DownloadsSectionProps {
 export function DownloadsSection({
   downloads,
   onCancel,
+  onCancelAll,
   onClear,
   onClearCompleted,
   hasCompletedDownloads,
@@ -26,6 +28,13 @@ export function DownloadsSection({
 }: DownloadsSectionProps) {
   const [isExpanded, setIsExpanded] = useState(true);
 
+  const hasRunningTasks = downloads.some(d =>
+    (d.task_type !== 'local_transcribe' && d.status === 'pending') ||
+    d.status === 'downloading' ||
+    d.status === 'postprocessing' ||
+    d.status.startsWith('transcribing')
+  );
+
   const handleOpenFile = useCallback(async (path: string) => {
     try {
       await openFile(path);
@@ -68,16 +77,28 @@ export function DownloadsSection({
           <span className="text-text-tertiary">({downloads.length})</span>
         </button>
 
-        {/* Clear completed button */}
-        {hasCompletedDownloads && (
-          <button
-            onClick={onClearCompleted}
-            className="flex items-center gap-1.5 px-2.5 py-1 text-xs text-text-tertiary hover:text-text-secondary hover:bg-bg-tertiary rounded-md transition-colors"
-          >
-            <TrashIcon className="w-3.5 h-3.5" />
-            <span>Clear completed</span>
-          </button>
-        )}
+        <div className="flex items-center gap-1">
+          {/* Stop all button */}
+          {hasRunningTasks && (
+            <button
+              onClick={onCancelAll}
+              className="flex items-center gap-1.5 px-2.5 py-1 text-xs text-text-tertiary hover:text-text-secondary hover:bg-bg-tertiary rounded-md transition-colors"
+            >
+              <span>Stop all</span>
+            </button>
+          )}
+
+          {/* Clear completed button */}
+          {hasCompletedDownloads && (
+            <button
+              onClick={onClearCompleted}
+              className="flex items-center gap-1.5 px-2.5 py-1 text-xs text-text-tertiary hover:text-text-secondary hover:bg-bg-tertiary rounded-md transition-colors"
+            >
+              <TrashIcon className="w-3.5 h-3.5" />
+              <span>Clear completed</span>
+            </button>
+          )}
+        </div>
       </div>
 
       {/* Download list */}
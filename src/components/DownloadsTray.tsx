@@ -42,7 +42,7 @@ export function DownloadsTray({
     return null;
   }
 
-  const activeCount = downloads.filter(d => d.status === 'downloading' || d.status === 'pending' || d.status === 'transcribing' || d.status.startsWith('transcribing:')).length;
+  const activeCount = downloads.filter(d => d.status === 'downloading' || d.status === 'postprocessing' || d.status === 'pending' || d.status === 'transcribing' || d.status.startsWith('transcribing:')).length;
 
   return (
     <motion.div
@@ -1,8 +1,8 @@
 import { useState, useCallback, useEffect } from 'react';
 import { motion, AnimatePresence } from 'motion/react';
-import type { AppConfig, YtDlpStatus, YtDlpInstallProgress, DenoStatus, WhisperStatus, TranscriptionEngine, TranscriptionInstallProgress, NetworkInterface } from '@/lib/types';
-import { selectDirectory, getYtdlpStatus, updateYtdlp, checkYtdlpUpdate, onYtdlpInstallProgress, getDenoStatus, installDeno, onDenoInstallProgress, getWhisperStatus, checkFfmpeg, getTranscriptionEngines, downloadTranscriptionModel, onTranscriptionInstallProgress, listNetworkInterfaces } from '@/lib/tauri';
-import { cn, truncate } from '@/lib/utils';
+import type { AppConfig, YtDlpStatus, YtDlpInstallProgress, DenoStatus, WhisperStatus, FfmpegInfo, TranscriptionEngine, TranscriptionInstallProgress, NetworkInterface } from '@/lib/types';
+import { selectDirectory, getYtdlpStatus, updateYtdlp, checkYtdlpUpdate, onYtdlpInstallProgress, getDenoStatus, installDeno, onDenoInstallProgress, getWhisperStatus, getFfmpegInfo, installFfmpeg, onFfmpegInstallProgress, getTranscriptionEngines, downloadTranscriptionModel, cancelModelDownload, onTranscriptionInstallProgress, listNetworkInterfaces, setAuthPassword } from '@/lib/tauri';
+import { cn, formatBytes, getErrorCode, getErrorMessage, truncate } from '@/lib/utils';
 import { QUALITY_PRESETS, FORMAT_OPTIONS } from '@/lib/constants';
 import { FolderIcon, XIcon, ChevronDownIcon, RefreshIcon, CheckIcon, LoaderIcon, DownloadIcon } from './Icons';
 import { ProgressBar } from './ProgressBar';
@@ -30,7 +30,9 @@ export function Settings({ isOpen, onClose, config, onSave }: SettingsProps) {
 
   // Whisper state (legacy - kept for existing installations display)
   const [whisperStatus, setWhisperStatus] = useState<WhisperStatus | null>(null);
-  const [hasFfmpeg, setHasFfmpeg] = useState<boolean | null>(null);
+  const [ffmpegInfo, setFfmpegInfo] = useState<FfmpegInfo | null>(null);
+  const [isInstallingFfmpeg, setIsInstallingFfmpeg] = useState(false);
+  const [ffmpegProgress, setFfmpegProgress] = useState<YtDlpInstallProgress | null>(null);
 
   // Transcription engine state
   const [engines, setEngines] = useState<TranscriptionEngine[]>([]);
@@ -42,6 +44,9 @@ export function Settings({ isOpen, onClose, config, onSave }: SettingsProps) {
   const [networkInterfaces, setNetworkInterfaces] = useState<NetworkInterface[]>([]);
   const [isNetworkDropdownOpen, setIsNetworkDropdownOpen] = useState(false);
 
+  // cookies_browser is "browser" or "browser:profile"
+  const [cookiesBrowser, cookiesProfile = ''] = (config.cookies_browser ?? '').split(/:(.*)/s);
+
   // Fetch yt-dlp status when settings open
   useEffect(() => {
     if (isOpen) {
@@ -53,7 +58,7 @@ export function Settings({ isOpen, onClose, config, onSave }: SettingsProps) {
       }).catch(() => {});
       getDenoStatus().then(setDenoStatus).catch(() => {});
       getWhisperStatus().then(setWhisperStatus).catch(() => {});
-      checkFfmpeg().then(setHasFfmpeg).catch(() => setHasFfmpeg(false));
+      getFfmpegInfo().then(setFfmpegInfo).catch(() => setFfmpegInfo({ found: false, path: null, version: null, install_hint: null }));
       getTranscriptionEngines().then(setEngines).catch(() => {});
       listNetworkInterfaces().then(setNetworkInterfaces).catch(() => setNetworkInterfaces([]));
       setAvailableUpdate(null);
@@ -92,6 +97,21 @@ export function Settings({ isOpen, onClose, config, onSave }: SettingsProps) {
     };
   }, []);
 
+  // Listen for ffmpeg install progress
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+
+    onFfmpegInstallProgress((progress) => {
+      setFfmpegProgress(progress);
+    }).then((fn) => {
+      unlisten = fn;
+    });
+
+    return () => {
+      unlisten?.();
+    };
+  }, []);
+
   // Listen for transcription engine install/download progress
   useEffect(() => {
     let unlisten: (() => void) | undefined;
@@ -136,6 +156,21 @@ export function Settings({ isOpen, onClose, config, onSave }: SettingsProps) {
     }
   }, []);
 
+  const handleInstallFfmpeg = useCallback(async () => {
+    setIsInstallingFfmpeg(true);
+    setFfmpegProgress(null);
+    try {
+      await installFfmpeg();
+      const info = await getFfmpegInfo();
+      setFfmpegInfo(info);
+    } catch (err) {
+      console.error('Failed to install ffmpeg:', err);
+    } finally {
+      setIsInstallingFfmpeg(false);
+      setFfmpegProgress(null);
+    }
+  }, []);
+
   const handleInstallDeno = useCallback(async () => {
     setIsInstallingDeno(true);
     setDenoProgress(null);
@@ -177,11 +212,17 @@ export function Settings({ isOpen, onClose, config, onSave }: SettingsProps) {
     await onSave({ generate_subtitles: !config.generate_subtitles });
   }, [onSave, config.generate_subtitles]);
 
+  const handleOfflineToggle = useCallback(async () => {
+    await onSave({ offline: !config.offline });
+  }, [onSave, config.offline]);
+
   // Transcription engine handlers
   const handleEngineChange = useCallback(async (engineId: string) => {
-    // Find the engine and its first installed model
+    // Prefer the model last used with this engine, else its first installed model
     const engine = engines.find(e => e.id === engineId);
-    const installedModel = engine?.models.find(m => m.installed);
+    const installedModel =
+      engine?.models.find(m => m.installed && m.id === config.engine_models[engineId]) ??
+      engine?.models.find(m => m.installed);
 
     // Save both engine and model in a single call to avoid race conditions
     const updates: Partial<AppConfig> = { transcription_engine: engineId };
@@ -190,7 +231,7 @@ export function Settings({ isOpen, onClose, config, onSave }: SettingsProps) {
     }
 
     await onSave(updates);
-  }, [onSave, engines]);
+  }, [onSave, engines, config.engine_models]);
 
   const handleEngineModelChange = useCallback(async (modelId: string) => {
     await onSave({ transcription_model: modelId });
@@ -205,15 +246,25 @@ export function Settings({ isOpen, onClose, config, onSave }: SettingsProps) {
       const updatedEngines = await getTranscriptionEngines();
       setEngines(updatedEngines);
     } catch (err) {
-      console.error('Failed to download engine model:', err);
-      const errorMessage = err instanceof Error ? err.message : String(err);
-      setDownloadError(errorMessage);
+      if (getErrorCode(err) !== 'cancelled') {
+        console.error('Failed to download engine model:', err);
+        setDownloadError(getErrorMessage(err, 'Failed to download model'));
+      }
     } finally {
       setIsDownloadingEngineModel(null);
       setEngineProgress(null);
     }
   }, []);
 
+  const handleCancelEngineModelDownload = useCallback(async () => {
+    if (!isDownloadingEngineModel) return;
+    try {
+      await cancelModelDownload(isDownloadingEngineModel.engine, isDownloadingEngineModel.model);
+    } catch (err) {
+      console.error('Failed to cancel model download:', err);
+    }
+  }, [isDownloadingEngineModel]);
+
   const handleNetworkInterfaceChange = useCallback(async (ipv4: string | null) => {
     await onSave({ network_interface: ipv4 });
     setIsNetworkDropdownOpen(false);
@@ -289,6 +340,19 @@ export function Settings({ isOpen, onClose, config, onSave }: SettingsProps) {
                   </span>
                   <ChevronDownIcon className="w-4 h-4 text-text-tertiary shrink-0 rotate-[-90deg]" />
                 </button>
+                <label className="flex items-center gap-2 mt-2 px-1 text-sm text-text-primary">
+                  <input
+                    type="checkbox"
+                    checked={config.restrict_filenames}
+                    onChange={async (e) => {
+                      await onSave({ restrict_filenames: e.target.checked });
+                    }}
+                  />
+                  ASCII-only filenames
+                </label>
+                <p className="mt-1 px-1 text-xs text-text-tertiary">
+                  Replaces accents and non-Latin characters with underscores. Turn off to keep titles in their original script.
+                </p>
               </section>
 
               {/* Default Quality */}
@@ -509,9 +573,10 @@ export function Settings({ isOpen, onClose, config, onSave }: SettingsProps) {
                       </label>
                       <div className="px-4 py-3 bg-bg-tertiary rounded-lg space-y-2">
                         <select
-                          value={config.cookies_browser ?? ''}
+                          value={cookiesBrowser}
                           onChange={async (e) => {
-                            const value = e.target.value || null;
+                            const browser = e.target.value;
+                            const value = browser ? (cookiesProfile ? `${browser}:${cookiesProfile}` : browser) : null;
                             await onSave({ cookies_browser: value });
                           }}
                           className="w-full bg-bg-secondary text-text-primary text-sm rounded-lg px-3 py-2 border border-border focus:border-accent focus:outline-none"
@@ -525,6 +590,22 @@ export function Settings({ isOpen, onClose, config, onSave }: SettingsProps) {
                           <option value="vivaldi">Vivaldi</option>
                           <option value="safari">Safari</option>
                         </select>
+                        {cookiesBrowser && (
+                          <input
+                            type="text"
+                            key={config.cookies_browser ?? ''}
+                            defaultValue={cookiesProfile}
+                            placeholder="Profile (optional, e.g. work)"
+                            onBlur={async (e) => {
+                              const profile = e.target.value.trim();
+                              const value = profile ? `${cookiesBrowser}:${profile}` : cookiesBrowser;
+                              if (value !== config.cookies_browser) {
+                                await onSave({ cookies_browser: value });
+                              }
+                            }}
+                            className="w-full bg-bg-secondary text-text-primary text-sm rounded-lg px-3 py-2 border border-border focus:border-accent focus:outline-none"
+                          />
+                        )}
                         <p className="text-xs text-text-tertiary">
                           Pass browser cookies to yt-dlp for higher quality YouTube downloads. Requires the selected browser to be logged into YouTube.
                         </p>
@@ -536,6 +617,96 @@ export function Settings({ isOpen, onClose, config, onSave }: SettingsProps) {
                       </div>
                     </div>
 
+                    {/* Site Login */}
+                    <div>
+                      <label className="block text-xs font-medium text-text-secondary px-1 mb-2">
+                        Site Login
+                      </label>
+                      <div className="px-4 py-3 bg-bg-tertiary rounded-lg space-y-2">
+                        <label className="flex items-center gap-2 text-sm text-text-primary">
+                          <input
+                            type="checkbox"
+                            checked={config.use_netrc}
+                            onChange={async (e) => {
+                              await onSave({ use_netrc: e.target.checked });
+                            }}
+                          />
+                          Use .netrc file
+                        </label>
+                        {!config.use_netrc && (
+                          <div className="grid grid-cols-2 gap-2">
+                            <input
+                              type="text"
+                              key={config.auth_username ?? ''}
+                              defaultValue={config.auth_username ?? ''}
+                              placeholder="Username"
+                              autoComplete="off"
+                              onBlur={async (e) => {
+                                const value = e.target.value.trim() || null;
+                                if (value !== config.auth_username) {
+                                  await onSave({ auth_username: value });
+                                }
+                              }}
+                              className="w-full bg-bg-secondary text-text-primary text-sm rounded-lg px-3 py-2 border border-border focus:border-accent focus:outline-none"
+                            />
+                            <input
+                              type="password"
+                              placeholder="Password"
+                              autoComplete="off"
+                              onBlur={async (e) => {
+                                await setAuthPassword(e.target.value || null);
+                              }}
+                              className="w-full bg-bg-secondary text-text-primary text-sm rounded-lg px-3 py-2 border border-border focus:border-accent focus:outline-none"
+                            />
+                          </div>
+                        )}
+                        <p className="text-xs text-text-tertiary">
+                          For age-restricted or members-only videos that cookies don't cover. The password is kept until Zinc closes and is never saved.
+                        </p>
+                      </div>
+                    </div>
+
+                    {/* Codec Preference */}
+                    <div>
+                      <label className="block text-xs font-medium text-text-secondary px-1 mb-2">
+                        Preferred Codecs
+                      </label>
+                      <div className="px-4 py-3 bg-bg-tertiary rounded-lg space-y-2">
+                        <div className="grid grid-cols-2 gap-2">
+                          <select
+                            value={config.preferred_vcodec ?? ''}
+                            onChange={async (e) => {
+                              const value = (e.target.value || null) as AppConfig['preferred_vcodec'];
+                              await onSave({ preferred_vcodec: value });
+                            }}
+                            className="w-full bg-bg-secondary text-text-primary text-sm rounded-lg px-3 py-2 border border-border focus:border-accent focus:outline-none"
+                          >
+                            <option value="">Any video codec</option>
+                            <option value="h264">H.264</option>
+                            <option value="hevc">HEVC</option>
+                            <option value="vp9">VP9</option>
+                            <option value="av1">AV1</option>
+                          </select>
+                          <select
+                            value={config.preferred_acodec ?? ''}
+                            onChange={async (e) => {
+                              const value = (e.target.value || null) as AppConfig['preferred_acodec'];
+                              await onSave({ preferred_acodec: value });
+                            }}
+                            className="w-full bg-bg-secondary text-text-primary text-sm rounded-lg px-3 py-2 border border-border focus:border-accent focus:outline-none"
+                          >
+                            <option value="">Any audio codec</option>
+                            <option value="aac">AAC</option>
+                            <option value="opus">Opus</option>
+                            <option value="mp3">MP3</option>
+                          </select>
+                        </div>
+                        <p className="text-xs text-text-tertiary">
+                          Prefer these codecs when available, e.g. H.264 for devices that can't play AV1. Falls back to any codec otherwise.
+                        </p>
+                      </div>
+                    </div>
+
                   </div>
                 ) : ytdlpStatus?.status === 'not_installed' ? (
                   <p className="text-sm text-text-tertiary">
@@ -554,6 +725,28 @@ export function Settings({ isOpen, onClose, config, onSave }: SettingsProps) {
 
               {/* Network Interface */}
               <section className="pt-4 border-t border-border">
+                {/* Offline toggle */}
+                <div className="flex items-center justify-between px-4 py-3 bg-bg-tertiary rounded-lg mb-4">
+                  <div>
+                    <p className="text-sm text-text-primary">Offline mode</p>
+                    <p className="text-xs text-text-tertiary mt-0.5">Never contact the network; use installed tools and models only</p>
+                  </div>
+                  <button
+                    onClick={handleOfflineToggle}
+                    className={cn(
+                      'relative w-11 h-6 rounded-full transition-colors',
+                      config.offline ? 'bg-accent' : 'bg-bg-secondary'
+                    )}
+                  >
+                    <span
+                      className={cn(
+                        'absolute top-1 w-4 h-4 rounded-full bg-white transition-transform',
+                        config.offline ? 'left-6' : 'left-1'
+                      )}
+                    />
+                  </button>
+                </div>
+
                 <label className="block text-sm font-medium text-text-primary mb-2">
                   Network Interface
                 </label>
@@ -678,12 +871,54 @@ export function Settings({ isOpen, onClose, config, onSave }: SettingsProps) {
                   </button>
                 </div>
 
+                <label className="flex items-center gap-2 mb-1 px-1 text-sm text-text-primary">
+                  <input
+                    type="checkbox"
+                    checked={config.embed_native_subs}
+                    onChange={async (e) => {
+                      await onSave({ embed_native_subs: e.target.checked });
+                    }}
+                  />
+                  Embed the site's own subtitles
+                </label>
+                <p className="mb-3 px-1 text-xs text-text-tertiary">
+                  Uses the uploader's subtitles when the site has them, independent of auto-transcription.
+                </p>
+
                 {/* ffmpeg warning */}
-                {hasFfmpeg === false && (
+                {ffmpegInfo && !ffmpegInfo.found && (
                   <div className="px-4 py-3 bg-warning/10 border border-warning/20 rounded-lg mb-3">
                     <p className="text-sm text-warning">
-                      ffmpeg is required for subtitle generation but was not found. Please install ffmpeg.
+                      ffmpeg is required for subtitle generation but was not found.
                     </p>
+                    {ffmpegInfo.install_hint && (
+                      <p className="text-xs text-text-secondary mt-1">{ffmpegInfo.install_hint}</p>
+                    )}
+                    <button
+                      onClick={handleInstallFfmpeg}
+                      disabled={isInstallingFfmpeg}
+                      className={cn(
+                        'flex items-center gap-2 px-3 py-1.5 mt-2 text-sm font-medium rounded-lg transition-colors',
+                        'bg-accent text-white hover:bg-accent/90 disabled:opacity-50'
+                      )}
+                    >
+                      {isInstallingFfmpeg ? (
+                        <>
+                          <LoaderIcon className="w-4 h-4 animate-spin" />
+                          Installing...
+                        </>
+                      ) : (
+                        <>
+                          <DownloadIcon className="w-4 h-4" />
+                          Install ffmpeg
+                        </>
+                      )}
+                    </button>
+                    {isInstallingFfmpeg && ffmpegProgress && (
+                      <div className="mt-2">
+                        <ProgressBar percentage={ffmpegProgress.percentage} />
+                      </div>
+                    )}
                   </div>
                 )}
 
@@ -829,8 +1064,25 @@ export function Settings({ isOpen, onClose, config, onSave }: SettingsProps) {
                               {/* Progress bar for downloads */}
                               {(isDownloadingEngineModel?.engine === engine.id) && engineProgress && (
                                 <div className="pt-2">
-                                  <p className="text-xs text-text-tertiary mb-1">{engineProgress.stage}</p>
-                                  <ProgressBar percentage={engineProgress.percentage} />
+                                  <div className="flex justify-between text-xs text-text-tertiary mb-1">
+                                    <span>{engineProgress.stage}</span>
+                                    {engineProgress.downloaded > 0 && (
+                                      <span>
+                                        {formatBytes(engineProgress.downloaded)}
+                                        {engineProgress.total ? ` / ${formatBytes(engineProgress.total)}` : ''}
+                                      </span>
+                                    )}
+                                  </div>
+                                  <div className="flex items-center gap-2">
+                                    <ProgressBar percentage={engineProgress.percentage} className="flex-1" />
+                                    <button
+                                      onClick={handleCancelEngineModelDownload}
+                                      className="p-1 text-text-tertiary hover:text-error transition-colors"
+                                      title="Cancel download"
+                                    >
+                                      <XIcon className="w-3 h-3" />
+                                    </button>
+                                  </div>
                                 </div>
                               )}
 
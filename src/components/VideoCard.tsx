@@ -79,6 +79,9 @@ export function VideoCard({
     return engines.find(e => e.id === selectedEngine);
   }, [engines, selectedEngine]);
 
+  // Word style needs per-word timing; assume it's there until the engine list loads
+  const supportsWordTiming = currentEngine?.supports_word_timestamps ?? true;
+
   const installedModels = useMemo(() => {
     return currentEngine?.models.filter(m => m.installed) || [];
   }, [currentEngine]);
@@ -143,7 +146,7 @@ export function VideoCard({
       enabled: subtitlesEnabled,
       engine: selectedEngine,
       model: selectedModel,
-      style: selectedStyle,
+      style: supportsWordTiming ? selectedStyle : 'sentence',
     } : undefined;
     onDownload(`${selectedQuality}:${format}`, settings);
   };
@@ -297,12 +300,12 @@ export function VideoCard({
 
                     {/* Style selector */}
                     <select
-                      value={selectedStyle}
+                      value={supportsWordTiming ? selectedStyle : 'sentence'}
                       onChange={(e) => setSelectedStyle(e.target.value as 'word' | 'sentence')}
                       className="pill-glass px-2 py-1 text-xs rounded-md bg-transparent border-none cursor-pointer"
                     >
                       <option value="sentence" className="bg-bg-secondary">Sentence</option>
-                      <option value="word" className="bg-bg-secondary">Word</option>
+                      <option value="word" className="bg-bg-secondary" disabled={!supportsWordTiming}>Word</option>
                     </select>
 
                     {/* ETA */}
@@ -80,6 +80,9 @@ export function VideoPreview({
     return engines.find(e => e.id === selectedEngine);
   }, [engines, selectedEngine]);
 
+  // Word style needs per-word timing; assume it's there until the engine list loads
+  const supportsWordTiming = currentEngine?.supports_word_timestamps ?? true;
+
   const installedModels = useMemo(() => {
     return currentEngine?.models.filter(m => m.installed) || [];
   }, [currentEngine]);
@@ -450,7 +453,7 @@ export function VideoPreview({
                                           onClick={() => setSelectedStyle('sentence')}
                                           className={cn(
                                             'px-2 py-0.5 text-[11px] font-medium rounded transition-all',
-                                            selectedStyle === 'sentence'
+                                            (selectedStyle === 'sentence' || !supportsWordTiming)
                                               ? 'bg-accent text-white'
                                               : 'bg-bg-tertiary text-text-secondary hover:text-text-primary'
                                           )}
@@ -460,13 +463,17 @@ export function VideoPreview({
                                         </button>
                                         <button
                                           onClick={() => setSelectedStyle('word')}
+                                          disabled={!supportsWordTiming}
                                           className={cn(
                                             'px-2 py-0.5 text-[11px] font-medium rounded transition-all',
-                                            selectedStyle === 'word'
+                                            selectedStyle === 'word' && supportsWordTiming
                                               ? 'bg-accent text-white'
-                                              : 'bg-bg-tertiary text-text-secondary hover:text-text-primary'
+                                              : 'bg-bg-tertiary text-text-secondary hover:text-text-primary',
+                                            !supportsWordTiming && 'opacity-40 cursor-not-allowed'
                                           )}
-                                          title="One word per subtitle for karaoke-style timing"
+                                          title={supportsWordTiming
+                                            ? 'One word per subtitle for karaoke-style timing'
+                                            : 'This engine has no per-word timing'}
                                         >
                                           Word
                                         </button>
@@ -506,7 +513,7 @@ export function VideoPreview({
                 enabled: subtitlesEnabled,
                 engine: selectedEngine,
                 model: selectedModel,
-                style: selectedStyle,
+                style: supportsWordTiming ? selectedStyle : 'sentence',
               } : undefined;
               onDownload(`${selectedQuality}:${format}`, settings);
             }}
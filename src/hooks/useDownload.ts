@@ -4,6 +4,7 @@ import type { Download, VideoInfo, SubtitleSettings } from '@/lib/types';
 import {
   startDownload as apiStartDownload,
   cancelDownload as apiCancelDownload,
+  cancelAll as apiCancelAll,
   clearDownload as apiClearDownload,
   clearCompletedDownloads as apiClearCompleted,
   addLocalTranscription as apiAddLocalTranscription,
@@ -77,7 +78,10 @@ export function useDownload() {
   const startDownload = useCallback(async (
     videoInfo: VideoInfo,
     format: string,
-    subtitleSettings?: SubtitleSettings
+    subtitleSettings?: SubtitleSettings,
+    outputDir?: string | null,
+    overrideDurationGuard?: boolean,
+    forceDuplicate?: boolean
   ): Promise<string> => {
     const downloadId = await apiStartDownload(
       videoInfo.url,
@@ -85,7 +89,11 @@ export function useDownload() {
       videoInfo.title,
       videoInfo.thumbnail,
       subtitleSettings,
-      videoInfo.duration
+      videoInfo.duration,
+      null,
+      outputDir,
+      overrideDurationGuard,
+      forceDuplicate
     );
 
     // Optimistic update
@@ -106,10 +114,17 @@ export function useDownload() {
         duration: videoInfo.duration,
         whisper_model: subtitleSettings?.enabled ? subtitleSettings.model : null,
         transcription_engine: subtitleSettings?.enabled ? subtitleSettings.engine : null,
+        transcription_style: subtitleSettings?.enabled ? subtitleSettings.style : null,
+        transcription_language: subtitleSettings?.enabled ? subtitleSettings.language ?? null : null,
+        subtitle_format: subtitleSettings?.enabled && subtitleSettings.output_format === 'lrc' ? 'lrc' : null,
         transcription_progress: null,
         transcription_message: null,
         task_type: 'download',
         source_path: null,
+        in_place: true,
+        codec: null,
+        rtf: null,
+        output_dir: outputDir ?? null,
       });
       return next;
     });
@@ -129,6 +144,20 @@ export function useDownload() {
     });
   }, []);
 
+  const cancelAll = useCallback(async () => {
+    await apiCancelAll();
+    setDownloads(prev => {
+      const next = new Map(prev);
+      for (const [id, download] of next) {
+        const isQueued = download.task_type !== 'local_transcribe' && download.status === 'pending';
+        if (isQueued || download.status === 'downloading' || download.status === 'postprocessing' || download.status.startsWith('transcribing')) {
+          next.set(id, { ...download, status: 'cancelled' });
+        }
+      }
+      return next;
+    });
+  }, []);
+
   const clearDownload = useCallback(async (downloadId: string) => {
     await apiClearDownload(downloadId);
     setDownloads(prev => {
@@ -155,45 +184,57 @@ export function useDownload() {
     filePath: string,
     engine: string,
     model: string,
-    style: string
+    style: string,
+    language?: string | null,
+    outputDir?: string | null,
+    forceDuplicate?: boolean
   ): Promise<string> => {
     // Extract filename for title
     const parts = filePath.replace(/\\/g, '/').split('/');
     const title = parts[parts.length - 1];
 
-    const taskId = await apiAddLocalTranscription(filePath, title, engine, model, style);
+    const taskId = await apiAddLocalTranscription(filePath, title, engine, model, style, language, outputDir, null, forceDuplicate);
 
-    // Optimistic update
+    // Optimistic update, unless the backend's event (with thumbnail and duration) already arrived
     setDownloads(prev => {
       const next = new Map(prev);
-      next.set(taskId, {
-        id: taskId,
-        url: '',
-        title,
-        thumbnail: null,
-        status: 'pending',
-        progress: 0,
-        speed: null,
-        eta: null,
-        output_path: filePath,
-        format: '',
-        error: null,
-        duration: null,
-        whisper_model: model,
-        transcription_engine: engine,
-        transcription_progress: null,
-        transcription_message: null,
-        task_type: 'local_transcribe',
-        source_path: filePath,
-      });
+      if (!next.has(taskId)) {
+        next.set(taskId, {
+          id: taskId,
+          url: '',
+          title,
+          thumbnail: null,
+          status: 'pending',
+          progress: 0,
+          speed: null,
+          eta: null,
+          output_path: filePath,
+          format: '',
+          error: null,
+          duration: null,
+          whisper_model: model,
+          transcription_engine: engine,
+          transcription_style: style,
+          transcription_language: language ?? null,
+          subtitle_format: null,
+          transcription_progress: null,
+          transcription_message: null,
+          task_type: 'local_transcribe',
+          source_path: filePath,
+          in_place: false,
+          codec: null,
+          rtf: null,
+          output_dir: outputDir ?? null,
+        });
+      }
       return next;
     });
 
     return taskId;
   }, []);
 
-  const startLocalTranscription = useCallback(async (taskId: string) => {
-    await apiStartLocalTranscription(taskId);
+  const startLocalTranscription = useCallback(async (taskId: string, overrideDurationGuard?: boolean) => {
+    await apiStartLocalTranscription(taskId, overrideDurationGuard);
     setDownloads(prev => {
       const next = new Map(prev);
       const task = next.get(taskId);
@@ -206,9 +247,9 @@ export function useDownload() {
 
   const updateTranscriptionSettings = useCallback(async (
     taskId: string,
-    settings: { engine?: string; model?: string; style?: string }
+    settings: { engine?: string; model?: string; style?: string; language?: string }
   ) => {
-    await apiUpdateTranscriptionSettings(taskId, settings.engine, settings.model, settings.style);
+    await apiUpdateTranscriptionSettings(taskId, settings.engine, settings.model, settings.style, settings.language);
     setDownloads(prev => {
       const next = new Map(prev);
       const task = next.get(taskId);
@@ -217,6 +258,8 @@ export function useDownload() {
           ...task,
           transcription_engine: settings.engine ?? task.transcription_engine,
           whisper_model: settings.model ?? task.whisper_model,
+          transcription_style: settings.style ?? task.transcription_style,
+          transcription_language: settings.language ?? task.transcription_language,
         });
       }
       return next;
@@ -230,7 +273,7 @@ export function useDownload() {
     let hasError = false;
 
     for (const d of downloads.values()) {
-      const isDownloading = d.status === 'downloading' || (d.task_type === 'download' && d.status === 'pending');
+      const isDownloading = d.status === 'downloading' || d.status === 'postprocessing' || (d.task_type !== 'local_transcribe' && d.status === 'pending');
       const isTranscribing = d.status === 'transcribing' || d.status.startsWith('transcribing:');
 
       if (isDownloading) {
@@ -272,7 +315,7 @@ export function useDownload() {
   );
 
   const activeDownloads = useMemo(
-    () => downloadList.filter(d => d.status === 'downloading' || d.status === 'pending'),
+    () => downloadList.filter(d => d.status === 'downloading' || d.status === 'postprocessing' || d.status === 'pending'),
     [downloadList]
   );
 
@@ -293,6 +336,7 @@ export function useDownload() {
     isLoading,
     startDownload,
     cancelDownload,
+    cancelAll,
     clearDownload,
     clearCompleted,
     addLocalTranscription,
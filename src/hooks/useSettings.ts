@@ -11,9 +11,45 @@ const defaultConfig: AppConfig = {
   whisper_model: 'base',
   transcription_engine: 'whisper_rs',
   transcription_model: 'base',
+  engine_models: {},
   network_interface: null,
-  ytdlp_channel: 'nightly',
+  ytdlp_channel: 'stable',
   cookies_browser: null,
+  restrict_filenames: true,
+  embed_native_subs: false,
+  auth_username: null,
+  use_netrc: false,
+  preferred_vcodec: null,
+  preferred_acodec: null,
+  format_sort: null,
+  max_filesize: null,
+  completion_webhook: null,
+  completion_webhook_secret: null,
+  post_download_command: null,
+  min_confidence: null,
+  sherpa_decoding_method: null,
+  sherpa_max_active_paths: null,
+  sherpa_feature_dim: null,
+  max_cue_chars: null,
+  max_cue_duration_secs: null,
+  subtitle_offset_ms: 0,
+  karaoke: { font: 'Arial', font_size: 64, primary_color: '#FFFFFF', highlight_color: '#FFD700' },
+  offline: false,
+  model_mirror_base: null,
+  hf_token: null,
+  ytdlp_mirror_base: null,
+  proxy: null,
+  max_transcription_minutes: null,
+  download_retries: 3,
+  connect_timeout_secs: 15,
+  read_timeout_secs: 30,
+  fallback_models: ['large-v3', 'medium', 'small', 'base'],
+  subtitle_failure_policy: 'warn_but_keep',
+  diarize: false,
+  tighten_cues: false,
+  log_level: 'info',
+  module_log_levels: {},
+  normalize_audio: false,
 };
 
 let cachedConfig: AppConfig | null = null;
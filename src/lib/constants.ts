@@ -20,8 +20,11 @@ export const VIDEO_FORMATS = [
 export const AUDIO_FORMATS = [
   { id: 'original', label: 'Original' },
   { id: 'mp3', label: 'MP3' },
+  { id: 'mp3-320', label: 'MP3 320k' },
+  { id: 'mp3-128', label: 'MP3 128k' },
   { id: 'm4a', label: 'M4A' },
   { id: 'opus', label: 'Opus' },
+  { id: 'flac', label: 'FLAC' },
 ] as const;
 
 export const FORMAT_OPTIONS = [
@@ -61,6 +64,25 @@ export const TRANSCRIPTION_ENGINES = [
       { id: 'base', name: 'Base', size: '400 MB', speed_gpu: 30, speed_cpu: 10 },
     ],
   },
+  {
+    id: 'zipformer',
+    name: 'Zipformer',
+    description: 'Low-memory CPU engine for long recordings (8-20x realtime)',
+    gpu_required: false,
+    models: [
+      { id: 'small', name: 'Small (int8)', size: '90 MB', speed_gpu: 20, speed_cpu: 20 },
+      { id: 'gigaspeech', name: 'GigaSpeech (int8)', size: '210 MB', speed_gpu: 8, speed_cpu: 8 },
+    ],
+  },
+  {
+    id: 'canary',
+    name: 'Canary',
+    description: 'Highest-accuracy multilingual engine, slower (2-6x realtime)',
+    gpu_required: false,
+    models: [
+      { id: '180m-flash', name: '180M Flash (int8)', size: '210 MB', speed_gpu: 6, speed_cpu: 2 },
+    ],
+  },
 ] as const;
 
 // Get speed multiplier for an engine/model combination
@@ -1,14 +1,14 @@
 import { invoke } from '@tauri-apps/api/core';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import { open } from '@tauri-apps/plugin-dialog';
-import type { AppConfig, Download, VideoInfo, DenoStatus, YtDlpStatus, YtDlpInstallProgress, WhisperStatus, WhisperModel, WhisperInstallProgress, TranscribeProgress, SubtitleSettings, TranscriptionEngine, TranscriptionModel, TranscriptionInstallProgress, NetworkInterface } from './types';
+import type { AppConfig, LogLevel, Download, VideoInfo, DenoStatus, YtDlpStatus, YtDlpInstallProgress, WhisperStatus, FfmpegInfo, WhisperModel, WhisperInstallProgress, TranscribeProgress, SubtitleSettings, TranscriptionEngine, TranscriptionModel, TranscriptionInstallProgress, ModelQueueProgress, NetworkInterface, InterfaceTest, SizeEstimate, FormatDetails, AppStats, Diagnostics, GpuInfo, SubtitleCue, ClipRequest, UpdateAllProgress, UpdateSummary, ModelRepair } from './types';
 
 export async function checkYtdlp(): Promise<boolean> {
   return invoke<boolean>('check_ytdlp');
 }
 
-export async function getVideoInfo(url: string): Promise<VideoInfo> {
-  return invoke<VideoInfo>('get_video_info', { url });
+export async function getVideoInfo(url: string, refresh = false): Promise<VideoInfo> {
+  return invoke<VideoInfo>('get_video_info', { url, refresh });
 }
 
 export async function startDownload(
@@ -18,18 +18,113 @@ export async function startDownload(
   thumbnail: string | null,
   subtitleSettings?: SubtitleSettings | null,
   duration?: number | null,
+  outputContainer?: 'mp4' | 'mkv' | 'webm' | 'mov' | null,
+  outputDir?: string | null,
+  overrideDurationGuard?: boolean,
+  forceDuplicate?: boolean,
 ): Promise<string> {
-  return invoke<string>('start_download', { url, format, title, thumbnail, subtitleSettings, duration });
+  return invoke<string>('start_download', {
+    request: {
+      url,
+      format,
+      title,
+      thumbnail,
+      subtitle_settings: subtitleSettings,
+      duration,
+      output_container: outputContainer,
+      output_dir: outputDir,
+      override_duration_guard: overrideDurationGuard,
+      force_duplicate: forceDuplicate,
+    },
+  });
+}
+
+export async function getFormatDetails(url: string): Promise<FormatDetails> {
+  return invoke<FormatDetails>('get_format_details', { url });
+}
+
+export async function estimateDownloadSize(url: string, format: string): Promise<SizeEstimate | null> {
+  return invoke<SizeEstimate | null>('estimate_download_size', { url, format });
+}
+
+export async function previewDownloadCommand(
+  url: string,
+  format: string,
+  outputContainer?: 'mp4' | 'mkv' | 'webm' | 'mov' | null,
+): Promise<string[]> {
+  return invoke<string[]>('preview_download_command', { url, format, outputContainer });
 }
 
 export async function cancelDownload(downloadId: string): Promise<void> {
   return invoke('cancel_download', { downloadId });
 }
 
+export async function cancelAll(): Promise<number> {
+  return invoke('cancel_all');
+}
+
 export async function getDownloads(): Promise<Download[]> {
   return invoke<Download[]>('get_downloads');
 }
 
+export async function getDownloadLog(downloadId: string): Promise<string[]> {
+  return invoke<string[]>('get_download_log', { downloadId });
+}
+
+export async function getAppStats(includeDiskUsage = false): Promise<AppStats> {
+  return invoke<AppStats>('get_app_stats', { includeDiskUsage });
+}
+
+export async function getSubtitlePreview(taskId: string, maxLines?: number): Promise<SubtitleCue[]> {
+  return invoke<SubtitleCue[]>('get_subtitle_preview', { taskId, maxLines });
+}
+
+export async function copyToClipboard(text: string): Promise<void> {
+  return invoke('copy_to_clipboard', { text });
+}
+
+// Both fail until the task has completed
+export async function copyOutputPath(taskId: string): Promise<void> {
+  return invoke('copy_output_path', { taskId });
+}
+
+export async function copyTranscript(taskId: string): Promise<void> {
+  return invoke('copy_transcript', { taskId });
+}
+
+export async function updateSubtitles(taskId: string, cues: SubtitleCue[]): Promise<void> {
+  return invoke('update_subtitles', { taskId, cues });
+}
+
+export async function createClips(taskId: string, clips: ClipRequest[]): Promise<string[]> {
+  return invoke<string[]>('create_clips', { taskId, clips });
+}
+
+export async function remux(taskId: string, targetContainer: 'mp4' | 'mkv' | 'webm' | 'mov' | 'm4v'): Promise<string> {
+  return invoke<string>('remux', { taskId, targetContainer });
+}
+
+// Returns the path of the new .karaoke.mp4; needs a Word-style transcription
+export async function burnKaraoke(taskId: string): Promise<string> {
+  return invoke<string>('burn_karaoke', { taskId });
+}
+
+export async function setLogLevel(level: LogLevel): Promise<void> {
+  return invoke('set_log_level', { level });
+}
+
+export async function setAuthPassword(password: string | null): Promise<void> {
+  return invoke('set_auth_password', { password });
+}
+
+export async function getDiagnostics(): Promise<Diagnostics> {
+  return invoke<Diagnostics>('get_diagnostics');
+}
+
+export async function refreshGpuStatus(): Promise<GpuInfo> {
+  return invoke<GpuInfo>('refresh_gpu_status');
+}
+
 export async function clearDownload(downloadId: string): Promise<void> {
   return invoke('clear_download', { downloadId });
 }
@@ -63,6 +158,14 @@ export async function openFolder(path: string): Promise<void> {
   return invoke('open_folder', { path });
 }
 
+export async function openModelsDir(): Promise<void> {
+  return invoke('open_models_dir');
+}
+
+export async function openConfigDir(): Promise<void> {
+  return invoke('open_config_dir');
+}
+
 export async function getFormatPresets(): Promise<Record<string, string>> {
   return invoke<Record<string, string>>('get_format_presets');
 }
@@ -123,6 +226,52 @@ export function onDenoInstallProgress(
   });
 }
 
+// ffmpeg manager functions
+
+export async function installFfmpeg(): Promise<string> {
+  return invoke<string>('install_ffmpeg');
+}
+
+export function onFfmpegInstallProgress(
+  callback: (progress: YtDlpInstallProgress) => void
+): Promise<UnlistenFn> {
+  return listen<YtDlpInstallProgress>('ffmpeg-install-progress', (event) => {
+    callback(event.payload);
+  });
+}
+
+// Bulk update functions
+
+export async function updateAll(): Promise<UpdateSummary> {
+  return invoke<UpdateSummary>('update_all');
+}
+
+export function onUpdateAllProgress(
+  callback: (progress: UpdateAllProgress) => void
+): Promise<UnlistenFn> {
+  return listen<UpdateAllProgress>('update-all-progress', (event) => {
+    callback(event.payload);
+  });
+}
+
+// Speaker diarization functions
+
+export async function getDiarizationStatus(): Promise<boolean> {
+  return invoke<boolean>('get_diarization_status');
+}
+
+export async function installDiarization(): Promise<void> {
+  return invoke('install_diarization');
+}
+
+export function onDiarizationInstallProgress(
+  callback: (progress: TranscriptionInstallProgress) => void
+): Promise<UnlistenFn> {
+  return listen<TranscriptionInstallProgress>('diarization-install-progress', (event) => {
+    callback(event.payload);
+  });
+}
+
 // Whisper manager functions
 
 export async function getWhisperStatus(): Promise<WhisperStatus> {
@@ -145,6 +294,10 @@ export async function checkFfmpeg(): Promise<boolean> {
   return invoke<boolean>('check_ffmpeg');
 }
 
+export async function getFfmpegInfo(): Promise<FfmpegInfo> {
+  return invoke<FfmpegInfo>('get_ffmpeg_info');
+}
+
 export function onWhisperInstallProgress(
   callback: (progress: WhisperInstallProgress) => void
 ): Promise<UnlistenFn> {
@@ -179,6 +332,33 @@ export async function downloadTranscriptionModel(engineId: string, modelId: stri
   return invoke<void>('download_transcription_model', { engineId, modelId });
 }
 
+// `source` is a task id or a file path; only the first `seconds` (default 60) are transcribed
+export async function previewTranscription(
+  source: string,
+  engine: string,
+  model: string,
+  language?: string | null,
+  seconds?: number
+): Promise<string> {
+  return invoke<string>('preview_transcription', { source, engine, model, language, seconds });
+}
+
+export async function shiftSubtitles(path: string, offsetMs: number): Promise<string> {
+  return invoke<string>('shift_subtitles', { path, offsetMs });
+}
+
+export async function repairModel(engineId: string, modelId: string): Promise<ModelRepair> {
+  return invoke<ModelRepair>('repair_model', { engineId, modelId });
+}
+
+export async function downloadModels(requests: [string, string][]): Promise<void> {
+  return invoke<void>('download_models', { requests });
+}
+
+export async function cancelModelDownload(engineId: string, modelId?: string): Promise<void> {
+  return invoke<void>('cancel_model_download', { engineId, modelId: modelId ?? null });
+}
+
 export async function getTranscriptionSpeedMultiplier(
   engineId: string,
   modelId: string,
@@ -195,16 +375,27 @@ export function onTranscriptionInstallProgress(
   });
 }
 
+export function onModelQueueProgress(
+  callback: (progress: ModelQueueProgress) => void
+): Promise<UnlistenFn> {
+  return listen<ModelQueueProgress>('model-queue-progress', (event) => {
+    callback(event.payload);
+  });
+}
+
 // Local file transcription functions (unified with downloads system)
 
 export async function selectVideoFile(): Promise<string | null> {
   const selected = await open({
     multiple: false,
-    title: 'Select Video File',
+    title: 'Select Video or Audio File',
     filters: [
       {
-        name: 'Video Files',
-        extensions: ['mp4', 'mkv', 'webm', 'mov', 'avi', 'm4v', 'wmv', 'flv'],
+        name: 'Media Files',
+        extensions: [
+          'mp4', 'mkv', 'webm', 'mov', 'avi', 'm4v', 'wmv', 'flv',
+          'mp3', 'm4a', 'aac', 'ogg', 'opus', 'wav', 'flac',
+        ],
       },
     ],
   });
@@ -216,22 +407,63 @@ export async function addLocalTranscription(
   title: string,
   engine: string,
   model: string,
-  style: string
+  style: string,
+  language?: string | null,
+  outputDir?: string | null,
+  outputFormat?: 'srt' | 'lrc' | null,
+  forceDuplicate?: boolean
 ): Promise<string> {
-  return invoke<string>('add_local_transcription', { filePath, title, engine, model, style });
+  return invoke<string>('add_local_transcription', {
+    request: {
+      file_path: filePath,
+      title,
+      engine,
+      model,
+      style,
+      language,
+      output_dir: outputDir,
+      output_format: outputFormat,
+      force_duplicate: forceDuplicate,
+    },
+  });
 }
 
-export async function startLocalTranscription(taskId: string): Promise<void> {
-  return invoke('start_local_transcription', { taskId });
+export async function startLocalTranscription(taskId: string, overrideDurationGuard?: boolean): Promise<void> {
+  return invoke('start_local_transcription', { taskId, overrideDurationGuard });
+}
+
+export async function startRemoteTranscription(
+  url: string,
+  engine: string,
+  model: string,
+  style: string,
+  language?: string | null,
+  outputDir?: string | null,
+  outputFormat?: 'srt' | 'lrc' | null,
+  overrideDurationGuard?: boolean
+): Promise<string> {
+  return invoke<string>('start_remote_transcription', {
+    request: {
+      url,
+      engine,
+      model,
+      style,
+      language,
+      output_dir: outputDir,
+      output_format: outputFormat,
+      override_duration_guard: overrideDurationGuard,
+    },
+  });
 }
 
 export async function updateTranscriptionSettings(
   taskId: string,
   engine?: string,
   model?: string,
-  style?: string
+  style?: string,
+  language?: string
 ): Promise<void> {
-  return invoke('update_transcription_settings', { taskId, engine, model, style });
+  return invoke('update_transcription_settings', { taskId, engine, model, style, language });
 }
 
 // Network interface functions
@@ -239,3 +471,7 @@ export async function updateTranscriptionSettings(
 export async function listNetworkInterfaces(): Promise<NetworkInterface[]> {
   return invoke<NetworkInterface[]>('list_network_interfaces');
 }
+
+export async function testInterface(ipv4: string): Promise<InterfaceTest> {
+  return invoke<InterfaceTest>('test_interface', { ipv4 });
+}
@@ -12,6 +12,62 @@ export interface VideoFormat {
   vbr: number | null;
 }
 
+export interface FormatDetails {
+  video_only: VideoFormat[];
+  audio_only: VideoFormat[];
+  combined: VideoFormat[];
+}
+
+export interface SizeEstimate {
+  bytes: number;
+  human: string;
+}
+
+export interface DiskUsage {
+  models: Record<string, number>; // Bytes per engine models directory
+  binaries: number;
+  total: number;
+}
+
+export interface AppStats {
+  by_status: Record<string, number>;
+  totals: {
+    completed: number;
+    failed: number;
+    downloaded_bytes: number;
+    transcribed_minutes: number;
+  };
+  disk_usage: DiskUsage | null;
+}
+
+// Cue read back from a finished task's subtitles
+export interface SubtitleCue {
+  index: number;
+  start: number; // ms
+  end: number;   // ms
+  text: string;
+}
+
+// Toolchain report from get_diagnostics
+export interface Diagnostics {
+  app_version: string;
+  os: string;
+  arch: string;
+  offline: boolean;
+  ytdlp: YtDlpStatus;
+  deno: DenoStatus;
+  whisper: WhisperStatus;
+  sherpa_installed: boolean;
+  ffmpeg: FfmpegInfo;
+  gpu: GpuInfo;
+  engines: TranscriptionEngine[];
+}
+
+export interface GpuInfo {
+  vendor: 'nvidia' | 'apple' | null;
+  available: boolean;
+}
+
 export interface VideoInfo {
   id: string;
   title: string;
@@ -30,6 +86,8 @@ export interface SubtitleSettings {
   engine: string;  // "whisper_rs", "moonshine"
   model: string;
   style: 'word' | 'sentence';  // "word" = one word per subtitle, "sentence" = natural groupings
+  language?: string | null;  // Source language code; null or "auto" auto-detects
+  output_format?: 'srt' | 'lrc' | null;  // "lrc" writes synced lyrics next to audio downloads
 }
 
 export interface Download {
@@ -37,7 +95,7 @@ export interface Download {
   url: string;
   title: string;
   thumbnail: string | null;
-  status: 'pending' | 'downloading' | 'completed' | 'error' | 'cancelled' | 'transcribing' | string;
+  status: 'pending' | 'downloading' | 'postprocessing' | 'completed' | 'error' | 'cancelled' | 'transcribing' | string;
   progress: number;
   speed: string | null;
   eta: string | null;
@@ -47,12 +105,21 @@ export interface Download {
   duration: number | null;
   whisper_model: string | null;
   transcription_engine: string | null;
+  transcription_style: string | null;
+  transcription_language: string | null;
+  subtitle_format: 'lrc' | null; // null means SRT
   transcription_progress: number | null;
   transcription_message: string | null;
-  task_type: 'download' | 'local_transcribe';
+  task_type: 'download' | 'local_transcribe' | 'clip';
   source_path: string | null;
+  in_place: boolean;
+  codec: string | null;
+  output_dir: string | null;  // Per-task folder override
+  rtf: number | null;  // Measured transcription seconds per second of audio; < 1 is faster than realtime
 }
 
+export type LogLevel = 'off' | 'error' | 'warn' | 'info' | 'debug' | 'trace';
+
 export interface AppConfig {
   output_dir: string;
   default_format: string;
@@ -62,9 +129,45 @@ export interface AppConfig {
   whisper_model: string;
   transcription_engine: string;
   transcription_model: string;
+  engine_models: Record<string, string>; // Engine id -> last model used with it
   network_interface: string | null; // IPv4 address or null for any interface
   ytdlp_channel: 'stable' | 'nightly' | 'master';
   cookies_browser: string | null;
+  restrict_filenames: boolean; // ASCII-only output names
+  embed_native_subs: boolean; // yt-dlp embeds the site's own subtitles while merging
+  auth_username: string | null;
+  use_netrc: boolean;
+  preferred_vcodec: 'h264' | 'hevc' | 'vp9' | 'av1' | null;
+  preferred_acodec: 'aac' | 'opus' | 'mp3' | null;
+  format_sort: string[] | null; // yt-dlp --format-sort fields, e.g. ["res:1080", "fps"]
+  max_filesize: string | null; // yt-dlp --max-filesize, e.g. "200M"
+  completion_webhook: string | null;
+  completion_webhook_secret: string | null;
+  post_download_command: string | null; // Shell command run after each finished task; {path}, {title}, {status}
+  min_confidence: number | null; // 0-1, whisper_rs drops segments below this
+  sherpa_decoding_method: 'greedy_search' | 'modified_beam_search' | null; // Zipformer only
+  sherpa_max_active_paths: number | null; // Beam width for modified_beam_search
+  sherpa_feature_dim: number | null; // Fbank feature dimension, sherpa's default is 80; Zipformer only
+  max_cue_chars: number | null;
+  max_cue_duration_secs: number | null;
+  subtitle_offset_ms: number; // Added to every generated cue; negative shows subtitles earlier
+  karaoke: KaraokeStyle; // Font and colors of burned-in karaoke subtitles
+  offline: boolean; // Never contact the network
+  model_mirror_base: string | null; // Replaces the HuggingFace/GitHub host for model downloads
+  hf_token: string | null; // Bearer token for gated HuggingFace models; only sent to huggingface.co
+  ytdlp_mirror_base: string | null;
+  proxy: string | null; // http(s) proxy for yt-dlp and model/binary downloads
+  max_transcription_minutes: number | null; // Longer jobs ask for confirmation first
+  download_retries: number;
+  connect_timeout_secs: number;
+  read_timeout_secs: number; // Model/binary downloads with no data for this long fail as stalled
+  fallback_models: string[]; // Largest first; retried in order on out-of-memory
+  subtitle_failure_policy: 'warn_but_keep' | 'fail_task' | 'retry_once';
+  diarize: boolean; // Prefix cues with "Speaker N:" when several voices are detected
+  tighten_cues: boolean; // Snap cue edges to speech (whisper_rs only)
+  log_level: LogLevel;
+  module_log_levels: Record<string, LogLevel>; // e.g. { "zinc::transcription": "debug" }; applied on restart
+  normalize_audio: boolean; // Loudness-normalize audio before transcribing; slower
 }
 
 export interface NetworkInterface {
@@ -74,6 +177,19 @@ export interface NetworkInterface {
   is_up: boolean;       // Connection status
 }
 
+export interface KaraokeStyle {
+  font: string;
+  font_size: number;       // At 1080 lines; scaled with the video
+  primary_color: string;   // "#RRGGBB" for words not yet spoken
+  highlight_color: string; // "#RRGGBB" for the current and already spoken words
+}
+
+export interface InterfaceTest {
+  local_address: string; // The address requests were bound to
+  egress_ip: string;     // Public address the echo service saw
+  latency_ms: number;
+}
+
 // Transcription engine types
 // Rust serde serializes unit variants as strings and struct variants as objects
 export type EngineStatus =
@@ -99,6 +215,9 @@ export interface TranscriptionEngine {
   gpu_available: boolean;
   languages: string[];
   models: TranscriptionModel[];
+  supports_word_timestamps: boolean; // "word" style gives real per-word timing
+  supports_translation: boolean; // Models can translate speech to English
+  multilingual: boolean; // More than one supported language
 }
 
 export interface TranscriptionInstallProgress {
@@ -108,7 +227,38 @@ export interface TranscriptionInstallProgress {
   stage: string;
 }
 
-export type FormatPreset = 'best' | '4k' | '2k' | '1080p' | '720p' | '480p' | 'audio' | 'mp3';
+export interface ModelQueueProgress {
+  engine_id: string;
+  model_id: string;
+  index: number;  // 0-based position of the current model
+  total: number;
+  model_percentage: number;
+  overall_percentage: number;
+  stage: string;
+}
+
+export interface ClipRequest {
+  start: number;  // Seconds
+  end: number;
+  name: string;  // Saved as <stem>_<name>.<ext> next to the full download
+}
+
+export interface UpdateAllProgress {
+  tool: 'yt-dlp' | 'deno' | 'whisper' | 'sherpa-onnx';
+  step: number;  // 1-based
+  total_steps: number;
+  percentage: number;
+  stage: string;
+}
+
+export interface UpdateSummary {
+  updated: string[];  // "tool version"
+  up_to_date: string[];
+  not_installed: string[];
+  failed: string[];  // "tool: error"
+}
+
+export type FormatPreset = 'best' | '4k' | '2k' | '1080p' | '720p' | '480p' | 'audio' | 'mp3' | 'mp3-320' | 'mp3-128' | 'm4a' | 'opus' | 'flac';
 
 export interface Toast {
   id: string;
@@ -160,3 +310,32 @@ export interface TranscribeProgress {
   message: string;
 }
 
+export type AppErrorCode =
+  | 'ffmpeg_missing'
+  | 'model_missing'
+  | 'network'
+  | 'auth_required'
+  | 'confirmation_required'
+  | 'output_unavailable'
+  | 'already_queued'
+  | 'cancelled'
+  | 'unsupported'
+  | 'other';
+
+// Structured error returned by commands that use AppError on the Rust side
+export interface AppError {
+  code: AppErrorCode;
+  message: string;
+}
+
+export interface FfmpegInfo {
+  found: boolean;
+  path: string | null;
+  version: string | null;
+  install_hint: string | null;
+}
+
+export interface ModelRepair {
+  removed: string[]; // Paths deleted before the fresh download
+  downloaded: string; // Model id fetched again
+}
@@ -1,3 +1,5 @@
+import type { AppError, AppErrorCode } from './types';
+
 export function formatDuration(seconds: number | null): string {
   if (seconds === null) return '--:--';
 
@@ -101,3 +103,19 @@ export function truncate(str: string, length: number): string {
   if (str.length <= length) return str;
   return str.slice(0, length - 1) + '\u2026';
 }
+
+function isAppError(err: unknown): err is AppError {
+  return typeof err === 'object' && err !== null && 'code' in err && 'message' in err;
+}
+
+// Commands reject with an AppError object, a plain string, or an Error
+export function getErrorMessage(err: unknown, fallback: string): string {
+  if (isAppError(err)) return err.message;
+  if (err instanceof Error) return err.message;
+  if (typeof err === 'string' && err) return err;
+  return fallback;
+}
+
+export function getErrorCode(err: unknown): AppErrorCode | null {
+  return isAppError(err) ? err.code : null;
+}